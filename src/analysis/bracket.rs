@@ -0,0 +1,376 @@
+//Estimating which Commander bracket a deck belongs in, from the things the
+//brackets are defined by: game changers, mass land denial, extra turns, and
+//two-card combos. Whether a deck is bracket 1 or 2, or 4 or 5, comes down to
+//how it's meant to be played, so those aren't told apart.
+
+use std::{fmt::Display, io::ErrorKind, path::Path};
+
+use crate::{
+    analysis::deck_cards,
+    data_model::{card::Card, deck::Deck},
+    dbs::{allcards::AllCardsDb, folders::save_directory},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Bracket {
+    Exhibition = 1,
+    Core = 2,
+    Upgraded = 3,
+    Optimized = 4,
+    Cedh = 5,
+}
+
+impl Bracket {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Bracket::Exhibition => "Exhibition",
+            Bracket::Core => "Core",
+            Bracket::Upgraded => "Upgraded",
+            Bracket::Optimized => "Optimized",
+            Bracket::Cedh => "cEDH",
+        }
+    }
+}
+
+impl Display for Bracket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bracket {} ({})", *self as u8, self.name())
+    }
+}
+
+/// Bracket 3 allows up to this many game changers
+pub const MAX_UPGRADED_GAME_CHANGERS: usize = 3;
+
+/// This many extra turn cards is taken to mean they're meant to be chained
+pub const EXTRA_TURN_CHAIN: usize = 3;
+
+/// Combos whose two pieces cost this much or less together can go off before the late game
+pub const EARLY_COMBO_MANA_VALUE: usize = 6;
+
+///
+/// A pair of cards which win, or lock the game, together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Combo {
+    pub first: String,
+    pub second: String,
+}
+
+///
+/// The two-card combos we know about. These come from a text file with one combo
+/// per line, as `Card A | Card B`. Blank lines and lines starting with `#` are skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ComboList {
+    pub combos: Vec<Combo>,
+}
+
+impl ComboList {
+    pub fn parse(text: &str) -> Self {
+        let combos = text
+            .lines()
+            .map(str::trim)
+            .filter(|x| !x.is_empty() && !x.starts_with('#'))
+            .filter_map(|line| {
+                let (first, second) = line.split_once('|')?;
+                let (first, second) = (first.trim(), second.trim());
+                (!first.is_empty() && !second.is_empty()).then(|| Combo {
+                    first: first.to_string(),
+                    second: second.to_string(),
+                })
+            })
+            .collect();
+
+        Self { combos }
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        std::fs::read_to_string(path).map(|x| Self::parse(&x))
+    }
+
+    /// `combos.txt` in the save directory
+    pub fn open_in_save_directory() -> std::io::Result<Self> {
+        let dir = save_directory().ok_or(ErrorKind::NotFound)?;
+        Self::open(dir.join("combos.txt"))
+    }
+}
+
+///
+/// Something in the deck which puts it in a higher bracket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BracketReason {
+    GameChangers(Vec<String>),
+    MassLandDenial(Vec<String>),
+    /// Whether there are enough of them to chain
+    ExtraTurns { cards: Vec<String>, chained: bool },
+    TwoCardCombo { combo: Combo, early: bool },
+}
+
+impl BracketReason {
+    ///
+    /// The lowest bracket a deck with this in it can be in.
+    pub fn minimum_bracket(&self) -> Bracket {
+        match self {
+            BracketReason::GameChangers(cards) if cards.len() <= MAX_UPGRADED_GAME_CHANGERS => {
+                Bracket::Upgraded
+            }
+            BracketReason::GameChangers(_) => Bracket::Optimized,
+            BracketReason::MassLandDenial(_) => Bracket::Optimized,
+            BracketReason::ExtraTurns { chained: true, .. } => Bracket::Optimized,
+            BracketReason::ExtraTurns { chained: false, .. } => Bracket::Core,
+            BracketReason::TwoCardCombo { early: true, .. } => Bracket::Optimized,
+            BracketReason::TwoCardCombo { early: false, .. } => Bracket::Upgraded,
+        }
+    }
+}
+
+impl Display for BracketReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BracketReason::GameChangers(cards) => {
+                write!(f, "{} game changers: {}", cards.len(), cards.join(", "))
+            }
+            BracketReason::MassLandDenial(cards) => {
+                write!(f, "Mass land denial: {}", cards.join(", "))
+            }
+            BracketReason::ExtraTurns { cards, chained } => write!(
+                f,
+                "{} extra turn cards{}: {}",
+                cards.len(),
+                if *chained { ", enough to chain" } else { "" },
+                cards.join(", ")
+            ),
+            BracketReason::TwoCardCombo { combo, early } => write!(
+                f,
+                "{} combo: {} + {}",
+                if *early { "Early" } else { "Late-game" },
+                combo.first,
+                combo.second
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BracketEstimate {
+    pub bracket: Bracket,
+    pub reasons: Vec<BracketReason>,
+}
+
+///
+/// The lowest bracket the deck fits in, going by the played sections.
+/// A deck with nothing to push it up is estimated as Core, since Exhibition
+/// is about the deck's theme rather than what's in it.
+pub fn estimate_bracket(db: &AllCardsDb, deck: &Deck, combos: &ComboList) -> BracketEstimate {
+    let (deck_cards, _) = deck_cards(db, deck);
+    let mut cards: Vec<&Card> = Vec::new();
+    for deck_card in deck_cards.iter() {
+        if !cards.iter().any(|x| x.name == deck_card.card.name) {
+            cards.push(&deck_card.card);
+        }
+    }
+
+    let names = |f: fn(&Card) -> bool| -> Vec<String> {
+        cards
+            .iter()
+            .filter(|x| f(x))
+            .map(|x| x.name.clone())
+            .collect()
+    };
+
+    let mut reasons = Vec::new();
+
+    let game_changers = names(|x| x.game_changer);
+    if !game_changers.is_empty() {
+        reasons.push(BracketReason::GameChangers(game_changers));
+    }
+
+    let land_denial = names(|x| is_mass_land_denial(&x.oracle_text));
+    if !land_denial.is_empty() {
+        reasons.push(BracketReason::MassLandDenial(land_denial));
+    }
+
+    let extra_turns = names(|x| gives_extra_turn(&x.oracle_text));
+    if !extra_turns.is_empty() {
+        reasons.push(BracketReason::ExtraTurns {
+            chained: extra_turns.len() >= EXTRA_TURN_CHAIN,
+            cards: extra_turns,
+        });
+    }
+
+    for combo in combos.combos.iter() {
+        let find = |name: &str| cards.iter().find(|x| x.name.eq_ignore_ascii_case(name));
+        let (Some(first), Some(second)) = (find(&combo.first), find(&combo.second)) else {
+            continue;
+        };
+
+        let mana_value_times_4 = first.mana_value_times_4 + second.mana_value_times_4;
+        reasons.push(BracketReason::TwoCardCombo {
+            combo: combo.clone(),
+            early: mana_value_times_4 <= EARLY_COMBO_MANA_VALUE * 4,
+        });
+    }
+
+    let bracket = reasons
+        .iter()
+        .map(BracketReason::minimum_bracket)
+        .max()
+        .unwrap_or(Bracket::Core);
+
+    BracketEstimate { bracket, reasons }
+}
+
+//Armageddon, Jokulhaups, Winter Orb and their like
+fn is_mass_land_denial(oracle_text: &str) -> bool {
+    let text = oracle_text.to_ascii_lowercase();
+
+    text.split(['.', '\n']).any(|sentence| {
+        let hits_all = ["destroy all", "exile all", "return all", "sacrifices all", "sacrifice all"]
+            .iter()
+            .any(|x| sentence.contains(x));
+        let stops_untapping = sentence.contains("can't untap more than")
+            || (sentence.contains("lands") && sentence.contains("don't untap"));
+
+        (hits_all && sentence.contains("lands")) || stops_untapping
+    })
+}
+
+fn gives_extra_turn(oracle_text: &str) -> bool {
+    let text = oracle_text.to_ascii_lowercase();
+    text.contains("take an extra turn") || text.contains("takes an extra turn")
+}
+
+#[cfg(test)]
+#[test]
+fn test_combo_list_parse() {
+    let list = ComboList::parse(
+        "# infinite mana\nDramatic Reversal | Isochron Scepter\n\nThassa's Oracle|Demonic Consultation\nnot a combo\n",
+    );
+    assert_eq!(
+        list.combos,
+        vec![
+            Combo {
+                first: "Dramatic Reversal".to_string(),
+                second: "Isochron Scepter".to_string()
+            },
+            Combo {
+                first: "Thassa's Oracle".to_string(),
+                second: "Demonic Consultation".to_string()
+            },
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_land_denial_and_extra_turns() {
+    assert!(is_mass_land_denial("Destroy all lands."));
+    assert!(is_mass_land_denial("Destroy all artifacts, creatures, and lands. They can't be regenerated."));
+    assert!(is_mass_land_denial(
+        "As long as Winter Orb is untapped, players can't untap more than one land during their untap steps."
+    ));
+    assert!(!is_mass_land_denial("Destroy all creatures. Search your library for a basic land card."));
+
+    assert!(gives_extra_turn("Take an extra turn after this one. Exile Time Warp."));
+    assert!(!gives_extra_turn("Draw a card."));
+}
+
+#[cfg(test)]
+#[test]
+fn test_estimate_bracket() {
+    use crate::{data_model::deck::DeckSection, dbs::allcards::test_all_cards_db};
+
+    let card = |name: &str, mana_value: usize, oracle_text: &str, game_changer: bool| Card {
+        mana_value_times_4: mana_value * 4,
+        oracle_text: oracle_text.to_string(),
+        game_changer,
+        ..Card::named(name)
+    };
+    let extra_turn = "Take an extra turn after this one.";
+
+    let (db, refs) = test_all_cards_db(
+        "bracket-estimate",
+        vec![
+            card("Forest", 0, "", false),
+            card("Rhystic Study", 3, "", true),
+            card("Cyclonic Rift", 2, "", true),
+            card("Smothering Tithe", 4, "", true),
+            card("Demonic Tutor", 2, "", true),
+            card("Time Warp", 5, extra_turn, false),
+            card("Temporal Manipulation", 5, extra_turn, false),
+            card("Walk the Aeons", 6, extra_turn, false),
+            card("Dramatic Reversal", 2, "", false),
+            card("Isochron Scepter", 2, "", false),
+            card("Exquisite Blood", 5, "", false),
+            card("Sanguine Bond", 5, "", false),
+        ],
+    );
+    let combos = ComboList::parse(
+        "Dramatic Reversal | Isochron Scepter\nSanguine Bond | Exquisite Blood\n",
+    );
+    let estimate = |cards: &[usize]| {
+        let mut deck = Deck::new();
+        deck.add(DeckSection::Main, refs[0].clone(), 30);
+        for &i in cards {
+            deck.add(DeckSection::Main, refs[i].clone(), 1);
+        }
+        estimate_bracket(&db, &deck, &combos)
+    };
+
+    let plain = estimate(&[]);
+    assert_eq!(plain.bracket, Bracket::Core);
+    assert!(plain.reasons.is_empty());
+
+    //the maybeboard isn't part of the deck
+    let mut deck = Deck::new();
+    deck.add(DeckSection::Main, refs[0].clone(), 30);
+    deck.add(DeckSection::Maybe, refs[1].clone(), 1);
+    assert_eq!(estimate_bracket(&db, &deck, &combos).bracket, Bracket::Core);
+
+    for count in 1..=MAX_UPGRADED_GAME_CHANGERS {
+        let game_changers = (1..=count).collect::<Vec<_>>();
+        assert_eq!(estimate(&game_changers).bracket, Bracket::Upgraded);
+    }
+    let four = estimate(&[1, 2, 3, 4]);
+    assert_eq!(four.bracket, Bracket::Optimized);
+    assert_eq!(
+        four.reasons,
+        [BracketReason::GameChangers(vec![
+            "Rhystic Study".to_string(),
+            "Cyclonic Rift".to_string(),
+            "Smothering Tithe".to_string(),
+            "Demonic Tutor".to_string(),
+        ])]
+    );
+
+    //a lone extra turn is fine, but enough of them to chain isn't
+    let one_turn = estimate(&[5]);
+    assert_eq!(one_turn.bracket, Bracket::Core);
+    assert_eq!(
+        one_turn.reasons,
+        [BracketReason::ExtraTurns {
+            cards: vec!["Time Warp".to_string()],
+            chained: false,
+        }]
+    );
+    assert_eq!(estimate(&[5, 6, 7]).bracket, Bracket::Optimized);
+
+    //both pieces have to be there, and cheap ones go off early
+    assert_eq!(estimate(&[8]).bracket, Bracket::Core);
+    let early = estimate(&[8, 9]);
+    assert_eq!(early.bracket, Bracket::Optimized);
+    assert_eq!(
+        early.reasons,
+        [BracketReason::TwoCardCombo {
+            combo: combos.combos[0].clone(),
+            early: true,
+        }]
+    );
+    let late = estimate(&[10, 11]);
+    assert_eq!(late.bracket, Bracket::Upgraded);
+    assert_eq!(
+        late.reasons,
+        [BracketReason::TwoCardCombo {
+            combo: combos.combos[1].clone(),
+            early: false,
+        }]
+    );
+}
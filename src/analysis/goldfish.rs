@@ -0,0 +1,670 @@
+//Playing a deck's first turns against nobody, to see how its mana base holds up.
+//Each game shuffles the library, draws a hand (mulliganing bad ones), and plays a land
+//a turn. Spells are never cast, so ramp doesn't count; what's measured is whether the
+//lands alone could pay for each card on the turn matching its mana value.
+//Games come from a seeded generator, so the same seed always gives the same report.
+
+use crate::{
+    analysis::{
+        DeckCard, deck_cards,
+        mana::{has_color, is_land, pips, produced_colors},
+        probability::{OPENING_HAND, PlayOrDraw},
+    },
+    data_model::{
+        card::{Card, Color, ColorCombination, ManaSymbol, Supertype},
+        deck::{Deck, DeckSection},
+    },
+    dbs::allcards::AllCardsDb,
+};
+
+///
+/// SplitMix64. It's small, fast, and good enough for shuffling.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; `n` must be above zero
+    pub fn below(&mut self, n: usize) -> usize {
+        //rejecting the top partial range keeps it unbiased
+        let n = n as u64;
+        let zone = u64::MAX - (u64::MAX % n);
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoldfishConfig {
+    pub seed: u64,
+    pub games: usize,
+    pub turns: usize,
+    pub play_or_draw: PlayOrDraw,
+    /// Hands with fewer than 2 or more than 5 lands are mulliganed, down to this many times
+    pub max_mulligans: usize,
+    /// Whether Phyrexian mana can be paid with life
+    pub pay_life: bool,
+}
+
+impl Default for GoldfishConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            games: 10_000,
+            turns: 6,
+            play_or_draw: PlayOrDraw::Play,
+            max_mulligans: 2,
+            pay_life: true,
+        }
+    }
+}
+
+///
+/// What mana a land makes, for paying costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LandMana {
+    /// With `colorless` set if it makes {C}
+    pub colors: ColorCombination,
+    pub snow: bool,
+    pub enters_tapped: bool,
+}
+
+impl LandMana {
+    pub fn of(card: &Card) -> Self {
+        let text = card.oracle_text.to_ascii_lowercase();
+        let enters_tapped = text.lines().any(|line| {
+            (line.contains("enters tapped") || line.contains("enters the battlefield tapped"))
+                && !line.contains("unless")
+        });
+
+        Self {
+            colors: produced_colors(card),
+            snow: card.super_types.contains(&Supertype::Snow),
+            enters_tapped,
+        }
+    }
+
+    fn makes(&self, kind: ManaKind) -> bool {
+        match kind {
+            ManaKind::Color(i) => has_color(&self.colors, i),
+            ManaKind::Colorless => self.colors.colorless,
+            ManaKind::Snow => self.snow,
+        }
+    }
+}
+
+///
+/// How often one card could be cast on curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardCastability {
+    pub name: String,
+    /// The turn the card would be cast on curve
+    pub turn: usize,
+    /// Games where the lands in play on that turn could pay for it, whether or not it was drawn
+    pub castable: f64,
+    /// Games where it was in hand on that turn, and the lands could pay for it
+    pub drawn_and_castable: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldfishReport {
+    pub games: usize,
+    pub average_mulligans: f64,
+    /// Average lands in play on each turn, from turn 1
+    pub lands_by_turn: Vec<f64>,
+    /// Average lands in play and untapped on each turn, from turn 1
+    pub mana_by_turn: Vec<f64>,
+    /// Nonland cards whose on-curve turn is within the simulated turns, in deck order
+    pub cards: Vec<CardCastability>,
+}
+
+struct SimCard {
+    /// Index into the deck's distinct cards
+    card: usize,
+    land: Option<LandMana>,
+    mana_value_times_4: usize,
+    pips: [usize; 5],
+}
+
+///
+/// The main deck's distinct cards, and a library with a `SimCard` for each copy of them.
+fn main_deck_library(db: &AllCardsDb, deck: &Deck) -> (Vec<DeckCard>, Vec<SimCard>) {
+    let (deck_cards, _) = deck_cards(db, deck);
+    let deck_cards: Vec<_> = deck_cards
+        .into_iter()
+        .filter(|x| x.section == DeckSection::Main)
+        .collect();
+
+    let mut library: Vec<SimCard> = Vec::new();
+    for (i, deck_card) in deck_cards.iter().enumerate() {
+        for _ in 0..deck_card.quantity {
+            library.push(SimCard {
+                card: i,
+                land: is_land(&deck_card.card).then(|| LandMana::of(&deck_card.card)),
+                mana_value_times_4: deck_card.card.mana_value_times_4,
+                pips: pips(&deck_card.card.mana_cost.0),
+            });
+        }
+    }
+
+    (deck_cards, library)
+}
+
+///
+/// An opening hand, after mulligans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleHand {
+    /// In the order they were drawn
+    pub cards: Vec<String>,
+    pub mulligans: usize,
+    /// Put on the bottom for the mulligans
+    pub bottomed: Vec<String>,
+}
+
+///
+/// Draws one opening hand the way `goldfish` does, mulliganing bad ones up to `max_mulligans` times.
+/// It's the same hand as the first game `goldfish` plays with the same seed.
+pub fn sample_hand(db: &AllCardsDb, deck: &Deck, seed: u64, max_mulligans: usize) -> SampleHand {
+    let (deck_cards, library) = main_deck_library(db, deck);
+
+    let mut order: Vec<usize> = (0..library.len()).collect();
+    let (hand, _, mulligans) = draw_opening_hand(&library, &mut order, &mut Rng::new(seed), max_mulligans);
+    let name = |i: &usize| deck_cards[library[*i].card].card.name.clone();
+    //draw_opening_hand moves them to the end of the library
+    let bottomed = OPENING_HAND.min(library.len()) - hand.len();
+
+    SampleHand {
+        cards: hand.iter().map(name).collect(),
+        mulligans,
+        bottomed: order[order.len() - bottomed..].iter().map(name).collect(),
+    }
+}
+
+pub fn goldfish(db: &AllCardsDb, deck: &Deck, config: GoldfishConfig) -> GoldfishReport {
+    let (deck_cards, library) = main_deck_library(db, deck);
+
+    //the turn each nonland card is on curve; zero-drops are on curve on turn 1
+    let on_curve: Vec<Option<usize>> = deck_cards
+        .iter()
+        .map(|x| {
+            let turn = x.card.mana_value_times_4.div_ceil(4).max(1);
+            (!is_land(&x.card) && turn <= config.turns).then_some(turn)
+        })
+        .collect();
+
+    let mut rng = Rng::new(config.seed);
+    let mut mulligans = 0;
+    let mut lands_by_turn = vec![0usize; config.turns];
+    let mut mana_by_turn = vec![0usize; config.turns];
+    let mut castable = vec![0usize; deck_cards.len()];
+    let mut drawn_and_castable = vec![0usize; deck_cards.len()];
+
+    let mut order: Vec<usize> = (0..library.len()).collect();
+    for _ in 0..config.games {
+        let (mut hand, mut next, taken) = draw_opening_hand(&library, &mut order, &mut rng, config.max_mulligans);
+        mulligans += taken;
+
+        //(land, the turn it came into play)
+        let mut battlefield: Vec<(LandMana, usize)> = Vec::new();
+        for turn in 1..=config.turns {
+            if (turn > 1 || config.play_or_draw == PlayOrDraw::Draw)
+                && let Some(i) = order.get(next)
+            {
+                hand.push(*i);
+                next += 1;
+            }
+
+            if let Some(land) = choose_land(&library, &hand, &battlefield) {
+                let i = hand.remove(land);
+                battlefield.push((library[i].land.unwrap(), turn));
+            }
+
+            let untapped: Vec<LandMana> = battlefield
+                .iter()
+                .filter(|(land, played)| !(land.enters_tapped && *played == turn))
+                .map(|x| x.0)
+                .collect();
+            lands_by_turn[turn - 1] += battlefield.len();
+            mana_by_turn[turn - 1] += untapped.len();
+
+            for (card, deck_card) in deck_cards.iter().enumerate() {
+                if on_curve[card] != Some(turn) || !can_pay(&deck_card.card.mana_cost.0, &untapped, config.pay_life) {
+                    continue;
+                }
+                castable[card] += 1;
+                if hand.iter().any(|x| library[*x].card == card) {
+                    drawn_and_castable[card] += 1;
+                }
+            }
+        }
+    }
+
+    let games = config.games.max(1) as f64;
+    GoldfishReport {
+        games: config.games,
+        average_mulligans: mulligans as f64 / games,
+        lands_by_turn: lands_by_turn.iter().map(|x| *x as f64 / games).collect(),
+        mana_by_turn: mana_by_turn.iter().map(|x| *x as f64 / games).collect(),
+        cards: deck_cards
+            .iter()
+            .enumerate()
+            .filter_map(|(i, deck_card)| {
+                Some(CardCastability {
+                    name: deck_card.card.name.clone(),
+                    turn: on_curve[i]?,
+                    castable: castable[i] as f64 / games,
+                    drawn_and_castable: drawn_and_castable[i] as f64 / games,
+                })
+            })
+            .collect(),
+    }
+}
+
+///
+/// Shuffles `order` and draws from it, with London mulligans. Returns the hand,
+/// the position of the next card to draw, and how many mulligans were taken.
+/// The cards put on the bottom are moved to the end of `order`.
+fn draw_opening_hand(
+    library: &[SimCard],
+    order: &mut [usize],
+    rng: &mut Rng,
+    max_mulligans: usize,
+) -> (Vec<usize>, usize, usize) {
+    let hand_size = OPENING_HAND.min(order.len());
+    let mut mulligans = 0;
+
+    loop {
+        rng.shuffle(order);
+        let lands = order[..hand_size]
+            .iter()
+            .filter(|x| library[**x].land.is_some())
+            .count();
+
+        if (2..=5).contains(&lands) || mulligans == max_mulligans {
+            break;
+        }
+        mulligans += 1;
+    }
+
+    //bottom lands when there are too many, otherwise the most expensive spells
+    let mut hand = order[..hand_size].to_vec();
+    let mut bottomed = Vec::new();
+    for _ in 0..mulligans.min(hand_size) {
+        let lands = hand.iter().filter(|x| library[**x].land.is_some()).count();
+        let i = if lands * 2 > hand.len() {
+            hand.iter().position(|x| library[*x].land.is_some()).unwrap()
+        } else {
+            (0..hand.len())
+                .filter(|i| library[hand[*i]].land.is_none())
+                .max_by_key(|i| library[hand[*i]].mana_value_times_4)
+                .unwrap_or(0)
+        };
+        bottomed.push(hand.remove(i));
+    }
+
+    //the rest of the library stays in order, with the bottomed cards after it
+    let rest: Vec<usize> = order[hand_size..].iter().copied().chain(bottomed).collect();
+    order[..hand.len()].copy_from_slice(&hand);
+    order[hand.len()..].copy_from_slice(&rest);
+
+    let next = hand.len();
+    (hand, next, mulligans)
+}
+
+///
+/// The land in hand worth playing: one that adds a color the hand needs
+/// and doesn't have yet, preferring lands that come in untapped.
+fn choose_land(library: &[SimCard], hand: &[usize], battlefield: &[(LandMana, usize)]) -> Option<usize> {
+    let mut needed = [0usize; 5];
+    for card in hand.iter().map(|x| &library[*x]).filter(|x| x.land.is_none()) {
+        for (need, pips) in needed.iter_mut().zip(card.pips) {
+            *need += pips;
+        }
+    }
+
+    (0..hand.len())
+        .filter_map(|i| Some((i, library[hand[i]].land?)))
+        .max_by_key(|(i, land)| {
+            let new_colors = (0..5)
+                .filter(|c| needed[*c] > 0 && has_color(&land.colors, *c))
+                .filter(|c| !battlefield.iter().any(|(x, _)| has_color(&x.colors, *c)))
+                .count();
+            //ties go to the earliest card in hand, so the choice doesn't depend on anything else
+            (new_colors, !land.enters_tapped, std::cmp::Reverse(*i))
+        })
+        .map(|(i, _)| i)
+}
+
+/// Something a single mana symbol can be paid with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ManaKind {
+    /// Indexed as in `mana::COLORS`
+    Color(usize),
+    Colorless,
+    Snow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Payment {
+    Mana(ManaKind),
+    Generic(usize),
+    Life,
+}
+
+fn color_kind(color: Color) -> ManaKind {
+    match color {
+        Color::White => ManaKind::Color(0),
+        Color::Blue => ManaKind::Color(1),
+        Color::Black => ManaKind::Color(2),
+        Color::Red => ManaKind::Color(3),
+        Color::Green => ManaKind::Color(4),
+        Color::Colorless => ManaKind::Colorless,
+    }
+}
+
+///
+/// The ways a symbol can be paid. None if it can't be paid at all, like {D} or
+/// one million generic mana. X is always taken to be zero.
+fn payment_options(symbol: &ManaSymbol, pay_life: bool) -> Option<Vec<Payment>> {
+    Some(match symbol {
+        ManaSymbol::Variable(_) => vec![Payment::Generic(0)],
+        ManaSymbol::GenericNumber(n) => vec![Payment::Generic(*n)],
+        ManaSymbol::Snow => vec![Payment::Mana(ManaKind::Snow)],
+        ManaSymbol::HalfWhite => vec![Payment::Mana(ManaKind::Color(0))],
+        ManaSymbol::ConventionalColored {
+            phyrexian,
+            split_two_generic,
+            color,
+            split_color,
+        } => {
+            let mut options = vec![Payment::Mana(color_kind(*color))];
+            if let Some(split_color) = split_color {
+                options.push(Payment::Mana(color_kind(*split_color)));
+            }
+            if *split_two_generic {
+                options.push(Payment::Generic(2));
+            }
+            if *phyrexian && pay_life {
+                options.push(Payment::Life);
+            }
+            options
+        }
+        ManaSymbol::LandDrop | ManaSymbol::Legendary | ManaSymbol::OneMillionGenericMana => {
+            return None;
+        }
+    })
+}
+
+///
+/// Whether the lands could pay the whole cost, each making one mana.
+pub fn can_pay(cost: &[ManaSymbol], lands: &[LandMana], pay_life: bool) -> bool {
+    let Some(options) = cost
+        .iter()
+        .map(|x| payment_options(x, pay_life))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return false;
+    };
+
+    //try each way of paying the symbols which have a choice
+    fn try_choices(options: &[Vec<Payment>], chosen: &mut Vec<Payment>, lands: &[LandMana]) -> bool {
+        let Some((first, rest)) = options.split_first() else {
+            return pays(chosen, lands);
+        };
+        first.iter().any(|payment| {
+            chosen.push(*payment);
+            let paid = try_choices(rest, chosen, lands);
+            chosen.pop();
+            paid
+        })
+    }
+
+    try_choices(&options, &mut Vec::new(), lands)
+}
+
+fn pays(payments: &[Payment], lands: &[LandMana]) -> bool {
+    let kinds: Vec<ManaKind> = payments
+        .iter()
+        .filter_map(|x| match x {
+            Payment::Mana(kind) => Some(*kind),
+            _ => None,
+        })
+        .collect();
+    let generic: usize = payments
+        .iter()
+        .map(|x| match x {
+            Payment::Generic(n) => *n,
+            _ => 0,
+        })
+        .sum();
+
+    if kinds.len() + generic > lands.len() {
+        return false;
+    }
+
+    //match each colored symbol to a different land that makes it
+    fn assign(kind: usize, kinds: &[ManaKind], lands: &[LandMana], seen: &mut [bool], used_by: &mut [Option<usize>]) -> bool {
+        for (land, mana) in lands.iter().enumerate() {
+            if seen[land] || !mana.makes(kinds[kind]) {
+                continue;
+            }
+            seen[land] = true;
+            let other = used_by[land];
+            if other.is_none_or(|other| assign(other, kinds, lands, seen, used_by)) {
+                used_by[land] = Some(kind);
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut used_by = vec![None; lands.len()];
+    (0..kinds.len()).all(|kind| assign(kind, &kinds, lands, &mut vec![false; lands.len()], &mut used_by))
+}
+
+#[cfg(test)]
+#[test]
+fn test_rng_is_seeded() {
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    let mut c = Rng::new(43);
+    let a: Vec<_> = (0..8).map(|_| a.next_u64()).collect();
+    let b: Vec<_> = (0..8).map(|_| b.next_u64()).collect();
+    let c: Vec<_> = (0..8).map(|_| c.next_u64()).collect();
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+
+    let mut items: Vec<usize> = (0..20).collect();
+    Rng::new(7).shuffle(&mut items);
+    let mut sorted = items.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+}
+
+#[cfg(test)]
+#[test]
+fn test_can_pay() {
+    use crate::color_combo;
+
+    let colored = |color, split_color, phyrexian| ManaSymbol::ConventionalColored {
+        phyrexian,
+        split_two_generic: false,
+        color,
+        split_color,
+    };
+    let land = |colors| LandMana {
+        colors,
+        snow: false,
+        enters_tapped: false,
+    };
+
+    let lands = [land(color_combo!(w)), land(color_combo!(u b))];
+
+    //{1}{W}{B}
+    let cost = [
+        ManaSymbol::GenericNumber(1),
+        colored(Color::White, None, false),
+        colored(Color::Black, None, false),
+    ];
+    assert!(!can_pay(&cost, &lands, true));
+    assert!(can_pay(&cost[1..], &lands, true));
+
+    //{W}{W} can't be paid by one white source
+    let cost = [colored(Color::White, None, false), colored(Color::White, None, false)];
+    assert!(!can_pay(&cost, &lands, true));
+
+    //{R/W}{U/B} is fine
+    let cost = [colored(Color::Red, Some(Color::White), false), colored(Color::Blue, Some(Color::Black), false)];
+    assert!(can_pay(&cost, &lands, true));
+
+    //{G/P} is only castable with life
+    let cost = [colored(Color::Green, None, true)];
+    assert!(can_pay(&cost, &lands, true));
+    assert!(!can_pay(&cost, &lands, false));
+}
+
+#[cfg(test)]
+#[test]
+fn test_sample_hand() {
+    use crate::dbs::allcards::test_all_cards_db;
+
+    let mountain = Card {
+        types: vec!["Land".to_string()],
+        subtypes: vec!["Mountain".to_string()],
+        ..Card::named("Mountain")
+    };
+    let (db, refs) = test_all_cards_db("goldfish-hand", vec![mountain, Card::named("Lightning Bolt")]);
+    let mut deck = Deck::default();
+    deck.add(DeckSection::Main, refs[0].clone(), 20);
+    deck.add(DeckSection::Main, refs[1].clone(), 40);
+
+    for seed in 0..20 {
+        let hand = sample_hand(&db, &deck, seed, 2);
+        assert_eq!(hand, sample_hand(&db, &deck, seed, 2));
+        assert!(hand.mulligans <= 2);
+        assert_eq!(hand.cards.len() + hand.bottomed.len(), OPENING_HAND);
+        assert_eq!(hand.bottomed.len(), hand.mulligans);
+
+        //a hand is only kept with 2 to 5 lands, unless it's out of mulligans
+        let lands = hand.cards.iter().chain(hand.bottomed.iter()).filter(|x| *x == "Mountain").count();
+        assert!((2..=5).contains(&lands) || hand.mulligans == 2);
+    }
+
+    //with no mulligans allowed, the first seven are kept whatever they are
+    let deck_of_lands = {
+        let mut deck = Deck::default();
+        deck.add(DeckSection::Main, refs[0].clone(), 60);
+        deck
+    };
+    let hand = sample_hand(&db, &deck_of_lands, 1, 0);
+    assert_eq!(hand.cards, vec!["Mountain"; OPENING_HAND]);
+    assert_eq!(hand.mulligans, 0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_goldfish() {
+    use crate::dbs::allcards::test_all_cards_db;
+
+    let colored = |color| ManaSymbol::ConventionalColored {
+        phyrexian: false,
+        split_two_generic: false,
+        color,
+        split_color: None,
+    };
+    let spell = |name: &str, cost: Vec<ManaSymbol>| Card {
+        types: vec!["Instant".to_string()],
+        mana_value_times_4: cost.len() * 4,
+        mana_cost: crate::data_model::card::ManaCost(cost),
+        ..Card::named(name)
+    };
+
+    let (db, refs) = test_all_cards_db(
+        "goldfish",
+        vec![
+            Card {
+                types: vec!["Land".to_string()],
+                subtypes: vec!["Mountain".to_string()],
+                ..Card::named("Mountain")
+            },
+            Card {
+                types: vec!["Land".to_string()],
+                oracle_text: "Izzet Guildgate enters tapped.\n{T}: Add {U} or {R}.".to_string(),
+                ..Card::named("Izzet Guildgate")
+            },
+            spell("Lightning Bolt", vec![colored(Color::Red)]),
+            spell("Counterspell", vec![colored(Color::Blue), colored(Color::Blue)]),
+        ],
+    );
+    let deck_of = |cards: &[(usize, usize)]| {
+        let mut deck = Deck::default();
+        for (i, count) in cards {
+            deck.add(DeckSection::Main, refs[*i].clone(), *count);
+        }
+        deck
+    };
+    let config = GoldfishConfig {
+        seed: 5,
+        games: 500,
+        turns: 4,
+        ..GoldfishConfig::default()
+    };
+
+    //every hand of only lands is mulliganed as far as it goes, and a land comes down each turn
+    let report = goldfish(&db, &deck_of(&[(0, 60)]), config);
+    assert_eq!(report.games, 500);
+    assert_eq!(report.average_mulligans, 2.0);
+    assert_eq!(report.lands_by_turn, [1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(report.mana_by_turn, [1.0, 2.0, 3.0, 4.0]);
+    assert!(report.cards.is_empty());
+
+    //tapped lands are a turn behind
+    let report = goldfish(&db, &deck_of(&[(1, 60)]), config);
+    assert_eq!(report.lands_by_turn, [1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(report.mana_by_turn, [0.0, 1.0, 2.0, 3.0]);
+
+    //with no blue sources, Counterspell never can be cast
+    let deck = deck_of(&[(0, 30), (2, 20), (3, 10)]);
+    let report = goldfish(&db, &deck, config);
+    assert_eq!(report, goldfish(&db, &deck, config));
+    assert_eq!(report.cards.len(), 2);
+
+    let bolt = &report.cards[0];
+    assert_eq!((bolt.name.as_str(), bolt.turn), ("Lightning Bolt", 1));
+    assert!(bolt.castable > 0.95);
+    assert!(bolt.drawn_and_castable > 0.0 && bolt.drawn_and_castable <= bolt.castable);
+
+    let counterspell = &report.cards[1];
+    assert_eq!((counterspell.name.as_str(), counterspell.turn), ("Counterspell", 2));
+    assert_eq!(counterspell.castable, 0.0);
+    assert_eq!(counterspell.drawn_and_castable, 0.0);
+
+    for (turn, (lands, mana)) in report.lands_by_turn.iter().zip(report.mana_by_turn.iter()).enumerate() {
+        assert!(*lands <= (turn + 1) as f64);
+        assert_eq!(lands, mana);
+    }
+    assert!(report.lands_by_turn.windows(2).all(|x| x[0] <= x[1]));
+}
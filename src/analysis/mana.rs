@@ -0,0 +1,164 @@
+//What colors of mana cards need and make.
+//Production is worked out from oracle text, since the DB doesn't store it:
+//basic land types, `Add {G}`-style abilities, "any color", and fetch lands.
+
+use crate::data_model::card::{Card, Color, ColorCombination, ManaSymbol};
+
+/// The five colors, in WUBRG order; per-color counts in this module are indexed the same way
+pub const COLORS: [Color; 5] = [
+    Color::White,
+    Color::Blue,
+    Color::Black,
+    Color::Red,
+    Color::Green,
+];
+
+const BASIC_LAND_TYPES: [&str; 5] = ["Plains", "Island", "Swamp", "Mountain", "Forest"];
+
+const MANA_LETTERS: [&str; 5] = ["{W}", "{U}", "{B}", "{R}", "{G}"];
+
+pub fn color_index(color: Color) -> Option<usize> {
+    COLORS.iter().position(|x| *x == color)
+}
+
+pub fn is_land(card: &Card) -> bool {
+    card.types.iter().any(|x| x == "Land")
+}
+
+///
+/// The colored pips in a mana cost, per color. A hybrid pip counts for both of its colors,
+/// since either will do, and a Phyrexian pip counts for its color even though it can be paid with life.
+pub fn pips(symbols: &[ManaSymbol]) -> [usize; 5] {
+    let mut pips = [0; 5];
+    for symbol in symbols {
+        if let ManaSymbol::ConventionalColored {
+            color, split_color, ..
+        } = symbol
+        {
+            for color in [Some(*color), *split_color].into_iter().flatten() {
+                if let Some(i) = color_index(color) {
+                    pips[i] += 1;
+                }
+            }
+        }
+    }
+    pips
+}
+
+fn with_color(mut colors: ColorCombination, i: usize) -> ColorCombination {
+    match i {
+        0 => colors.white = true,
+        1 => colors.blue = true,
+        2 => colors.black = true,
+        3 => colors.red = true,
+        _ => colors.green = true,
+    }
+    colors
+}
+
+pub fn has_color(colors: &ColorCombination, i: usize) -> bool {
+    [colors.white, colors.blue, colors.black, colors.red, colors.green][i]
+}
+
+///
+/// The colors of mana the card can make, with `colorless` set if it makes {C}.
+pub fn produced_colors(card: &Card) -> ColorCombination {
+    let mut colors = ColorCombination::default();
+
+    for (i, land_type) in BASIC_LAND_TYPES.iter().enumerate() {
+        if card.subtypes.iter().any(|x| x == land_type) {
+            colors = with_color(colors, i);
+        }
+    }
+
+    let text = colors_from_text(&card.oracle_text);
+    ColorCombination {
+        white: colors.white || text.white,
+        blue: colors.blue || text.blue,
+        black: colors.black || text.black,
+        red: colors.red || text.red,
+        green: colors.green || text.green,
+        colorless: text.colorless,
+    }
+}
+
+fn colors_from_text(oracle_text: &str) -> ColorCombination {
+    let mut colors = ColorCombination::default();
+
+    let text = oracle_text.to_ascii_lowercase();
+    for line in text.lines() {
+        let Some((_, added)) = line.split_once("add ") else {
+            //fetch lands make whatever they find
+            if let Some((_, found)) = line.split_once("search your library for") {
+                let found = found.split('.').next().unwrap_or_default();
+                if found.contains("basic land card") && !BASIC_LAND_TYPES
+                    .iter()
+                    .any(|x| found.contains(&x.to_ascii_lowercase()))
+                {
+                    colors = (0..5).fold(colors, with_color);
+                }
+                for (i, land_type) in BASIC_LAND_TYPES.iter().enumerate() {
+                    if found.contains(&land_type.to_ascii_lowercase()) {
+                        colors = with_color(colors, i);
+                    }
+                }
+            }
+            continue;
+        };
+        let added = added.split('.').next().unwrap_or_default();
+
+        if added.contains("any color")
+            || added.contains("any type")
+            || added.contains("chosen color")
+            || added.contains("color identity")
+        {
+            colors = (0..5).fold(colors, with_color);
+        }
+        for (i, letter) in MANA_LETTERS.iter().enumerate() {
+            if added.contains(&letter.to_ascii_lowercase()) {
+                colors = with_color(colors, i);
+            }
+        }
+        if added.contains("{c}") {
+            colors.colorless = true;
+        }
+    }
+
+    colors
+}
+
+#[cfg(test)]
+#[test]
+fn test_colors_from_text() {
+    use crate::color_combo;
+
+    assert_eq!(colors_from_text("{T}: Add {R} or {G}."), color_combo!(r g));
+    assert_eq!(colors_from_text("{T}: Add {C}.\n{T}, Pay 1 life: Add {W} or {B}."), color_combo!(c w b));
+    assert_eq!(colors_from_text("{T}: Add one mana of any color in your commander's color identity."), color_combo!(w u b r g));
+    assert_eq!(
+        colors_from_text("{T}, Pay 1 life, Sacrifice this land: Search your library for a Forest or Plains card, put it onto the battlefield, then shuffle."),
+        color_combo!(g w)
+    );
+    assert_eq!(colors_from_text("Flying"), color_combo!());
+}
+
+#[cfg(test)]
+#[test]
+fn test_pips() {
+    let cost = [
+        ManaSymbol::GenericNumber(2),
+        ManaSymbol::ConventionalColored {
+            phyrexian: false,
+            split_two_generic: false,
+            color: Color::Green,
+            split_color: None,
+        },
+        ManaSymbol::ConventionalColored {
+            phyrexian: false,
+            split_two_generic: false,
+            color: Color::White,
+            split_color: Some(Color::Blue),
+        },
+    ];
+    assert_eq!(pips(&cost), [1, 1, 0, 0, 1]);
+}
@@ -0,0 +1,55 @@
+//Working things out about a deck as a whole: whether it's legal, how strong it is,
+//and how it's likely to play. Everything here works from a `Deck` and the all-cards DB.
+
+use crate::{
+    data_model::{
+        card::{Card, CardRef},
+        deck::{Deck, DeckSection},
+    },
+    dbs::allcards::AllCardsDb,
+};
+
+pub mod bracket;
+pub mod goldfish;
+pub mod mana;
+pub mod probability;
+pub mod stats;
+pub mod validate;
+
+///
+/// One card of a deck, looked up. Different printings of the same card
+/// in the same section are merged.
+#[derive(Debug, Clone)]
+pub struct DeckCard {
+    pub section: DeckSection,
+    pub card: Card,
+    pub quantity: usize,
+}
+
+///
+/// The cards of the deck's played sections, and the refs which aren't in the DB.
+pub fn deck_cards(db: &AllCardsDb, deck: &Deck) -> (Vec<DeckCard>, Vec<CardRef>) {
+    let mut cards: Vec<DeckCard> = Vec::new();
+    let mut unknown = Vec::new();
+
+    for entry in deck.entries.iter().filter(|x| x.section.is_played()) {
+        let Some(card) = db.card_by_ref(&entry.card) else {
+            unknown.push(entry.card.clone());
+            continue;
+        };
+
+        match cards
+            .iter_mut()
+            .find(|x| x.section == entry.section && x.card.name == card.name)
+        {
+            Some(existing) => existing.quantity += entry.quantity,
+            None => cards.push(DeckCard {
+                section: entry.section,
+                card,
+                quantity: entry.quantity,
+            }),
+        }
+    }
+
+    (cards, unknown)
+}
@@ -0,0 +1,264 @@
+//Exact odds of having drawn certain kinds of cards by a certain turn,
+//e.g. "2 lands and a one-drop by turn 2".
+//Each kind of card is a search query, and the library is the deck's main section;
+//commanders and companions start outside the library, so they're never drawn.
+
+use std::collections::HashMap;
+
+use crate::{
+    analysis::deck_cards,
+    data_model::deck::{Deck, DeckSection},
+    query::{
+        compile::build_search_query, context::QueryContext, err_warn_support::IgnoreMessages,
+    },
+};
+
+pub const OPENING_HAND: usize = 7;
+
+/// Every combination of requirements gets checked, so there can't be many
+pub const MAX_REQUIREMENTS: usize = 6;
+
+///
+/// At least `at_least` cards matching the query. A card that matches more than one
+/// requirement's query can only count towards one of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub query: String,
+    pub at_least: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayOrDraw {
+    /// No draw on the first turn
+    Play,
+    Draw,
+}
+
+///
+/// How the game starts. With the London mulligan, each mulligan still draws seven,
+/// but puts one more card on the bottom; the cards kept are always the best ones for the requirements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawPlan {
+    pub play_or_draw: PlayOrDraw,
+    pub mulligans: usize,
+}
+
+impl DrawPlan {
+    /// Cards drawn after the opening hand, by the start of the turn's main phase
+    pub fn draws_by(&self, turn: usize) -> usize {
+        match self.play_or_draw {
+            PlayOrDraw::Play => turn.saturating_sub(1),
+            PlayOrDraw::Draw => turn,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbabilityErr {
+    /// The requirement's query isn't a query; plain names aren't supported
+    BadQuery(String),
+    TooManyRequirements(usize),
+}
+
+///
+/// The chance of meeting all the requirements on each of turns 1 through `turns`.
+pub fn odds_by_turn(
+    ctx: QueryContext,
+    deck: &Deck,
+    requirements: &[Requirement],
+    plan: DrawPlan,
+    turns: usize,
+) -> Result<Vec<f64>, ProbabilityErr> {
+    if requirements.len() > MAX_REQUIREMENTS {
+        return Err(ProbabilityErr::TooManyRequirements(requirements.len()));
+    }
+
+    let msgs = IgnoreMessages;
+    let queries = requirements
+        .iter()
+        .map(|x| {
+            build_search_query(&x.query, &msgs)
+                .map_err(|_| ProbabilityErr::BadQuery(x.query.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    //cards are grouped by which of the queries they match
+    let (cards, _) = deck_cards(ctx.cards, deck);
+    let mut library = 0;
+    let mut classes: HashMap<u32, usize> = HashMap::new();
+    for card in cards.iter().filter(|x| x.section == DeckSection::Main) {
+        library += card.quantity;
+
+        let mask = queries.iter().enumerate().fold(0, |mask, (i, query)| {
+            let matches = query
+                .query_cards(ctx, std::iter::once(card.card.clone()))
+                .next()
+                .is_some();
+            if matches { mask | (1 << i) } else { mask }
+        });
+        if mask != 0 {
+            *classes.entry(mask).or_default() += card.quantity;
+        }
+    }
+
+    let classes: Vec<(u32, usize)> = classes.into_iter().collect();
+    let needs: Vec<usize> = requirements.iter().map(|x| x.at_least).collect();
+    let keep = OPENING_HAND.saturating_sub(plan.mulligans);
+
+    Ok((1..=turns)
+        .map(|turn| odds(library, &classes, &needs, keep, plan.draws_by(turn)))
+        .collect())
+}
+
+///
+/// The chance of drawing at least `at_least` of the `successes` cards
+/// in a `population`, when drawing `draws` cards.
+pub fn hypergeometric_at_least(population: usize, successes: usize, draws: usize, at_least: usize) -> f64 {
+    let draws = draws.min(population);
+    let total = choose(population, draws);
+    (at_least..=successes.min(draws))
+        .map(|k| choose(successes, k) * choose(population - successes, draws - k) / total)
+        .sum()
+}
+
+fn choose(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+//cards in the hand so far, cards drawn so far, and the capped counts of each class in the hand and in the draws
+type Outcome = (usize, usize, Vec<usize>, Vec<usize>);
+
+///
+/// The exact chance of meeting the needs, over every way the opening hand and draws could
+/// fall. `classes` are the cards matching each combination of requirements (by bitmask),
+/// and only `keep` of the opening hand's cards count.
+///
+/// Since no requirement can use more cards than all of them need together, the count of
+/// each class is capped at that; this keeps the number of distinct outcomes small.
+fn odds(library: usize, classes: &[(u32, usize)], needs: &[usize], keep: usize, draws: usize) -> f64 {
+    let hand = OPENING_HAND.min(library);
+    let draws = draws.min(library - hand);
+    let cap = needs.iter().sum::<usize>();
+
+    let mut states: HashMap<Outcome, f64> = HashMap::new();
+    states.insert((0, 0, Vec::new(), Vec::new()), 1.0);
+
+    for (_, count) in classes.iter() {
+        let mut next = HashMap::new();
+        for ((in_hand, drawn, hand_counts, draw_counts), ways) in states {
+            for h in 0..=(*count).min(hand - in_hand) {
+                for d in 0..=(count - h).min(draws - drawn) {
+                    let mut hand_counts = hand_counts.clone();
+                    hand_counts.push(h.min(cap));
+                    let mut draw_counts = draw_counts.clone();
+                    draw_counts.push(d.min(cap));
+
+                    *next
+                        .entry((in_hand + h, drawn + d, hand_counts, draw_counts))
+                        .or_insert(0.0) += ways * choose(*count, h) * choose(count - h, d);
+                }
+            }
+        }
+        states = next;
+    }
+
+    let others = library - classes.iter().map(|x| x.1).sum::<usize>();
+    let total = choose(library, hand) * choose(library - hand, draws);
+
+    states
+        .into_iter()
+        .filter(|((_, _, hand_counts, draw_counts), _)| {
+            can_keep(classes, needs, hand_counts, draw_counts, keep)
+        })
+        .map(|((in_hand, drawn, _, _), ways)| {
+            let other_in_hand = hand - in_hand;
+            ways * choose(others, other_in_hand)
+                * choose(others.saturating_sub(other_in_hand), draws - drawn)
+        })
+        .sum::<f64>()
+        / total
+}
+
+///
+/// Whether some `keep` cards of the hand, with everything drawn after, meet the needs.
+fn can_keep(
+    classes: &[(u32, usize)],
+    needs: &[usize],
+    hand_counts: &[usize],
+    draw_counts: &[usize],
+    keep: usize,
+) -> bool {
+    fn choose_kept(
+        i: usize,
+        keep: usize,
+        kept: &mut Vec<usize>,
+        classes: &[(u32, usize)],
+        needs: &[usize],
+        hand_counts: &[usize],
+        draw_counts: &[usize],
+    ) -> bool {
+        if i == classes.len() {
+            let counts: Vec<usize> = kept.iter().zip(draw_counts).map(|(a, b)| a + b).collect();
+            return meets_needs(classes, needs, &counts);
+        }
+
+        (0..=hand_counts[i].min(keep)).rev().any(|x| {
+            kept.push(x);
+            let found = choose_kept(i + 1, keep - x, kept, classes, needs, hand_counts, draw_counts);
+            kept.pop();
+            found
+        })
+    }
+
+    choose_kept(0, keep, &mut Vec::new(), classes, needs, hand_counts, draw_counts)
+}
+
+//Hall's condition: every set of requirements has at least as many cards
+//which could go to one of them as they need between them.
+fn meets_needs(classes: &[(u32, usize)], needs: &[usize], counts: &[usize]) -> bool {
+    (1..(1u32 << needs.len())).all(|set| {
+        let needed: usize = (0..needs.len())
+            .filter(|i| set & (1 << i) != 0)
+            .map(|i| needs[i])
+            .sum();
+        let available: usize = classes
+            .iter()
+            .zip(counts)
+            .filter(|((mask, _), _)| mask & set != 0)
+            .map(|(_, count)| count)
+            .sum();
+        available >= needed
+    })
+}
+
+#[cfg(test)]
+#[test]
+fn test_hypergeometric() {
+    assert!((hypergeometric_at_least(4, 2, 2, 1) - 5.0 / 6.0).abs() < 1e-9);
+    assert!((hypergeometric_at_least(60, 24, 7, 0) - 1.0).abs() < 1e-9);
+
+    //a single requirement is plain hypergeometric
+    let expected = hypergeometric_at_least(60, 24, 8, 2);
+    assert!((odds(60, &[(1, 24)], &[2], 7, 1) - expected).abs() < 1e-9);
+}
+
+#[cfg(test)]
+#[test]
+fn test_overlapping_requirements() {
+    //two cards which each match both requirements can't meet a need of one each and a third
+    let classes = [(0b11, 2)];
+    assert_eq!(odds(2, &classes, &[1, 1], 7, 0), 1.0);
+    assert_eq!(odds(2, &classes, &[2, 1], 7, 0), 0.0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_mulligans() {
+    //every card's a land, but after six mulligans only one from the hand can be kept
+    assert_eq!(odds(20, &[(1, 20)], &[3], 1, 1), 0.0);
+    assert!((odds(20, &[(1, 20)], &[3], 1, 2) - 1.0).abs() < 1e-9);
+}
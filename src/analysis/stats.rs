@@ -0,0 +1,229 @@
+//Summary numbers for a deck: its curve, colors, and card types.
+
+use crate::{
+    analysis::{
+        DeckCard, deck_cards,
+        mana::{has_color, is_land, pips, produced_colors},
+    },
+    data_model::deck::{Deck, DeckSection},
+    dbs::allcards::AllCardsDb,
+};
+
+/// Mana values 0 through 6, then everything at 7 or more
+pub const CURVE_LEN: usize = 8;
+
+///
+/// How many nonland cards in a section there are at each mana value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionCurve {
+    pub section: DeckSection,
+    /// Half mana values are rounded down; the last entry is 7 and up
+    pub counts: [usize; CURVE_LEN],
+}
+
+///
+/// How much of one color the deck asks for, against how many lands make it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorBalance {
+    pub pips: usize,
+    /// This color's share of all the deck's colored pips
+    pub pip_share: f64,
+    pub sources: usize,
+    /// This color's share of all the colored sources, counting a land once for each color it makes
+    pub source_share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeckStats {
+    pub curves: Vec<SectionCurve>,
+    pub lands: usize,
+    pub creatures: usize,
+    /// Everything that's neither a land nor a creature
+    pub other_spells: usize,
+    pub average_mana_value: f64,
+    pub average_mana_value_with_lands: f64,
+    /// In WUBRG order
+    pub colors: [ColorBalance; 5],
+}
+
+impl DeckStats {
+    pub fn total(&self) -> usize {
+        self.lands + self.creatures + self.other_spells
+    }
+}
+
+//the sections that are played from the start of the game; the sideboard's only in the curves
+fn in_deck(card: &DeckCard) -> bool {
+    matches!(
+        card.section,
+        DeckSection::Commander | DeckSection::Companion | DeckSection::Main
+    )
+}
+
+fn share(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+pub fn deck_stats(db: &AllCardsDb, deck: &Deck) -> DeckStats {
+    let (cards, _) = deck_cards(db, deck);
+
+    let mut curves: Vec<SectionCurve> = Vec::new();
+    for card in cards.iter().filter(|x| !is_land(&x.card)) {
+        let mana_value = (card.card.mana_value_times_4 / 4).min(CURVE_LEN - 1);
+        match curves.iter_mut().find(|x| x.section == card.section) {
+            Some(curve) => curve.counts[mana_value] += card.quantity,
+            None => {
+                let mut counts = [0; CURVE_LEN];
+                counts[mana_value] = card.quantity;
+                curves.push(SectionCurve {
+                    section: card.section,
+                    counts,
+                });
+            }
+        }
+    }
+    curves.sort_by_key(|x| x.section);
+
+    let mut pip_counts = [0; 5];
+    let mut sources = [0; 5];
+    let (mut lands, mut creatures, mut other_spells) = (0, 0, 0);
+    let (mut nonland_mana_value_times_4, mut all_mana_value_times_4) = (0, 0);
+
+    for card in cards.iter().filter(|x| in_deck(x)) {
+        all_mana_value_times_4 += card.card.mana_value_times_4 * card.quantity;
+
+        if is_land(&card.card) {
+            lands += card.quantity;
+            let produced = produced_colors(&card.card);
+            for (i, count) in sources.iter_mut().enumerate() {
+                if has_color(&produced, i) {
+                    *count += card.quantity;
+                }
+            }
+            continue;
+        }
+
+        nonland_mana_value_times_4 += card.card.mana_value_times_4 * card.quantity;
+        if card.card.types.iter().any(|x| x == "Creature") {
+            creatures += card.quantity;
+        } else {
+            other_spells += card.quantity;
+        }
+
+        for (count, card_pips) in pip_counts.iter_mut().zip(pips(&card.card.mana_cost.0)) {
+            *count += card_pips * card.quantity;
+        }
+    }
+
+    let total_pips = pip_counts.iter().sum();
+    let total_sources = sources.iter().sum();
+    let colors = std::array::from_fn(|i| ColorBalance {
+        pips: pip_counts[i],
+        pip_share: share(pip_counts[i], total_pips),
+        sources: sources[i],
+        source_share: share(sources[i], total_sources),
+    });
+
+    DeckStats {
+        curves,
+        lands,
+        creatures,
+        other_spells,
+        average_mana_value: share(nonland_mana_value_times_4, (creatures + other_spells) * 4),
+        average_mana_value_with_lands: share(
+            all_mana_value_times_4,
+            (lands + creatures + other_spells) * 4,
+        ),
+        colors,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_deck_stats() {
+    use crate::{
+        data_model::card::{Card, Color, ManaCost, ManaSymbol},
+        dbs::allcards::test_all_cards_db,
+    };
+
+    let colored = |color| ManaSymbol::ConventionalColored {
+        phyrexian: false,
+        split_two_generic: false,
+        color,
+        split_color: None,
+    };
+    let spell = |name: &str, types: &str, cost: Vec<ManaSymbol>, mana_value: usize| Card {
+        types: vec![types.to_string()],
+        mana_cost: ManaCost(cost),
+        mana_value_times_4: mana_value * 4,
+        ..Card::named(name)
+    };
+    let land = |name: &str| Card {
+        types: vec!["Land".to_string()],
+        subtypes: vec![name.to_string()],
+        ..Card::named(name)
+    };
+
+    let (db, refs) = test_all_cards_db(
+        "deck-stats",
+        vec![
+            land("Mountain"),
+            land("Island"),
+            spell("Lightning Bolt", "Instant", vec![colored(Color::Red)], 1),
+            spell("Goblin Guide", "Creature", vec![colored(Color::Red)], 1),
+            spell("Counterspell", "Instant", vec![colored(Color::Blue), colored(Color::Blue)], 2),
+            spell(
+                "Big Dragon",
+                "Creature",
+                vec![ManaSymbol::GenericNumber(6), colored(Color::Red), colored(Color::Red)],
+                8,
+            ),
+        ],
+    );
+    let mut deck = Deck::default();
+    for (i, count) in [10, 6, 4, 4, 2, 1].into_iter().enumerate() {
+        deck.add(DeckSection::Main, refs[i].clone(), count);
+    }
+    deck.add(DeckSection::Side, refs[4].clone(), 2);
+
+    let stats = deck_stats(&db, &deck);
+
+    //the sideboard has a curve of its own, but isn't counted in anything else;
+    //8 mana goes in the last column
+    assert_eq!(
+        stats.curves,
+        [
+            SectionCurve {
+                section: DeckSection::Main,
+                counts: [0, 8, 2, 0, 0, 0, 0, 1],
+            },
+            SectionCurve {
+                section: DeckSection::Side,
+                counts: [0, 0, 2, 0, 0, 0, 0, 0],
+            },
+        ]
+    );
+    assert_eq!((stats.lands, stats.creatures, stats.other_spells), (16, 5, 6));
+    assert_eq!(stats.total(), 27);
+
+    //4 + 4 + 2 * 2 + 8 over 11 spells, then over all 27 cards
+    assert_eq!(stats.average_mana_value, 80.0 / 44.0);
+    assert_eq!(stats.average_mana_value_with_lands, 80.0 / 108.0);
+
+    let [white, blue, black, red, green] = stats.colors;
+    assert_eq!((red.pips, red.sources), (10, 10));
+    assert_eq!((blue.pips, blue.sources), (4, 6));
+    assert_eq!((red.pip_share, red.source_share), (10.0 / 14.0, 10.0 / 16.0));
+    assert_eq!((blue.pip_share, blue.source_share), (4.0 / 14.0, 6.0 / 16.0));
+    for unused in [white, black, green] {
+        assert_eq!(unused, ColorBalance { pips: 0, pip_share: 0.0, sources: 0, source_share: 0.0 });
+    }
+
+    let empty = deck_stats(&db, &Deck::default());
+    assert_eq!(empty.total(), 0);
+    assert_eq!(empty.average_mana_value, 0.0);
+}
@@ -0,0 +1,613 @@
+//Checking a deck against a format's deckbuilding rules.
+//Every problem is reported, rather than stopping at the first one,
+//so the whole list can be fixed in one go.
+
+use std::fmt::Display;
+
+use crate::{
+    analysis::{DeckCard, deck_cards},
+    data_model::{
+        card::{Card, CardRef, ColorCombination, Supertype},
+        deck::{Deck, DeckSection},
+        legality::{GameFormat, Legality},
+    },
+    dbs::allcards::AllCardsDb,
+};
+
+///
+/// The deck size and copy limits of a format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatRules {
+    /// Counting the commanders, in Commander
+    pub min_deck: usize,
+    pub max_deck: Option<usize>,
+    pub max_copies: usize,
+    /// Counting the companion, outside of Commander
+    pub max_sideboard: usize,
+}
+
+impl FormatRules {
+    pub fn of(format: GameFormat) -> Self {
+        match format {
+            GameFormat::Commander => FormatRules {
+                min_deck: 100,
+                max_deck: Some(100),
+                max_copies: 1,
+                max_sideboard: 0,
+            },
+            _ => FormatRules {
+                min_deck: 60,
+                max_deck: None,
+                max_copies: 4,
+                max_sideboard: 15,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// A card in the deck that isn't in the DB, so nothing else could be checked for it
+    UnknownCard(CardRef),
+    TooFewCards { count: usize, minimum: usize },
+    TooManyCards { count: usize, maximum: usize },
+    SideboardTooBig { count: usize, maximum: usize },
+    TooManyCompanions(usize),
+    TooManyCopies { card: String, count: usize, maximum: usize },
+    Banned(String),
+    NotLegal(String),
+    Restricted { card: String, count: usize },
+    /// Cards in the commander section of a deck for a format without commanders
+    CommanderNotInFormat(String),
+    NoCommander,
+    TooManyCommanders(usize),
+    NotACommander(String),
+    /// Two commanders which can't be played together
+    CantPair(String, String),
+    OutsideColorIdentity(String),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::UnknownCard(card_ref) => write!(
+                f,
+                "Unknown card {} {}",
+                card_ref.set, card_ref.collector_number
+            ),
+            Violation::TooFewCards { count, minimum } => {
+                write!(f, "Deck has {count} cards; it needs at least {minimum}")
+            }
+            Violation::TooManyCards { count, maximum } => {
+                write!(f, "Deck has {count} cards; it can have at most {maximum}")
+            }
+            Violation::SideboardTooBig { count, maximum } => {
+                write!(f, "Sideboard has {count} cards; it can have at most {maximum}")
+            }
+            Violation::TooManyCompanions(count) => {
+                write!(f, "{count} companions; there can only be one")
+            }
+            Violation::TooManyCopies {
+                card,
+                count,
+                maximum,
+            } => write!(f, "{count} copies of {card}; at most {maximum} allowed"),
+            Violation::Banned(card) => write!(f, "{card} is banned"),
+            Violation::NotLegal(card) => write!(f, "{card} isn't legal in this format"),
+            Violation::Restricted { card, count } => {
+                write!(f, "{card} is restricted, but there are {count} copies")
+            }
+            Violation::CommanderNotInFormat(card) => {
+                write!(f, "{card} is a commander, but this format doesn't have them")
+            }
+            Violation::NoCommander => write!(f, "Deck has no commander"),
+            Violation::TooManyCommanders(count) => {
+                write!(f, "{count} commanders; there can be one, or two that pair up")
+            }
+            Violation::NotACommander(card) => write!(f, "{card} can't be a commander"),
+            Violation::CantPair(first, second) => {
+                write!(f, "{first} and {second} can't be commanders together")
+            }
+            Violation::OutsideColorIdentity(card) => {
+                write!(f, "{card} is outside the commander's color identity")
+            }
+        }
+    }
+}
+
+///
+/// Every way the deck breaks the format's rules; empty if it's legal.
+/// Cards the DB has no legality data for are only checked against the deckbuilding rules.
+pub fn validate(db: &AllCardsDb, deck: &Deck, format: GameFormat) -> Vec<Violation> {
+    let rules = FormatRules::of(format);
+    let (cards, unknown) = deck_cards(db, deck);
+    let mut violations: Vec<_> = unknown.into_iter().map(Violation::UnknownCard).collect();
+
+    let count = |sections: &[DeckSection]| -> usize {
+        cards
+            .iter()
+            .filter(|x| sections.contains(&x.section))
+            .map(|x| x.quantity)
+            .sum()
+    };
+
+    let (deck_size, sideboard_size) = match format {
+        GameFormat::Commander => (
+            count(&[DeckSection::Commander, DeckSection::Main]),
+            count(&[DeckSection::Side]),
+        ),
+        _ => (
+            count(&[DeckSection::Main]),
+            count(&[DeckSection::Side, DeckSection::Companion]),
+        ),
+    };
+
+    if deck_size < rules.min_deck {
+        violations.push(Violation::TooFewCards {
+            count: deck_size,
+            minimum: rules.min_deck,
+        });
+    }
+    if let Some(maximum) = rules.max_deck.filter(|x| deck_size > *x) {
+        violations.push(Violation::TooManyCards {
+            count: deck_size,
+            maximum,
+        });
+    }
+    if sideboard_size > rules.max_sideboard {
+        violations.push(Violation::SideboardTooBig {
+            count: sideboard_size,
+            maximum: rules.max_sideboard,
+        });
+    }
+
+    let companions = count(&[DeckSection::Companion]);
+    if companions > 1 {
+        violations.push(Violation::TooManyCompanions(companions));
+    }
+
+    //copies are limited across all the played sections together
+    let mut totals: Vec<(&Card, usize)> = Vec::new();
+    for deck_card in cards.iter() {
+        match totals.iter_mut().find(|(card, _)| card.name == deck_card.card.name) {
+            Some((_, total)) => *total += deck_card.quantity,
+            None => totals.push((&deck_card.card, deck_card.quantity)),
+        }
+    }
+
+    for (card, total) in totals {
+        let maximum = copy_limit(card).unwrap_or(rules.max_copies);
+        if total > maximum {
+            violations.push(Violation::TooManyCopies {
+                card: card.name.clone(),
+                count: total,
+                maximum,
+            });
+        }
+
+        match db.legalities(card).map(|x| x.get(format)) {
+            Some(Legality::Banned) => violations.push(Violation::Banned(card.name.clone())),
+            Some(Legality::NotLegal) => violations.push(Violation::NotLegal(card.name.clone())),
+            Some(Legality::Restricted) if total > 1 => violations.push(Violation::Restricted {
+                card: card.name.clone(),
+                count: total,
+            }),
+            _ => {}
+        }
+    }
+
+    let commanders: Vec<&DeckCard> = cards
+        .iter()
+        .filter(|x| x.section == DeckSection::Commander)
+        .collect();
+
+    if format == GameFormat::Commander {
+        violations.extend(check_commanders(&cards, &commanders));
+    } else {
+        violations.extend(
+            commanders
+                .iter()
+                .map(|x| Violation::CommanderNotInFormat(x.card.name.clone())),
+        );
+    }
+
+    violations
+}
+
+fn check_commanders(cards: &[DeckCard], commanders: &[&DeckCard]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    let count: usize = commanders.iter().map(|x| x.quantity).sum();
+    match count {
+        0 => violations.push(Violation::NoCommander),
+        1 => {
+            let card = &commanders[0].card;
+            if !can_be_commander(card) {
+                violations.push(Violation::NotACommander(card.name.clone()));
+            }
+        }
+        2 if commanders.len() == 2 => {
+            let (first, second) = (&commanders[0].card, &commanders[1].card);
+            if can_pair(first, second) {
+                //a background can only be a commander alongside the one choosing it,
+                //which `can_pair` has already checked
+                for card in [first, second] {
+                    if !can_be_commander(card) && !is_background(card) {
+                        violations.push(Violation::NotACommander(card.name.clone()));
+                    }
+                }
+            } else {
+                violations.push(Violation::CantPair(first.name.clone(), second.name.clone()));
+            }
+        }
+        count => violations.push(Violation::TooManyCommanders(count)),
+    }
+
+    if commanders.is_empty() {
+        return violations;
+    }
+
+    let identity = commanders
+        .iter()
+        .fold(ColorCombination::default(), |identity, x| {
+            union(identity, x.card.color_id)
+        });
+
+    violations.extend(
+        cards
+            .iter()
+            .filter(|x| x.section != DeckSection::Commander)
+            .filter(|x| !within_identity(x.card.color_id, identity))
+            .map(|x| Violation::OutsideColorIdentity(x.card.name.clone())),
+    );
+
+    violations
+}
+
+///
+/// The number of copies the card itself allows, if it overrides the format's limit:
+/// basic lands, and cards which say a deck can have any number (or some number) of them.
+pub fn copy_limit(card: &Card) -> Option<usize> {
+    if card.super_types.contains(&Supertype::Basic) {
+        return Some(usize::MAX);
+    }
+    copy_limit_from_text(&card.oracle_text)
+}
+
+fn copy_limit_from_text(oracle_text: &str) -> Option<usize> {
+    let text = oracle_text.to_ascii_lowercase();
+    if text.contains("a deck can have any number of cards named") {
+        return Some(usize::MAX);
+    }
+
+    //e.g. Seven Dwarves: "A deck can have up to seven cards named Seven Dwarves."
+    let (_, rest) = text.split_once("a deck can have up to ")?;
+    let (number, rest) = rest.split_once(' ')?;
+    if !rest.starts_with("cards named") {
+        return None;
+    }
+
+    const NUMBERS: [&str; 11] = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    ];
+    NUMBERS
+        .iter()
+        .position(|x| *x == number)
+        .or_else(|| number.parse().ok())
+}
+
+///
+/// The abilities which let two cards be commanders together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PairingKeyword {
+    Partner,
+    PartnerWith(String),
+    FriendsForever,
+    ChooseABackground,
+    DoctorsCompanion,
+}
+
+fn pairing_keywords(oracle_text: &str) -> Vec<PairingKeyword> {
+    let mut keywords = Vec::new();
+
+    for line in oracle_text.lines() {
+        //drop the reminder text
+        let line = match line.find('(') {
+            Some(i) => &line[..i],
+            None => line,
+        }
+        .trim();
+
+        //the partner's name can have a comma in it, so this one's always on its own line
+        if line.to_ascii_lowercase().starts_with("partner with ") {
+            keywords.push(PairingKeyword::PartnerWith(
+                line["partner with ".len()..].to_string(),
+            ));
+            continue;
+        }
+
+        for keyword in line.split(',').map(str::trim) {
+            let lower = keyword.to_ascii_lowercase();
+            keywords.push(match lower.as_str() {
+                "partner" => PairingKeyword::Partner,
+                "friends forever" => PairingKeyword::FriendsForever,
+                "choose a background" => PairingKeyword::ChooseABackground,
+                "doctor's companion" => PairingKeyword::DoctorsCompanion,
+                _ => continue,
+            });
+        }
+    }
+
+    keywords
+}
+
+fn can_be_commander(card: &Card) -> bool {
+    let legendary_creature = card.super_types.contains(&Supertype::Legendary)
+        && card.types.iter().any(|x| x == "Creature");
+
+    legendary_creature
+        || card
+            .oracle_text
+            .to_ascii_lowercase()
+            .contains("can be your commander")
+}
+
+fn is_background(card: &Card) -> bool {
+    card.subtypes.iter().any(|x| x == "Background")
+}
+
+fn is_time_lord_doctor(card: &Card) -> bool {
+    card.subtypes.iter().any(|x| x == "Time Lord") && card.subtypes.iter().any(|x| x == "Doctor")
+}
+
+fn can_pair(first: &Card, second: &Card) -> bool {
+    let first_keywords = pairing_keywords(&first.oracle_text);
+    let second_keywords = pairing_keywords(&second.oracle_text);
+    let both = |keyword: PairingKeyword| {
+        first_keywords.contains(&keyword) && second_keywords.contains(&keyword)
+    };
+
+    let partners_with = |keywords: &[PairingKeyword], other: &Card| {
+        keywords.contains(&PairingKeyword::PartnerWith(other.name.clone()))
+    };
+
+    let one_way = |a_keywords: &[PairingKeyword], b: &Card| {
+        (a_keywords.contains(&PairingKeyword::ChooseABackground) && is_background(b))
+            || (a_keywords.contains(&PairingKeyword::DoctorsCompanion) && is_time_lord_doctor(b))
+    };
+
+    both(PairingKeyword::Partner)
+        || both(PairingKeyword::FriendsForever)
+        || (partners_with(&first_keywords, second) && partners_with(&second_keywords, first))
+        || one_way(&first_keywords, second)
+        || one_way(&second_keywords, first)
+}
+
+fn union(a: ColorCombination, b: ColorCombination) -> ColorCombination {
+    ColorCombination {
+        white: a.white || b.white,
+        blue: a.blue || b.blue,
+        black: a.black || b.black,
+        red: a.red || b.red,
+        green: a.green || b.green,
+        colorless: a.colorless || b.colorless,
+    }
+}
+
+//colorless cards fit in any identity, so that flag is ignored
+fn within_identity(card: ColorCombination, identity: ColorCombination) -> bool {
+    (!card.white || identity.white)
+        && (!card.blue || identity.blue)
+        && (!card.black || identity.black)
+        && (!card.red || identity.red)
+        && (!card.green || identity.green)
+}
+
+#[cfg(test)]
+#[test]
+fn test_copy_limit_from_text() {
+    assert_eq!(
+        copy_limit_from_text("A deck can have any number of cards named Relentless Rats."),
+        Some(usize::MAX)
+    );
+    assert_eq!(
+        copy_limit_from_text("A deck can have up to seven cards named Seven Dwarves."),
+        Some(7)
+    );
+    assert_eq!(copy_limit_from_text("Flying"), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_pairing_keywords() {
+    assert_eq!(
+        pairing_keywords("Partner (You can have two commanders if both have partner.)\nFlying"),
+        vec![PairingKeyword::Partner]
+    );
+    assert_eq!(
+        pairing_keywords("Partner with Pir, Imaginative Rascal (When this creature enters...)"),
+        vec![PairingKeyword::PartnerWith("Pir, Imaginative Rascal".to_string())]
+    );
+    assert_eq!(
+        pairing_keywords("Flying, vigilance\nChoose a Background (You can have a Background as a second commander.)"),
+        vec![PairingKeyword::ChooseABackground]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_validate() {
+    use crate::{color_combo, dbs::allcards::test_all_cards_db};
+
+    let bolt = Card {
+        color_id: color_combo!(r),
+        ..Card::named("Lightning Bolt")
+    };
+    let mountain = Card {
+        super_types: vec![Supertype::Basic],
+        types: vec!["Land".to_string()],
+        ..Card::named("Mountain")
+    };
+    let elves = Card {
+        color_id: color_combo!(g),
+        ..Card::named("Llanowar Elves")
+    };
+    let krenko = Card {
+        color_id: color_combo!(r),
+        super_types: vec![Supertype::Legendary],
+        types: vec!["Creature".to_string()],
+        ..Card::named("Krenko, Mob Boss")
+    };
+    let (db, refs) = test_all_cards_db("validate", vec![bolt, mountain, elves, krenko]);
+    let [bolt, mountain, elves, krenko] = [0, 1, 2, 3].map(|i| refs[i].clone());
+
+    let mut deck = Deck::default();
+    deck.add(DeckSection::Main, bolt.clone(), 4);
+    deck.add(DeckSection::Main, mountain.clone(), 56);
+    assert_eq!(validate(&db, &deck, GameFormat::Modern), []);
+
+    //basic lands aren't limited, but everything else is, across the main deck and sideboard
+    deck.add(DeckSection::Side, bolt.clone(), 1);
+    deck.add(DeckSection::Side, mountain.clone(), 15);
+    deck.remove(DeckSection::Main, &mountain, 1);
+    assert_eq!(
+        validate(&db, &deck, GameFormat::Modern),
+        [
+            Violation::TooFewCards { count: 59, minimum: 60 },
+            Violation::SideboardTooBig { count: 16, maximum: 15 },
+            Violation::TooManyCopies {
+                card: "Lightning Bolt".to_string(),
+                count: 5,
+                maximum: 4
+            },
+        ]
+    );
+
+    let unknown = CardRef {
+        set: "TST".to_string(),
+        collector_number: 5.into(),
+        printing: None,
+    };
+    let mut deck = Deck::default();
+    deck.add(DeckSection::Commander, krenko.clone(), 1);
+    deck.add(DeckSection::Main, mountain.clone(), 60);
+    deck.add(DeckSection::Main, unknown.clone(), 1);
+    assert_eq!(
+        validate(&db, &deck, GameFormat::Modern),
+        [
+            Violation::UnknownCard(unknown),
+            Violation::CommanderNotInFormat("Krenko, Mob Boss".to_string()),
+        ]
+    );
+
+    //the commander counts towards the hundred, and sets the color identity
+    let mut deck = Deck::default();
+    deck.add(DeckSection::Commander, krenko, 1);
+    deck.add(DeckSection::Main, bolt, 1);
+    deck.add(DeckSection::Main, mountain.clone(), 98);
+    assert_eq!(validate(&db, &deck, GameFormat::Commander), []);
+
+    deck.remove(DeckSection::Main, &mountain, 1);
+    deck.add(DeckSection::Main, elves.clone(), 1);
+    deck.add(DeckSection::Side, elves, 1);
+    assert_eq!(
+        validate(&db, &deck, GameFormat::Commander),
+        [
+            Violation::SideboardTooBig { count: 1, maximum: 0 },
+            Violation::TooManyCopies {
+                card: "Llanowar Elves".to_string(),
+                count: 2,
+                maximum: 1
+            },
+            Violation::OutsideColorIdentity("Llanowar Elves".to_string()),
+            Violation::OutsideColorIdentity("Llanowar Elves".to_string()),
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_check_commanders() {
+    use crate::color_combo;
+
+    let legend = |name: &str, text: &str, color_id: ColorCombination| Card {
+        color_id,
+        oracle_text: text.to_string(),
+        super_types: vec![Supertype::Legendary],
+        types: vec!["Creature".to_string()],
+        ..Card::named(name)
+    };
+    let in_section = |section: DeckSection, card: &Card| DeckCard {
+        section,
+        card: card.clone(),
+        quantity: 1,
+    };
+    let check = |commanders: &[&Card], others: &[&Card]| {
+        let cards = commanders
+            .iter()
+            .map(|x| in_section(DeckSection::Commander, *x))
+            .chain(others.iter().map(|x| in_section(DeckSection::Main, *x)))
+            .collect::<Vec<_>>();
+        let commanders = cards
+            .iter()
+            .filter(|x| x.section == DeckSection::Commander)
+            .collect::<Vec<_>>();
+        check_commanders(&cards, &commanders)
+    };
+
+    let thrasios = legend("Thrasios, Triton Hero", "Partner (You can have two commanders if both have partner.)", color_combo!(g u));
+    let tymna = legend("Tymna the Weaver", "Lifelink\nPartner", color_combo!(w b));
+    let krenko = legend("Krenko, Mob Boss", "", color_combo!(r));
+    let wilson = legend("Wilson, Refined Grizzly", "Choose a Background", color_combo!(g));
+    let background = Card {
+        color_id: color_combo!(b),
+        super_types: vec![Supertype::Legendary],
+        types: vec!["Enchantment".to_string()],
+        subtypes: vec!["Background".to_string()],
+        ..Card::named("Cult of the Hidden Sun")
+    };
+    let goblin = Card {
+        color_id: color_combo!(r),
+        types: vec!["Creature".to_string()],
+        ..Card::named("Goblin Guide")
+    };
+    let sword = Card {
+        color_id: color_combo!(w),
+        ..Card::named("Swords to Plowshares")
+    };
+
+    assert_eq!(check(&[], &[&sword]), [Violation::NoCommander]);
+    assert_eq!(check(&[&krenko], &[&goblin]), []);
+    assert_eq!(
+        check(&[&goblin], &[]),
+        [Violation::NotACommander("Goblin Guide".to_string())]
+    );
+    assert_eq!(
+        check(&[&thrasios, &tymna, &krenko], &[]),
+        [Violation::TooManyCommanders(3)]
+    );
+
+    //partners pool their color identities
+    assert_eq!(check(&[&thrasios, &tymna], &[&sword]), []);
+    assert_eq!(
+        check(&[&thrasios, &krenko], &[]),
+        [Violation::CantPair(
+            "Thrasios, Triton Hero".to_string(),
+            "Krenko, Mob Boss".to_string()
+        )]
+    );
+
+    //a background isn't a creature, but can be a commander alongside one that chooses it
+    assert_eq!(check(&[&wilson, &background], &[]), []);
+    assert_eq!(
+        check(&[&krenko, &background], &[]),
+        [Violation::CantPair(
+            "Krenko, Mob Boss".to_string(),
+            "Cult of the Hidden Sun".to_string()
+        )]
+    );
+    assert_eq!(
+        check(&[&wilson, &background], &[&goblin]),
+        [Violation::OutsideColorIdentity("Goblin Guide".to_string())]
+    );
+}
@@ -1,12 +1,17 @@
 use core::panic;
 use minimal_storage::multitype_paged_storage::MultitypePagedStorage;
 use project::data_model::card::{
-    self, Card, CardRef, Color, ColorCombination, ManaCost, ManaSymbol, Supertype,
+    self, Card, CardRef, Color, ColorCombination, ForeignName, ForeignNames, Language, ManaCost,
+    ManaSymbol, Printing, Supertype,
 };
+use project::data_model::date::Date;
+use project::data_model::legality::{CardLegalities, GameFormat, Legality};
+use project::data_model::ruling::{CardRulings, OracleChange, Ruling};
+use project::data_model::set::SetInfo;
 use project::dbs::allcards::AllCardsDb;
 use project::dbs::allcards::cardref_key::card_ref_to_index;
 use serde_json;
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::str::FromStr;
@@ -238,7 +243,7 @@ fn parse_mana_cost(mut cost: &str) -> ManaCost {
     ManaCost(mana)
 }
 
-fn process_sets_map(json: serde_json::Value) -> BTreeMap<String, CardRef> {
+fn process_sets_map(json: &serde_json::Value) -> BTreeMap<String, CardRef> {
     let mut map = BTreeMap::new();
 
     for set_spec in json.as_array().expect("Sets JSON should be an array") {
@@ -250,8 +255,8 @@ fn process_sets_map(json: serde_json::Value) -> BTreeMap<String, CardRef> {
                     .as_array()
                     .expect("Collector numbers should be an array");
                 assert!(
-                    v.len() == 2,
-                    "Collector numbers should be an array of 2 items"
+                    v.len() >= 2,
+                    "Collector numbers should be an array of at least 2 items"
                 );
 
                 let name = v
@@ -280,26 +285,260 @@ fn process_sets_map(json: serde_json::Value) -> BTreeMap<String, CardRef> {
     map
 }
 
+fn parse_set_info(set: &serde_json::Value) -> SetInfo {
+    let optional_str = |field: &str| set[field].as_str().map(String::from);
+
+    SetInfo {
+        code: set["code"]
+            .as_str()
+            .expect("Set code should be a string")
+            .to_string(),
+        name: set["name"]
+            .as_str()
+            .expect("Set name should be a string")
+            .to_string(),
+        release_date: set["releaseDate"]
+            .as_str()
+            .and_then(|x| x.parse().ok())
+            .expect("Release dates should be formatted YYYY-MM-DD"),
+        set_type: set["type"]
+            .as_str()
+            .expect("Set type should be a string")
+            .to_string(),
+        block: optional_str("block"),
+        parent_code: optional_str("parentCode"),
+        card_count: set["totalSetSize"].as_u64().unwrap_or_default() as usize,
+    }
+}
+
+///
+/// Every printing of every card in the sets file, along with the name of the card
+/// it's a printing of, its MTGJSON UUID, and its nonfoil and foil MTGO catalog IDs.
+fn process_printings(
+    json: &serde_json::Value,
+    sets: &BTreeMap<String, CardRef>,
+) -> Vec<(String, Option<String>, (Option<usize>, Option<usize>), Printing)> {
+    let mut printings = Vec::new();
+
+    for set_spec in json.as_array().expect("Sets JSON should be an array") {
+        let set_spec = set_spec.as_object().expect("Each set should be an object");
+
+        for (setcode, v) in set_spec.iter() {
+            for entry in v.as_array().expect("Sets should be an arr") {
+                let name = entry[0].as_str().expect("Names should be a string");
+                let collector_number = entry[1]
+                    .as_str()
+                    .expect("Collector number should be a string");
+                let details = &entry[2];
+
+                let Some(card) = sets.get(name) else {
+                    continue;
+                };
+
+                let detail_str = |field: &str| details[field].as_str().map(String::from);
+                //MTGJSON gives these as strings
+                let mtgo_id = |field: &str| detail_str(field).and_then(|x| x.parse().ok());
+
+                printings.push((
+                    name.to_string(),
+                    detail_str("uuid"),
+                    (mtgo_id("mtgoId"), mtgo_id("mtgoFoilId")),
+                    Printing {
+                        card_ref: CardRef {
+                            set: setcode.to_string(),
+                            collector_number: collector_number.parse().unwrap(),
+                            printing: None,
+                        },
+                        card: card.clone(),
+                        artist: detail_str("artist").unwrap_or_default(),
+                        flavor_text: detail_str("flavorText").unwrap_or_default(),
+                        watermark: detail_str("watermark"),
+                        frame: detail_str("frameVersion").unwrap_or_default(),
+                        border: detail_str("borderColor").unwrap_or_default(),
+                    },
+                ));
+            }
+        }
+    }
+
+    printings
+}
+
+fn parse_rulings(rulings: &serde_json::Value) -> Vec<Ruling> {
+    let mut rulings: Vec<_> = rulings
+        .as_array()
+        .expect("Rulings should be an array")
+        .iter()
+        .map(|ruling| Ruling {
+            date: ruling["date"]
+                .as_str()
+                .and_then(|x| x.parse().ok())
+                .expect("Ruling dates should be formatted YYYY-MM-DD"),
+            text: ruling["text"]
+                .as_str()
+                .expect("Ruling text should be a string")
+                .to_string(),
+        })
+        .collect();
+
+    rulings.sort_by_key(|x| x.date);
+    rulings
+}
+
+fn parse_foreign_names(names: &serde_json::Value) -> ForeignNames {
+    let mut names: Vec<_> = names
+        .as_object()
+        .expect("Foreign names should be an object")
+        .iter()
+        .filter_map(|(language, name)| {
+            //MTGJSON occasionally adds languages; there's nothing to do with those until we know them
+            let language = language.parse::<Language>().ok()?;
+            Some(ForeignName {
+                language,
+                name: name
+                    .as_str()
+                    .expect("Foreign names should be strings")
+                    .to_string(),
+            })
+        })
+        .collect();
+
+    names.sort_by_key(|x| x.language);
+    ForeignNames(names)
+}
+
+fn parse_legalities(legalities: &serde_json::Value) -> CardLegalities {
+    let mut parsed = CardLegalities::default();
+    for format in GameFormat::ALL {
+        if let Some(status) = legalities[format.mtgjson_key()].as_str() {
+            parsed.set(format, Legality::from_mtgjson(status));
+        }
+    }
+    parsed
+}
+
+/// Bumped whenever the oracle history file's shape changes; older files are ignored.
+const ORACLE_HISTORY_VERSION: u64 = 1;
+
+///
+/// Oracle texts are kept in a JSON file next to the DB, rather than read back out of the old
+/// DB, since the old DB may have been written with a different layout.
+fn oracle_history_file(db_file: &str) -> String {
+    format!("{db_file}.oracle.json")
+}
+
+///
+/// The oracle text of every card in the last build, along with the history it already had.
+/// Since we rebuild the DB from scratch, this is the only place that old texts survive.
+fn previous_oracle_texts(db_file: &str) -> HashMap<String, (String, Vec<OracleChange>)> {
+    let Ok(file) = File::open(oracle_history_file(db_file)) else {
+        return HashMap::new();
+    };
+    let Ok(json) = serde_json::from_reader::<_, serde_json::Value>(BufReader::new(file)) else {
+        return HashMap::new();
+    };
+    if json["version"].as_u64() != Some(ORACLE_HISTORY_VERSION) {
+        eprintln!("Oracle history is from a different version; starting it over");
+        return HashMap::new();
+    }
+    let Some(cards) = json["cards"].as_object() else {
+        return HashMap::new();
+    };
+
+    cards
+        .iter()
+        .filter_map(|(name, card)| {
+            let text = card["text"].as_str()?.to_string();
+            let history = card["changes"]
+                .as_array()?
+                .iter()
+                .filter_map(|change| {
+                    Some(OracleChange {
+                        replaced: change["replaced"].as_str()?.parse().ok()?,
+                        old_text: change["old_text"].as_str()?.to_string(),
+                    })
+                })
+                .collect();
+            Some((name.clone(), (text, history)))
+        })
+        .collect()
+}
+
+fn write_oracle_texts(db_file: &str, texts: &BTreeMap<String, (String, Vec<OracleChange>)>) -> io::Result<()> {
+    let cards = texts
+        .iter()
+        .map(|(name, (text, history))| {
+            let changes = history
+                .iter()
+                .map(|x| serde_json::json!({ "replaced": x.replaced.to_string(), "old_text": x.old_text }))
+                .collect::<Vec<_>>();
+            (name.clone(), serde_json::json!({ "text": text, "changes": changes }))
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    let file = File::create(oracle_history_file(db_file))?;
+    serde_json::to_writer(
+        io::BufWriter::new(file),
+        &serde_json::json!({ "version": ORACLE_HISTORY_VERSION, "cards": cards }),
+    )
+    .map_err(io::Error::from)
+}
+
+const USAGE: &str =
+    "Usage: build_card_db <cards_file> <sets_file> <db_file> [set_info_file] [rulings_file] [foreign_names_file] [legalities_file]";
+
 //NOTE: In general, this module panics instead of sensibly handling errors
 fn main() -> io::Result<()> {
-    let cards_file = std::env::args()
-        .nth(1)
-        .expect("Usage: build_card_db <cards_file> <sets_file> <db_file>");
-    let sets_file = std::env::args()
-        .nth(2)
-        .expect("Usage: build_card_db <cards_file> <sets_file> <db_file>");
-    let db_file = std::env::args()
-        .nth(3)
-        .expect("Usage: build_card_db <cards_file> <sets_file> <db_file>");
+    let cards_file = std::env::args().nth(1).expect(USAGE);
+    let sets_file = std::env::args().nth(2).expect(USAGE);
+    let db_file = std::env::args().nth(3).expect(USAGE);
+    let set_info_file = std::env::args().nth(4);
+    let rulings_file = std::env::args().nth(5);
+    let foreign_names_file = std::env::args().nth(6);
+    let legalities_file = std::env::args().nth(7);
 
     let rdr = BufReader::new(File::open(cards_file).expect("Can't open <cards_file>"));
     let json_cards: serde_json::Value =
         serde_json::from_reader(rdr).expect("Bad data in <cards_file>");
 
+    let mut previous_oracle = previous_oracle_texts(&db_file);
+    let today = Date::today();
+
+    let mut rulings = match rulings_file {
+        Some(rulings_file) => {
+            let rdr = BufReader::new(File::open(rulings_file).expect("Can't open <rulings_file>"));
+            serde_json::from_reader::<_, serde_json::Map<String, serde_json::Value>>(rdr)
+                .expect("Bad data in <rulings_file>")
+        }
+        None => serde_json::Map::new(),
+    };
+
+    let mut foreign_names = match foreign_names_file {
+        Some(foreign_names_file) => {
+            let rdr = BufReader::new(
+                File::open(foreign_names_file).expect("Can't open <foreign_names_file>"),
+            );
+            serde_json::from_reader::<_, serde_json::Map<String, serde_json::Value>>(rdr)
+                .expect("Bad data in <foreign_names_file>")
+        }
+        None => serde_json::Map::new(),
+    };
+
+    let mut legalities = match legalities_file {
+        Some(legalities_file) => {
+            let rdr = BufReader::new(
+                File::open(legalities_file).expect("Can't open <legalities_file>"),
+            );
+            serde_json::from_reader::<_, serde_json::Map<String, serde_json::Value>>(rdr)
+                .expect("Bad data in <legalities_file>")
+        }
+        None => serde_json::Map::new(),
+    };
+
     //try to remove the old database. no sweat if it doesn't work.
     let _ = std::fs::remove_file(&db_file);
 
-    let mut db = AllCardsDb::open(db_file).expect("Could not open <db_file>");
+    let mut db = AllCardsDb::open(&db_file).expect("Could not open <db_file>");
 
     let cards_arr = match json_cards {
         serde_json::Value::Array(values) => values,
@@ -307,13 +546,14 @@ fn main() -> io::Result<()> {
     };
 
     let rdr = BufReader::new(File::open(sets_file).expect("Can't open <cards_file>"));
-    let sets = serde_json::from_reader::<_, serde_json::Value>(rdr)
-        .map(process_sets_map)
+    let sets_json = serde_json::from_reader::<_, serde_json::Value>(rdr)
         .expect("Bad data in <sets_file>");
+    let sets = process_sets_map(&sets_json);
 
     let card_last_idx = cards_arr.len() - 1;
 
     let mut cards_already_seen = HashSet::new();
+    let mut oracle_texts = BTreeMap::new();
 
     for (i, card) in cards_arr.into_iter().enumerate() {
         let card = parse_card(card);
@@ -325,15 +565,67 @@ fn main() -> io::Result<()> {
         let cardref = sets
             .get(&card.name)
             .expect(&format!("'{}' must have a collector's number", card.name));
+
+        let mut card_rulings = CardRulings {
+            rulings: rulings
+                .remove(&card.name)
+                .map(|x| parse_rulings(&x))
+                .unwrap_or_default(),
+            oracle_changes: Vec::new(),
+        };
+        if let Some((old_text, history)) = previous_oracle.remove(&card.name) {
+            card_rulings.oracle_changes = history;
+            if old_text != card.oracle_text {
+                card_rulings.oracle_changes.push(OracleChange {
+                    replaced: today,
+                    old_text,
+                });
+            }
+        }
+        oracle_texts.insert(
+            card.name.clone(),
+            (card.oracle_text.clone(), card_rulings.oracle_changes.clone()),
+        );
+        db.add_rulings(cardref, card_rulings);
+
+        if let Some(names) = foreign_names.remove(&card.name) {
+            db.add_foreign_names(cardref, parse_foreign_names(&names));
+        }
+
+        if let Some(card_legalities) = legalities.remove(&card.name) {
+            db.add_legalities(cardref, parse_legalities(&card_legalities));
+        }
+
         db.add(cardref, card);
 
         eprint!("{i}/{card_last_idx} \u{1b}[0E");
     }
 
+    eprintln!("Adding printings...");
+    for (name, uuid, (mtgo_id, mtgo_foil_id), printing) in process_printings(&sets_json, &sets) {
+        if cards_already_seen.contains(&name) {
+            if let Some(uuid) = uuid {
+                db.add_printing_uuid(&uuid, &printing.card_ref);
+            }
+            db.add_mtgo_ids(&printing.card_ref, mtgo_id, mtgo_foil_id);
+            db.add_printing(printing);
+        }
+    }
+
+    if let Some(set_info_file) = set_info_file {
+        let rdr = BufReader::new(File::open(set_info_file).expect("Can't open <set_info_file>"));
+        let set_infos: Vec<serde_json::Value> =
+            serde_json::from_reader(rdr).expect("Bad data in <set_info_file>");
+
+        for set in set_infos.iter() {
+            db.add_set(parse_set_info(set));
+        }
+    }
+
     eprintln!("Garbage collecting DB...");
     db.condense();
 
-    Ok(())
+    write_oracle_texts(&db_file, &oracle_texts)
 }
 
 #[cfg(test)]
@@ -346,6 +638,36 @@ mod test {
     use minimal_storage::serialize_min::{DeserializeFromMinimal, SerializeMinimal};
     use project::data_model::card::Card;
 
+    #[test]
+    fn oracle_history_roundtrip() {
+        use project::data_model::ruling::OracleChange;
+        use std::collections::BTreeMap;
+
+        let db_file = std::env::temp_dir().join(format!("build-card-db-test-{}.db", std::process::id()));
+        let db_file = db_file.to_str().unwrap();
+
+        let mut texts = BTreeMap::new();
+        texts.insert(
+            "Lightning Bolt".to_string(),
+            (
+                "Lightning Bolt deals 3 damage to any target.".to_string(),
+                vec![OracleChange {
+                    replaced: "2019-01-31".parse().unwrap(),
+                    old_text: "Lightning Bolt deals 3 damage to target creature or player.".to_string(),
+                }],
+            ),
+        );
+        crate::write_oracle_texts(db_file, &texts).unwrap();
+
+        let read = crate::previous_oracle_texts(db_file);
+        assert_eq!(read.len(), 1);
+        assert_eq!(read["Lightning Bolt"], texts["Lightning Bolt"]);
+
+        //a file from another version is ignored rather than misread
+        std::fs::write(crate::oracle_history_file(db_file), r#"{"version": 0, "cards": {}}"#).unwrap();
+        assert!(crate::previous_oracle_texts(db_file).is_empty());
+    }
+
     #[test]
     fn serde_deserde_cards() {
         let cards_file = "data/cards.json";
@@ -0,0 +1,45 @@
+use project::dbs::allcards::AllCardsDb;
+use project::dbs::collection::CollectionDb;
+use project::dbs::saving_decks::GroupDb;
+use project::formats::collection_csv::{ALL_PROFILES, write_collection_db_csv, write_group_csv};
+use project::formats::mtgjson::{write_mtgjson_collection, write_mtgjson_group};
+use std::io::{self, BufWriter};
+
+const USAGE: &str = "Usage: export_collection <db_file> <moxfield|deckbox|archidekt|manabox|tcgplayer|mtgjson> [group name]";
+
+fn main() -> io::Result<()> {
+    let db_file = std::env::args().nth(1).expect(USAGE);
+    let format = std::env::args().nth(2).expect(USAGE);
+    let group_name = std::env::args().nth(3);
+
+    let cards = AllCardsDb::open(db_file).expect("Could not open <db_file>");
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    let group = group_name.map(|name| {
+        let groups = GroupDb::open_in_save_directory().expect("Could not open the group DB");
+        groups
+            .find_by_name(None, &name)
+            .map(|(_, group)| group)
+            .expect("No group with that name")
+    });
+
+    let open_collection =
+        || CollectionDb::open_in_save_directory().expect("Could not open the collection DB");
+
+    if format.eq_ignore_ascii_case("mtgjson") {
+        return match group {
+            Some(group) => write_mtgjson_group(&cards, &group, &mut out),
+            None => write_mtgjson_collection(&cards, &open_collection(), &mut out),
+        };
+    }
+
+    let profile = ALL_PROFILES
+        .into_iter()
+        .find(|x| x.service.eq_ignore_ascii_case(&format))
+        .expect(USAGE);
+
+    match group {
+        Some(group) => write_group_csv(&cards, &group, profile, &mut out),
+        None => write_collection_db_csv(&cards, &open_collection(), profile, &mut out),
+    }
+}
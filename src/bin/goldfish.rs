@@ -0,0 +1,84 @@
+use project::analysis::goldfish::{GoldfishConfig, goldfish, sample_hand};
+use project::analysis::probability::PlayOrDraw;
+use project::dbs::allcards::AllCardsDb;
+use project::formats::DeckFormat;
+use std::io;
+use std::path::Path;
+
+const USAGE: &str = "Usage: goldfish [--hand] <db_file> <deck_file> [seed] [games] [turns] [play|draw]\n  --hand: just draw one opening hand with the seed";
+
+fn main() -> io::Result<()> {
+    //--hand can go anywhere; everything else is positional
+    let show_hand = std::env::args().any(|x| x == "--hand");
+    let args: Vec<String> = std::env::args().filter(|x| x != "--hand").collect();
+
+    let db_file = args.get(1).expect(USAGE);
+    let deck_file = args.get(2).expect(USAGE);
+    let mut config = GoldfishConfig::default();
+    if let Some(seed) = args.get(3) {
+        config.seed = seed.parse().expect(USAGE);
+    }
+    if let Some(games) = args.get(4) {
+        config.games = games.parse().expect(USAGE);
+    }
+    if let Some(turns) = args.get(5) {
+        config.turns = turns.parse().expect(USAGE);
+    }
+    if let Some(play_or_draw) = args.get(6) {
+        config.play_or_draw = match play_or_draw.as_str() {
+            "play" => PlayOrDraw::Play,
+            "draw" => PlayOrDraw::Draw,
+            _ => panic!("{USAGE}"),
+        };
+    }
+
+    let db = AllCardsDb::open(db_file).expect("Could not open <db_file>");
+
+    let format = Path::new(deck_file)
+        .extension()
+        .and_then(|x| x.to_str())
+        .and_then(DeckFormat::from_extension)
+        .unwrap_or(DeckFormat::Text);
+    let imported = format
+        .import(&db, &std::fs::read_to_string(deck_file)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", e.line, e.message)))?;
+    for line in imported.unresolved.iter() {
+        eprintln!("Line {}: unknown card '{}'", line.line, line.name);
+    }
+
+    if show_hand {
+        let hand = sample_hand(&db, &imported.deck, config.seed, config.max_mulligans);
+        println!("Seed {}, {} mulligans", config.seed, hand.mulligans);
+        for card in hand.cards.iter() {
+            println!("  {card}");
+        }
+        for card in hand.bottomed.iter() {
+            println!("  {card} (bottomed)");
+        }
+        return Ok(());
+    }
+
+    let report = goldfish(&db, &imported.deck, config);
+
+    println!(
+        "{} games, seed {}, {:.2} mulligans on average",
+        report.games, config.seed, report.average_mulligans
+    );
+    for (turn, (lands, mana)) in report.lands_by_turn.iter().zip(report.mana_by_turn.iter()).enumerate() {
+        println!("Turn {}: {lands:.2} lands, {mana:.2} untapped", turn + 1);
+    }
+
+    println!();
+    println!("Castable on curve (any hand / in hand):");
+    for card in report.cards.iter() {
+        println!(
+            "T{} {:>5.1}% {:>5.1}%  {}",
+            card.turn,
+            card.castable * 100.0,
+            card.drawn_and_castable * 100.0,
+            card.name
+        );
+    }
+
+    Ok(())
+}
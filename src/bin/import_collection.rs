@@ -0,0 +1,61 @@
+use project::dbs::allcards::AllCardsDb;
+use project::dbs::collection::CollectionDb;
+use project::formats::collection_csv::{
+    ALL_PROFILES, CsvRowIssue, import_collection_csv, write_problem_rows_csv,
+};
+use std::io;
+
+const USAGE: &str = "Usage: import_collection <export.csv> <db_file> [moxfield|archidekt|deckbox|manabox|tcgplayer]";
+
+fn main() -> io::Result<()> {
+    let csv_file = std::env::args().nth(1).expect(USAGE);
+    let db_file = std::env::args().nth(2).expect(USAGE);
+    let profile = std::env::args().nth(3).map(|service| {
+        ALL_PROFILES
+            .into_iter()
+            .find(|x| x.service.eq_ignore_ascii_case(&service))
+            .expect(USAGE)
+    });
+
+    let cards = AllCardsDb::open(db_file).expect("Could not open <db_file>");
+    let mut collection =
+        CollectionDb::open_in_save_directory().expect("Could not open the collection DB");
+
+    let text = std::fs::read_to_string(&csv_file)?;
+    let report = import_collection_csv(&cards, &collection, &text, profile)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", e.line, e.message)))?;
+
+    eprintln!(
+        "Imported {} copies from {} rows",
+        report.imported_copies, report.imported_rows
+    );
+    for problem in report.problems.iter() {
+        let row = &problem.row;
+        let issue = match &problem.issue {
+            CsvRowIssue::UnknownCard => "no card with this name".to_string(),
+            CsvRowIssue::UnknownPrinting => "no such printing".to_string(),
+            CsvRowIssue::Ambiguous(candidates) => format!("could be {} printings", candidates.len()),
+        };
+        eprintln!(
+            "Line {}: {} ({} {}): {}",
+            row.line,
+            row.name,
+            row.set_code.as_deref().or(row.set_name.as_deref()).unwrap_or("?"),
+            row.collector_number.as_deref().unwrap_or("?"),
+            issue
+        );
+    }
+
+    //the good rows are already in, so only the rest should be imported again
+    if !report.problems.is_empty() {
+        let problems_file = format!("{csv_file}.problems.csv");
+        let mut out = io::BufWriter::new(std::fs::File::create(&problems_file)?);
+        write_problem_rows_csv(&text, &report, &mut out)?;
+        eprintln!("The rows left out are in {problems_file}; fix them there and import that file");
+    }
+
+    eprintln!("Garbage collecting DB...");
+    collection.condense();
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use project::dbs::allcards::AllCardsDb;
+use project::dbs::prices::PriceDb;
+use project::dbs::prices::import::import_mtgjson_prices;
+use std::io;
+
+const USAGE: &str = "Usage: import_prices <AllPrices.json or AllPricesToday.json> <db_file>";
+
+fn main() -> io::Result<()> {
+    let prices_file = std::env::args().nth(1).expect(USAGE);
+    let db_file = std::env::args().nth(2).expect(USAGE);
+
+    let cards = AllCardsDb::open(db_file).expect("Could not open <db_file>");
+    let mut prices = PriceDb::open_in_save_directory().expect("Could not open the price DB");
+
+    let summary = import_mtgjson_prices(prices_file, &cards, &prices)?;
+
+    eprintln!(
+        "Added {} prices ({} were already known, {} printings weren't in <db_file>)",
+        summary.added, summary.already_known, summary.unknown_printings
+    );
+
+    eprintln!("Garbage collecting DB...");
+    prices.condense();
+
+    Ok(())
+}
@@ -11,19 +11,39 @@ use ratatui::{
     layout::{Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Style, Stylize},
     text::{Line, Text},
-    widgets::{Block, List, ListState, Paragraph},
+    widgets::{Bar, BarChart, BarGroup, Block, List, ListState, Paragraph, Wrap},
 };
 use serde_json;
 use std::any::type_name;
 use std::cell::OnceCell;
-use std::fs::{File, read_dir, read_to_string};
-use std::io::{self, BufRead, BufReader};
+use std::fs::{read_dir, read_to_string};
+use std::io;
 use std::path::Path;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use project::analysis::bracket::{BracketEstimate, ComboList, estimate_bracket};
+use project::analysis::stats::{CURVE_LEN, DeckStats, deck_stats};
+use project::analysis::validate::{Violation, validate};
+use project::data_model::card::{Card, Finish};
+use project::data_model::collection::CollectionEntry;
+use project::data_model::deck::{Deck, DeckSection};
+use project::data_model::group::{CardGroup, CardGroupVariety, GroupId};
+use project::data_model::legality::GameFormat;
+use project::data_model::price::Currency;
 use project::dbs::allcards::AllCardsDb;
+use project::dbs::collection::CollectionDb;
+use project::dbs::journal::{Journal, JournalTarget};
+use project::dbs::prices::PriceDb;
+use project::dbs::saving_decks::GroupDb;
+use project::dbs::saving_decks::allocation::{AllocationErr, Allocator};
+use project::dbs::saving_decks::versions::DeckVersionDb;
+use project::dbs::tags::TagDb;
+use project::formats::reconcile::{Reconciler, Reconciliation};
+use project::formats::{DeckFormat, DeckLine};
+use project::data_model::tag::TagTarget;
+use project::query::context::QuerySources;
 use project::query::{self, start_query_running_background_threads};
 
 #[derive(PartialEq)]
@@ -33,8 +53,29 @@ enum InputMode {
     Decklist,
     Saving,
     Opening,
+    Tagging,
+    /// Going through the lines of an imported deck whose cards weren't found exactly
+    Reviewing,
+    /// Asking whether to replace a different stored deck with the same name
+    Replacing { saving: bool },
 }
 
+///
+/// What's shown about the deck as a whole. It's worked out again whenever the deck
+/// or format changes, rather than on every redraw.
+struct DeckSummary {
+    violations: Vec<Violation>,
+    /// Only for Commander decks
+    bracket: Option<BracketEstimate>,
+    /// What the played sections cost, when there are prices
+    cents: Option<usize>,
+    /// What the stats pane shows
+    stats: Option<DeckStats>,
+}
+
+/// How many of a printing's prices the details show
+const PRICE_HISTORY_LEN: usize = 5;
+
 // Setup App struct
 struct App {
     search: String,
@@ -43,11 +84,44 @@ struct App {
     exit: bool,
     results: Vec<String>,
     selected: usize,
-    decklist: Vec<String>,
+    deck: Deck,
     decklist_selected: usize,
+    /// The section cards get added to from the search results
+    deck_section: DeckSection,
+    /// Whether the details pane shows the deck's stats instead of a card
+    show_stats: bool,
+    /// The format the deck's checked against as it's edited
+    format: GameFormat,
+    summary: DeckSummary,
+    /// For estimating Commander brackets; empty unless there's a `combos.txt` in the save directory
+    combos: ComboList,
+    /// The stored deck being edited, once it's been saved.
+    /// Edits to a stored deck go straight into the group store, so they can be undone.
+    deck_id: Option<GroupId>,
+    /// The journal's next seq when the deck was stored, so undo doesn't reach back past it
+    undo_since: u128,
     deckname: String,
     file_selected: usize,
     files: Vec<String>,
+    sources: Option<QuerySources>,
+    /// Every save of a deck is kept here, when there's a save directory
+    versions: Option<DeckVersionDb>,
+    /// What the collection's worth, when there are prices
+    collection_cents: Option<usize>,
+    journal: Option<Arc<Journal>>,
+    tag_input: String,
+    /// Whether the tag goes on every search result, rather than just the selected card
+    tag_all_results: bool,
+    /// The mode to go back to once tagging's done
+    tagging_from: Option<InputMode>,
+    /// Which of the card's copies in the collection gets tagged, rather than the card itself.
+    /// When tagging all results, any copy means all of their copies.
+    tag_copy: Option<usize>,
+    reconciliation: Reconciliation,
+    /// The lines of `reconciliation` still to be reviewed
+    review: Vec<usize>,
+    /// The selected candidate; one past the last candidate is skipping the line
+    review_selected: usize,
 }
 
 // Implement App
@@ -61,11 +135,34 @@ impl App {
             err_line: None,
             results: Vec::new(),
             selected: 0,
-            decklist: Vec::new(),
+            deck: Deck::new(),
             decklist_selected: 0,
+            deck_section: DeckSection::Main,
+            show_stats: false,
+            format: GameFormat::Standard,
+            summary: DeckSummary {
+                violations: Vec::new(),
+                bracket: None,
+                cents: None,
+                stats: None,
+            },
+            combos: ComboList { combos: Vec::new() },
+            deck_id: None,
+            undo_since: 0,
             deckname: String::new(),
             file_selected: 0,
             files: Vec::new(),
+            sources: None,
+            versions: None,
+            collection_cents: None,
+            journal: None,
+            tag_input: String::new(),
+            tag_all_results: false,
+            tagging_from: None,
+            tag_copy: None,
+            reconciliation: Reconciliation { lines: Vec::new() },
+            review: Vec::new(),
+            review_selected: 0,
         }
     }
 
@@ -76,10 +173,41 @@ impl App {
         let db = AllCardsDb::open(db_file)?;
         let db = Arc::new(db);
 
-        // TEMP
-        // TEMP
+        // prices are optional; they only exist once `import_prices` has been run
+        let prices = PriceDb::open_in_save_directory().ok().map(Arc::new);
+        self.journal = Journal::open_in_save_directory().ok().map(Arc::new);
+        let collection = CollectionDb::open_in_save_directory().ok().map(|x| match &self.journal {
+            Some(journal) => Arc::new(x.with_journal(journal.clone())),
+            None => Arc::new(x),
+        });
+        let groups = GroupDb::open_in_save_directory().ok().map(|x| match &self.journal {
+            Some(journal) => Arc::new(x.with_journal(journal.clone())),
+            None => Arc::new(x),
+        });
+        let tags = TagDb::open_in_save_directory().ok().map(Arc::new);
+        self.versions = DeckVersionDb::open_in_save_directory().ok();
+        self.combos = ComboList::open_in_save_directory().unwrap_or_default();
+
+        let sources = QuerySources {
+            cards: db,
+            prices,
+            collection,
+            groups,
+            tags,
+        };
+        self.sources = Some(sources.clone());
+        //the collection isn't edited from here, so this only needs working out once
+        if let (Some(prices), Some(collection)) = (&sources.prices, &sources.collection) {
+            let entries = collection.entries();
+            self.collection_cents = Some(prices.value_of(
+                entries
+                    .iter()
+                    .map(|x| (&x.card.card, x.card.finish, x.card.duplicates)),
+                Currency::Usd,
+            ));
+        }
 
-        let (mut query_sender, mut search_result_receiver) = start_query_running_background_threads(db);
+        let (mut query_sender, mut search_result_receiver) = start_query_running_background_threads(sources);
 
         term.draw(|frame| self.draw(frame))?; // Drawing
 
@@ -102,6 +230,73 @@ impl App {
                     center(frame.area(), Constraint::Length(20), Constraint::Length(3));
                 frame.render_widget(text_pop, popup_area);
             }
+            InputMode::Replacing { .. } => {
+                let text_pop = Paragraph::new("Y: Replace it | N: Keep it")
+                    .block(Block::bordered().title(format!("Replace the stored {}?", self.deckname)));
+                let popup_area =
+                    center(frame.area(), Constraint::Length(50), Constraint::Length(3));
+                frame.render_widget(text_pop, popup_area);
+            }
+            InputMode::Tagging => {
+                let target = match (self.tag_copy, self.tag_all_results) {
+                    (None, true) => format!("all {} results", self.results.len()),
+                    (None, false) => "the card".to_string(),
+                    (Some(_), true) => format!("every copy of all {} results", self.results.len()),
+                    (Some(i), false) => {
+                        let copies = self
+                            .tagging_cards()
+                            .first()
+                            .map(|card| self.owned_entries(card))
+                            .unwrap_or_default();
+                        match copies.get(i) {
+                            Some(copy) => format!(
+                                "copy {}/{}: {} {} {} {} x{}",
+                                i + 1,
+                                copies.len(),
+                                copy.card.card.set,
+                                copy.card.card.collector_number,
+                                copy.card.finish.name(),
+                                copy.card.condition.abbreviation(),
+                                copy.card.duplicates
+                            ),
+                            None => "the card".to_string(),
+                        }
+                    }
+                };
+                let title = format!("Tag {target} (-tag to remove, Tab: card/copies)");
+                let text_pop =
+                    Paragraph::new(self.tag_input.as_str()).block(Block::bordered().title(title));
+                let popup_area =
+                    center(frame.area(), Constraint::Length(70), Constraint::Length(3));
+                frame.render_widget(text_pop, popup_area);
+            }
+            InputMode::Reviewing => {
+                let Some(line) = self.review.first().and_then(|i| self.reconciliation.lines.get(*i)) else {
+                    return;
+                };
+                let title = format!(
+                    "Line {}: {} ({} left)",
+                    line.line.line,
+                    line.line.name,
+                    self.review.len()
+                );
+                let mut items = line
+                    .resolution
+                    .candidates()
+                    .iter()
+                    .map(|x| format!("{} ({} {})", x.name, x.card.set, x.card.collector_number))
+                    .collect::<Vec<_>>();
+                items.push("Leave it out".to_string());
+
+                let mut review_state = ListState::default();
+                review_state.select(Some(self.review_selected));
+                let review_popup = List::new(items)
+                    .block(Block::bordered().title(title))
+                    .highlight_style(Style::new().reversed());
+                let review_area =
+                    center(frame.area(), Constraint::Length(60), Constraint::Length(10));
+                frame.render_stateful_widget(review_popup, review_area, &mut review_state);
+            }
             InputMode::Opening => {
                 let mut open_state = ListState::default();
                 let open_popup = List::new(self.files.clone())
@@ -134,7 +329,14 @@ impl App {
 
                 let help_area = left[0]; // Area for keybinds/help text
                 let input_area = left[1]; // Area for input box                 || TODO: Refactor to searchbar
-                let body_area = left[2]; // Area for results box               || TODO: Rename this
+                // Results on the left, details of the selected card on the right
+                let middle = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(left[2]);
+
+                let body_area = middle[0]; // Area for results box               || TODO: Rename this
+                let details_area = middle[1]; // Area for the selected card's details
                 let decklist_area = total[1]; // Area for decklist (rename this?)
                 let results_height = body_area.height as usize - 2;
                 let mut offset = 0;
@@ -153,37 +355,37 @@ impl App {
                 let (line, style) = match self.input_mode {
                     InputMode::Normal => (
                         Line::from(
-                            "Normal | A: Add | F: Decklist | Q: Quit | /: Search | Ctrl-S: Save | [K/J]: Up/Down",
+                            "Normal | Enter: Add | S: Section | V: Format | I: Stats | F: Decklist | Q: Quit | /: Search | T: Tag | Shift-T: Tag all | U: Undo | Shift-U: Redo | Ctrl-S: Save | [K/J]: Up/Down",
                         ),
                         Style::default(),
                     ),
                     InputMode::Editing => (
                         match &self.err_line {
-                            Some(msg) => Line::default().spans([
-                                Span::from("Search | "),
-                                match msg.msg_type {
-                                    Warning => Span::styled("", (Color::Yellow, Modifier::BOLD)),
-                                    Error => Span::styled("", (Color::Red, Modifier::BOLD)),
-                                },
-                                Span::from(" "),
-                                Span::styled(&msg.msg_content, match msg.msg_type {
-                                    Warning => Color::Yellow,
-                                    Error => Color::Red
-                                })
-                            ]),
+                            Some(msg) => Line::default().spans([Span::from("Search | ")].into_iter().chain(message_spans(msg))),
                             None => Line::from("Search | Esc/Enter: Results"),
                         },
                         Style::default(),
                     ),
                     InputMode::Decklist => (
                         Line::from(
-                            "Decklist | Enter: Add | D: Delete | Ctrl-S: Save | Esc: Results | Q: Quit | [K/J]: Up/Down",
+                            "Decklist | Enter: Add | D: Delete | S: Move section | V: Format | I: Stats | T: Tag | U: Undo | Shift-U: Redo | Ctrl-S: Save | Esc: Results | Q: Quit | [K/J]: Up/Down",
                         ),
                         Style::default(),
                     ),
                     InputMode::Saving => (Line::from("Saving"), Style::default()),
                     InputMode::Opening => (Line::from("Opening"), Style::default()),
+                    InputMode::Tagging => (Line::from("Tagging"), Style::default()),
+                    InputMode::Reviewing => (Line::from("Reviewing"), Style::default()),
+                    InputMode::Replacing { .. } => (Line::from("Replacing"), Style::default()),
                 };
+                //anything that went wrong outside of searching, e.g. a file that wouldn't open
+                let mut line = line;
+                if let Some(msg) = self.err_line.as_ref().filter(|_| self.input_mode != InputMode::Editing) {
+                    line.push_span(" | ");
+                    for span in message_spans(msg) {
+                        line.push_span(span);
+                    }
+                }
                 let text = Text::from(line).patch_style(style);
                 let help_msg = Paragraph::new(text);
 
@@ -194,17 +396,21 @@ impl App {
                 }
 
                 let mut state = ListState::default();
+                let results_title = match self.collection_cents {
+                    Some(cents) => format!("Results | Collection worth {}", dollars(cents)),
+                    None => "Results".to_string(),
+                };
                 let body = if self.results.len() > results_height {
                     List::new(
                         self.results[offset..(results_height + offset)]
                             .iter()
                             .map(String::as_str),
                     )
-                    .block(Block::bordered().title("Results"))
+                    .block(Block::bordered().title(results_title))
                     .highlight_style(Style::new().reversed())
                 } else {
                     List::new(self.results.clone())
-                        .block(Block::bordered().title("Results"))
+                        .block(Block::bordered().title(results_title))
                         .highlight_style(Style::new().reversed())
                 };
 
@@ -215,8 +421,10 @@ impl App {
 
                 // Decklist area
                 let mut deck_state = ListState::default();
-                let decklist = List::new(self.decklist.clone())
-                    .block(Block::bordered().title("Decklist"))
+                let violations = &self.summary.violations;
+                let bracket = self.summary.bracket.as_ref();
+                let decklist = List::new(self.deck_lines())
+                    .block(Block::bordered().title(self.decklist_title()))
                     .highlight_style(Style::new().reversed());
 
                 match self.input_mode {
@@ -224,8 +432,32 @@ impl App {
                     _ => deck_state.select(None),
                 }
 
+                let mut details_text = self.card_details();
+                if self.input_mode == InputMode::Decklist && !violations.is_empty() {
+                    details_text.push_line(Line::default());
+                    details_text.push_line(Line::from(format!("Not legal in {}", self.format.name())).bold());
+                    for violation in violations.iter() {
+                        details_text.push_line(Line::from(violation.to_string()).red());
+                    }
+                }
+                if let Some(bracket) = bracket.filter(|_| self.input_mode == InputMode::Decklist) {
+                    details_text.push_line(Line::default());
+                    details_text.push_line(Line::from(bracket.bracket.to_string()).bold());
+                    for reason in bracket.reasons.iter() {
+                        details_text.push_line(Line::from(reason.to_string()));
+                    }
+                }
+                let details = Paragraph::new(details_text)
+                    .wrap(Wrap { trim: false })
+                    .block(Block::bordered().title("Details"));
+
                 // Render stuff
                 frame.render_widget(help_msg, help_area);
+                if self.show_stats {
+                    self.draw_stats(frame, details_area);
+                } else {
+                    frame.render_widget(details, details_area);
+                }
                 frame.render_widget(search, input_area);
                 frame.render_stateful_widget(body, body_area, &mut state);
                 frame.render_stateful_widget(decklist, decklist_area, &mut deck_state);
@@ -235,6 +467,598 @@ impl App {
         // }
     }
 
+    fn draw_stats(&self, frame: &mut Frame, area: Rect) {
+        let Some(stats) = &self.summary.stats else {
+            return;
+        };
+
+        let [curve_area, colors_area, summary_area] = Layout::vertical([
+            Constraint::Min(8),
+            Constraint::Length(10),
+            Constraint::Length(4),
+        ])
+        .areas(area);
+
+        let mut curve = BarChart::default()
+            .block(Block::bordered().title("Curve"))
+            .bar_width(2)
+            .bar_gap(1)
+            .group_gap(2);
+        for section_curve in stats.curves.iter() {
+            let bars: Vec<Bar> = section_curve
+                .counts
+                .iter()
+                .enumerate()
+                .map(|(mana_value, count)| {
+                    let label = if mana_value == CURVE_LEN - 1 {
+                        format!("{mana_value}+")
+                    } else {
+                        mana_value.to_string()
+                    };
+                    Bar::default().value(*count as u64).label(Line::from(label))
+                })
+                .collect();
+            curve = curve.data(
+                BarGroup::default()
+                    .label(Line::from(section_curve.section.name()))
+                    .bars(&bars),
+            );
+        }
+
+        //pips and sources side by side for each color in WUBRG order, as percentages of their totals
+        let bar_colors = [Color::White, Color::Blue, Color::Magenta, Color::Red, Color::Green];
+        let mut colors = BarChart::default()
+            .block(Block::bordered().title("Pips % / Sources %"))
+            .bar_width(3)
+            .bar_gap(0)
+            .group_gap(2);
+        for (i, balance) in stats.colors.iter().enumerate() {
+            if balance.pips == 0 && balance.sources == 0 {
+                continue;
+            }
+            let bars = [
+                Bar::default()
+                    .value((balance.pip_share * 100.0).round() as u64)
+                    .style(Style::new().fg(bar_colors[i])),
+                Bar::default()
+                    .value((balance.source_share * 100.0).round() as u64)
+                    .style(Style::new().fg(bar_colors[i]).add_modifier(Modifier::DIM)),
+            ];
+            let letter = ["W", "U", "B", "R", "G"][i];
+            colors = colors.data(BarGroup::default().label(Line::from(letter)).bars(&bars));
+        }
+
+        let total = stats.total().max(1);
+        let summary = Paragraph::new(vec![
+            Line::from(format!(
+                "Lands {} ({}%) | Creatures {} ({}%) | Other {} ({}%)",
+                stats.lands,
+                stats.lands * 100 / total,
+                stats.creatures,
+                stats.creatures * 100 / total,
+                stats.other_spells,
+                stats.other_spells * 100 / total,
+            )),
+            Line::from(format!(
+                "Average MV {:.2} ({:.2} with lands)",
+                stats.average_mana_value, stats.average_mana_value_with_lands
+            )),
+        ])
+        .block(Block::bordered().title("Stats"));
+
+        frame.render_widget(curve, curve_area);
+        frame.render_widget(colors, colors_area);
+        frame.render_widget(summary, summary_area);
+    }
+
+    fn card_details(&self) -> Text<'static> {
+        let Some(sources) = &self.sources else {
+            return Text::default();
+        };
+        let selected = match self.input_mode {
+            InputMode::Decklist => self.selected_deck_card(),
+            _ => self
+                .results
+                .get(self.selected)
+                .and_then(|name| sources.cards.card_by_name(name)),
+        };
+        let Some(card) = selected else {
+            return Text::default();
+        };
+
+        let mut lines = vec![
+            Line::from(card.name.clone()).bold(),
+            Line::default(),
+        ];
+        lines.extend(card.oracle_text.lines().map(|x| Line::from(x.to_string())));
+
+        lines.push(Line::default());
+        lines.push(Line::from("Printed in").bold());
+        for set in card.sets_released.iter() {
+            match sources.cards.set_info(set) {
+                Some(info) => lines.push(Line::from(format!(
+                    "{} ({}, {})",
+                    info.name, info.code, info.release_date
+                ))),
+                None => lines.push(Line::from(set.clone())),
+            }
+        }
+
+        if let (Some(tags), Some(card_ref)) = (&sources.tags, sources.cards.card_ref(&card)) {
+            let card_tags = tags.tags_of(&TagTarget::Card(card_ref));
+            if !card_tags.is_empty() {
+                lines.push(Line::default());
+                lines.push(Line::from(format!("Tags: {}", card_tags.join(", "))).italic());
+            }
+            for copy in self.owned_entries(&card) {
+                let copy_tags = tags.tags_of(&TagTarget::Copy(copy.id));
+                if !copy_tags.is_empty() {
+                    lines.push(
+                        Line::from(format!(
+                            "Tags on {} {} {}: {}",
+                            copy.card.card.set,
+                            copy.card.card.collector_number,
+                            copy.card.finish.name(),
+                            copy_tags.join(", ")
+                        ))
+                        .italic(),
+                    );
+                }
+            }
+        }
+
+        if let Some(prices) = &sources.prices {
+            //the most recent few prices of each printing and finish that has any
+            let mut price_lines = Vec::new();
+            for printing in sources.cards.printings(&card) {
+                for finish in Finish::ALL {
+                    let history = prices.history(&printing.card_ref, finish, Currency::Usd);
+                    if history.is_empty() {
+                        continue;
+                    }
+                    let points = history[history.len().saturating_sub(PRICE_HISTORY_LEN)..]
+                        .iter()
+                        .map(|x| format!("{} ({})", dollars(x.cents), x.date))
+                        .collect::<Vec<_>>();
+                    price_lines.push(Line::from(format!(
+                        "{} {} {}: {}",
+                        printing.card_ref.set,
+                        printing.card_ref.collector_number,
+                        finish.name(),
+                        points.join(" → ")
+                    )));
+                }
+            }
+            if !price_lines.is_empty() {
+                lines.push(Line::default());
+                lines.push(Line::from("Prices (USD)").bold());
+                lines.extend(price_lines);
+            }
+        }
+
+        let rulings = sources.cards.rulings(&card);
+        if !rulings.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("Rulings").bold());
+            for ruling in rulings {
+                lines.push(Line::from(format!("{}: {}", ruling.date, ruling.text)));
+            }
+        }
+
+        let history = sources.cards.oracle_history(&card);
+        if !history.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from("Previous oracle text").bold());
+            for change in history {
+                lines.push(Line::from(format!("Until {}:", change.replaced)).italic());
+                lines.extend(change.old_text.lines().map(|x| Line::from(x.to_string())));
+            }
+        }
+
+        Text::from(lines)
+    }
+
+    /// The candidates for the line being reviewed, plus leaving it out
+    fn review_options(&self) -> usize {
+        self.review
+            .first()
+            .and_then(|i| self.reconciliation.lines.get(*i))
+            .map_or(0, |x| x.resolution.candidates().len())
+            + 1
+    }
+
+    fn finish_review(&mut self) {
+        let imported = self.reconciliation.to_imported();
+        self.deck = imported.deck;
+        self.summarize_deck();
+        //lines that weren't matched to a card (and weren't skipped on purpose) aren't in the deck
+        if !imported.unresolved.is_empty() {
+            let lines = imported
+                .unresolved
+                .iter()
+                .map(|x| format!("{} (line {})", x.name, x.line))
+                .collect::<Vec<_>>();
+            self.err_line = Some(Message {
+                msg_type: Warning,
+                msg_content: format!("Left out {}: {}", lines.len(), lines.join(", ")),
+                byte_pos: 0,
+                source_phase_index: 0,
+            });
+        }
+        self.review.clear();
+        self.decklist_selected = 0;
+        self.deck_id = None;
+        self.input_mode = InputMode::Normal;
+        if !self.store_deck(false) {
+            self.input_mode = InputMode::Replacing { saving: false };
+        }
+    }
+
+    ///
+    /// Puts an opened deck in the group store, so its edits are journaled from the start.
+    /// Returns false without storing anything if a different stored deck already has the
+    /// name and `replace` isn't set.
+    fn store_deck(&mut self, replace: bool) -> bool {
+        let Some(groups) = self.sources.as_ref().and_then(|x| x.groups.as_ref()) else {
+            return true;
+        };
+        if self.deckname.is_empty() {
+            return true;
+        }
+
+        let existing = groups.find_by_name(Some(CardGroupVariety::Deck), &self.deckname);
+        if !replace
+            && existing
+                .as_ref()
+                .is_some_and(|(id, group)| Some(*id) != self.deck_id && group.cards != self.deck)
+        {
+            return false;
+        }
+
+        //the deck's stored as it was opened, even if it uses copies that exclusive decks
+        //have reserved; it's the edits from here on which are checked
+        let stored = match existing {
+            Some((id, _)) => groups.set_cards(id, self.deck.clone()).map(|_| id),
+            None => {
+                let mut group = CardGroup::new(CardGroupVariety::Deck, self.deckname.clone());
+                group.cards = self.deck.clone();
+                groups.create(group)
+            }
+        };
+        match stored {
+            Ok(id) => {
+                //storing it isn't one of the deck's edits, so it can't be undone from here
+                if self.deck_id != Some(id) {
+                    self.undo_since = self.journal.as_ref().map_or(0, |x| x.next_seq());
+                }
+                self.deck_id = Some(id);
+            }
+            Err(e) => {
+                self.err_line = Some(Message {
+                    msg_type: Error,
+                    msg_content: format!("Couldn't store the deck: {e:?}"),
+                    byte_pos: 0,
+                    source_phase_index: 0,
+                })
+            }
+        }
+        true
+    }
+
+    fn start_tagging(&mut self, all_results: bool) {
+        self.tag_all_results = all_results;
+        self.tag_copy = None;
+        self.tag_input.clear();
+        self.tagging_from = Some(std::mem::replace(&mut self.input_mode, InputMode::Tagging));
+    }
+
+    /// The cards being tagged
+    fn tagging_cards(&self) -> Vec<Card> {
+        let Some(sources) = &self.sources else {
+            return Vec::new();
+        };
+
+        if self.tag_all_results {
+            self.results
+                .iter()
+                .filter_map(|name| sources.cards.card_by_name(name))
+                .collect()
+        } else {
+            match self.tagging_from {
+                Some(InputMode::Decklist) => self.selected_deck_card().into_iter().collect(),
+                _ => self
+                    .results
+                    .get(self.selected)
+                    .and_then(|name| sources.cards.card_by_name(name))
+                    .into_iter()
+                    .collect(),
+            }
+        }
+    }
+
+    /// The collection's entries for any printing of the card
+    fn owned_entries(&self, card: &Card) -> Vec<CollectionEntry> {
+        let Some((cards, collection)) = self
+            .sources
+            .as_ref()
+            .and_then(|x| Some((&x.cards, x.collection.as_ref()?)))
+        else {
+            return Vec::new();
+        };
+
+        cards
+            .printings(card)
+            .iter()
+            .flat_map(|x| collection.entries_of(&x.card_ref))
+            .collect()
+    }
+
+    /// Tab goes from the card to each of its copies in turn, then back to the card
+    fn next_tag_target(&mut self) {
+        let copies = if self.tag_all_results {
+            1
+        } else {
+            self.tagging_cards()
+                .first()
+                .map_or(0, |card| self.owned_entries(card).len())
+        };
+
+        self.tag_copy = match self.tag_copy {
+            None if copies > 0 => Some(0),
+            Some(i) if i + 1 < copies => Some(i + 1),
+            _ => None,
+        };
+    }
+
+    fn apply_tag(&mut self) {
+        let Some(sources) = &self.sources else {
+            return;
+        };
+        let Some(tags) = &sources.tags else {
+            return;
+        };
+
+        let cards = self.tagging_cards();
+        let targets: Vec<TagTarget> = match self.tag_copy {
+            None => cards
+                .iter()
+                .filter_map(|card| sources.cards.card_ref(card))
+                .map(TagTarget::Card)
+                .collect(),
+            Some(_) if self.tag_all_results => cards
+                .iter()
+                .flat_map(|card| self.owned_entries(card))
+                .map(|x| TagTarget::Copy(x.id))
+                .collect(),
+            Some(i) => cards
+                .first()
+                .and_then(|card| self.owned_entries(card).get(i).map(|x| TagTarget::Copy(x.id)))
+                .into_iter()
+                .collect(),
+        };
+
+        match self.tag_input.strip_prefix('-') {
+            Some(tag) => tags.untag_all(targets, tag),
+            None => tags.tag_all(targets, &self.tag_input),
+        };
+    }
+
+    fn deck_lines(&self) -> Vec<String> {
+        self.deck
+            .entries
+            .iter()
+            .map(|entry| {
+                let name = self
+                    .sources
+                    .as_ref()
+                    .and_then(|sources| sources.cards.card_by_ref(&entry.card))
+                    .map(|card| card.name)
+                    .unwrap_or_else(|| format!("{} {}", entry.card.set, entry.card.collector_number));
+                match entry.section {
+                    DeckSection::Main => format!("{} {}", entry.quantity, name),
+                    section => format!("[{}] {} {}", section.name(), entry.quantity, name),
+                }
+            })
+            .collect()
+    }
+
+    fn selected_deck_card(&self) -> Option<Card> {
+        let entry = self.deck.entries.get(self.decklist_selected)?;
+        self.sources.as_ref()?.cards.card_by_ref(&entry.card)
+    }
+
+    fn add_selected_result(&mut self) {
+        let Some(sources) = &self.sources else {
+            return;
+        };
+        let card_ref = self
+            .results
+            .get(self.selected)
+            .and_then(|name| sources.cards.card_by_name(name))
+            .and_then(|card| sources.cards.card_ref(&card));
+        if let Some(card_ref) = card_ref {
+            self.deck.add(self.deck_section, card_ref, 1);
+            self.deck_edited();
+        }
+    }
+
+    ///
+    /// Writes the deck through to the group store if it's a stored deck. With a collection,
+    /// an edit which would use copies an exclusive deck has reserved is undone.
+    fn deck_edited(&mut self) {
+        self.summarize_deck();
+        let (Some(id), Some(sources)) = (self.deck_id, &self.sources) else {
+            return;
+        };
+        let Some(groups) = &sources.groups else {
+            return;
+        };
+        let Some(collection) = &sources.collection else {
+            let _ = groups.set_cards(id, self.deck.clone());
+            return;
+        };
+
+        let allocator = Allocator::new(&sources.cards, collection, groups);
+        let msg_content = match allocator.set_cards(id, self.deck.clone()) {
+            Ok(()) => return,
+            Err(AllocationErr::Conflicts(conflicts)) => {
+                let names = conflicts
+                    .iter()
+                    .map(|x| {
+                        sources
+                            .cards
+                            .card_by_ref(&x.card)
+                            .map_or_else(|| format!("{} {}", x.card.set, x.card.collector_number), |card| card.name)
+                    })
+                    .collect::<Vec<_>>();
+                format!("Not enough copies left over from exclusive decks: {}", names.join(", "))
+            }
+            Err(e) => format!("Couldn't save the edit: {e:?}"),
+        };
+
+        if let Some(group) = groups.get(id) {
+            self.deck = group.cards;
+            self.decklist_selected = self
+                .decklist_selected
+                .min(self.deck.entries.len().saturating_sub(1));
+            self.summarize_deck();
+        }
+        self.err_line = Some(Message {
+            msg_type: Error,
+            msg_content,
+            byte_pos: 0,
+            source_phase_index: 0,
+        });
+    }
+
+    fn undo_or_redo(&mut self, redo: bool) {
+        let (Some(journal), Some(sources)) = (&self.journal, &self.sources) else {
+            return;
+        };
+        //a deck that isn't stored has no steps, so undoing would undo something else
+        let Some(id) = self.deck_id else {
+            self.err_line = Some(Message {
+                msg_type: Warning,
+                msg_content: String::from("Save the deck (Ctrl-S) before undoing its edits"),
+                byte_pos: 0,
+                source_phase_index: 0,
+            });
+            return;
+        };
+        let (Some(collection), Some(groups)) = (&sources.collection, &sources.groups) else {
+            return;
+        };
+
+        //only this deck's steps; the newest step might be another deck's, or the collection's
+        let target = JournalTarget::Group(id);
+        let done = if redo {
+            journal.redo_for(target, self.undo_since, collection, groups)
+        } else {
+            journal.undo_for(target, self.undo_since, collection, groups)
+        };
+        match done {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                self.err_line = Some(Message {
+                    msg_type: Warning,
+                    msg_content: String::from(if redo { "Nothing to redo" } else { "Nothing to undo" }),
+                    byte_pos: 0,
+                    source_phase_index: 0,
+                });
+                return;
+            }
+            Err(e) => {
+                self.err_line = Some(Message {
+                    msg_type: Error,
+                    msg_content: format!("Couldn't undo/redo: {e:?}"),
+                    byte_pos: 0,
+                    source_phase_index: 0,
+                });
+                return;
+            }
+        }
+
+        if let Some(group) = groups.get(id) {
+            self.deck = group.cards;
+            self.decklist_selected = self
+                .decklist_selected
+                .min(self.deck.entries.len().saturating_sub(1));
+            self.summarize_deck();
+        }
+    }
+
+    /// Writes the deck to the text file, and to the group store when there is one
+    fn save_deck(&mut self) -> io::Result<()> {
+        let Some(sources) = &self.sources else {
+            return Ok(());
+        };
+        save_decklist(&sources.cards, &self.deck, &self.deckname)?;
+
+        if !self.store_deck(false) {
+            self.input_mode = InputMode::Replacing { saving: true };
+            return Ok(());
+        }
+        self.snapshot_version();
+        Ok(())
+    }
+
+    fn snapshot_version(&self) {
+        if let (Some(id), Some(versions)) = (self.deck_id, &self.versions) {
+            versions.snapshot(id, &self.deck, "");
+        }
+    }
+
+    /// Called whenever the deck or the format changes
+    fn summarize_deck(&mut self) {
+        let Some(sources) = self.sources.as_ref().filter(|_| !self.deck.entries.is_empty()) else {
+            self.summary = DeckSummary {
+                violations: Vec::new(),
+                bracket: None,
+                cents: None,
+                stats: None,
+            };
+            return;
+        };
+
+        let bracket = (self.format == GameFormat::Commander)
+            .then(|| estimate_bracket(&sources.cards, &self.deck, &self.combos));
+        let cents = sources.prices.as_ref().map(|prices| {
+            self.deck
+                .entries
+                .iter()
+                .filter(|entry| entry.section.is_played())
+                .filter_map(|entry| {
+                    let card = sources.cards.card_by_ref(&entry.card)?;
+                    let price = prices.cheapest(sources.cards.printings(&card).iter(), Currency::Usd)?;
+                    Some(price.cents * entry.quantity)
+                })
+                .sum()
+        });
+
+        self.summary = DeckSummary {
+            violations: validate(&sources.cards, &self.deck, self.format),
+            bracket,
+            cents,
+            stats: Some(deck_stats(&sources.cards, &self.deck)),
+        };
+    }
+
+    fn decklist_title(&self) -> String {
+        let mut badge = match self.summary.violations.len() {
+            0 => format!("{} ✓", self.format.name()),
+            n => format!("{} ✗{n}", self.format.name()),
+        };
+        if let Some(bracket) = &self.summary.bracket {
+            badge = format!("{badge} | B{}", bracket.bracket as u8);
+        }
+        let title = format!("Decklist ({} | adding to {} | {badge})", self.deck.total(), self.deck_section.name());
+
+        match self.summary.cents {
+            Some(cents) => format!("{title} {}", dollars(cents)),
+            None => title,
+        }
+    }
+
     fn handle_events(&mut self, query_sender: &mut Sender<String>, results_receiver: &mut Receiver<(Option<Message>, Vec<String>)>) -> io::Result<bool> {
         if let Ok(t) = results_receiver.try_recv() {
             self.err_line = t.0;
@@ -246,6 +1070,38 @@ impl App {
         }
 
         if let Event::Key(key) = event::read()? {
+            //messages from outside of searching only last until the next key
+            if self.input_mode != InputMode::Editing && key.kind == KeyEventKind::Press {
+                self.err_line = None;
+            }
+
+            //handled first, so that e.g. Ctrl-S doesn't also do what a plain S does
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                match key.code {
+                    KeyCode::Char('s') => {
+                        self.input_mode = InputMode::Saving;
+                        return Ok(true);
+                    }
+                    KeyCode::Char('o') => {
+                        self.input_mode = InputMode::Opening;
+                        self.files =
+                            read_dir(std::env::home_dir().unwrap_or("".into()).join("Downloads"))
+                                .unwrap()
+                                .filter_map(|f| f.ok())
+                                .filter(|f| {
+                                    let name = f.file_name().into_string().unwrap();
+                                    name.rsplit_once('.').is_some_and(|(_, extension)| {
+                                        DeckFormat::from_extension(extension).is_some()
+                                    })
+                                })
+                                .map(|f| f.file_name().into_string().unwrap())
+                                .collect();
+                        return Ok(true);
+                    }
+                    _ => (),
+                }
+            }
+
             match self.input_mode {
                 InputMode::Normal => match key.code {
                     KeyCode::Char('q') => self.exit = true,
@@ -272,9 +1128,33 @@ impl App {
                             }
                         }
                     }
+                    KeyCode::Enter => self.add_selected_result(),
+                    KeyCode::Char('s') => self.deck_section = next_section(self.deck_section),
+                    KeyCode::Char('v') => {
+                        self.format = next_format(self.format);
+                        self.summarize_deck();
+                    }
+                    KeyCode::Char('i') => self.show_stats = !self.show_stats,
+                    KeyCode::Char('u') => self.undo_or_redo(false),
+                    KeyCode::Char('U') => self.undo_or_redo(true),
+                    KeyCode::Char('t') if !self.results.is_empty() => self.start_tagging(false),
+                    KeyCode::Char('T') if !self.results.is_empty() => self.start_tagging(true),
+                    _ => {}
+                },
+                InputMode::Tagging if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Tab => self.next_tag_target(),
                     KeyCode::Enter => {
-                        let sel = self.results[self.selected].clone();
-                        self.decklist.push(sel);
+                        self.apply_tag();
+                        self.input_mode = self.tagging_from.take().unwrap_or(InputMode::Normal);
+                    }
+                    KeyCode::Esc => {
+                        self.input_mode = self.tagging_from.take().unwrap_or(InputMode::Normal);
+                    }
+                    KeyCode::Char(c) => {
+                        self.tag_input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.tag_input.pop();
                     }
                     _ => {}
                 },
@@ -293,22 +1173,46 @@ impl App {
                 },
                 InputMode::Decklist if key.kind == KeyEventKind::Press => match key.code {
                     KeyCode::Char('d') => {
-                        if self.decklist.len() > 1 {
-                            self.decklist.remove(self.decklist_selected);
-                            self.decklist_selected = if self.decklist_selected == 0 {
-                                0
-                            } else {
-                                self.decklist_selected - 1
-                            };
-                        } else if self.decklist.len() == 1 {
-                            self.decklist.remove(self.decklist_selected);
-                            self.input_mode = InputMode::Normal;
+                        if let Some(entry) = self.deck.entries.get(self.decklist_selected).cloned() {
+                            self.deck.remove(entry.section, &entry.card, 1);
+                            self.deck_edited();
+                            if self.deck.entries.is_empty() {
+                                self.decklist_selected = 0;
+                                self.input_mode = InputMode::Normal;
+                            } else if self.decklist_selected >= self.deck.entries.len() {
+                                self.decklist_selected = self.deck.entries.len() - 1;
+                            }
                         }
                     }
                     KeyCode::Enter => {
-                        let sel = self.decklist[self.decklist_selected].clone();
-                        self.decklist.push(sel);
+                        if let Some(entry) = self.deck.entries.get(self.decklist_selected).cloned() {
+                            self.deck.add(entry.section, entry.card, 1);
+                            self.deck_edited();
+                        }
                     }
+                    KeyCode::Char('s') => {
+                        if let Some(entry) = self.deck.entries.get(self.decklist_selected).cloned() {
+                            let to = next_section(entry.section);
+                            self.deck.move_to(entry.section, &entry.card, to);
+                            self.deck_edited();
+                            if let Some(i) = self
+                                .deck
+                                .entries
+                                .iter()
+                                .position(|x| x.section == to && x.card == entry.card)
+                            {
+                                self.decklist_selected = i;
+                            }
+                        }
+                    }
+                    KeyCode::Char('t') if !self.deck.entries.is_empty() => self.start_tagging(false),
+                    KeyCode::Char('v') => {
+                        self.format = next_format(self.format);
+                        self.summarize_deck();
+                    }
+                    KeyCode::Char('i') => self.show_stats = !self.show_stats,
+                    KeyCode::Char('u') => self.undo_or_redo(false),
+                    KeyCode::Char('U') => self.undo_or_redo(true),
                     KeyCode::Char('q') => self.exit = true,
                     KeyCode::Char('/') => {
                         self.input_mode = InputMode::Editing;
@@ -316,9 +1220,9 @@ impl App {
                     }
                     KeyCode::Esc => self.input_mode = InputMode::Normal,
                     KeyCode::Char('j') => {
-                        if self.decklist.len() > 0 {
+                        if self.deck.entries.len() > 0 {
                             self.decklist_selected =
-                                if self.decklist_selected < (self.decklist.len() - 1) {
+                                if self.decklist_selected < (self.deck.entries.len() - 1) {
                                     self.decklist_selected + 1
                                 } else {
                                     0
@@ -326,11 +1230,11 @@ impl App {
                         }
                     }
                     KeyCode::Char('k') => {
-                        if self.decklist.len() > 0 {
+                        if self.deck.entries.len() > 0 {
                             self.decklist_selected = if self.decklist_selected > 0 {
                                 self.decklist_selected - 1
                             } else {
-                                self.decklist.len() - 1
+                                self.deck.entries.len() - 1
                             }
                         }
                     }
@@ -338,8 +1242,16 @@ impl App {
                 },
                 InputMode::Saving if key.kind == KeyEventKind::Press => match key.code {
                     KeyCode::Enter => {
-                        let _ = save_decklist(&self.decklist, &self.deckname);
+                        //before saving, since saving can ask about replacing a stored deck
                         self.input_mode = InputMode::Normal;
+                        if let Err(e) = self.save_deck() {
+                            self.err_line = Some(Message {
+                                msg_type: Error,
+                                msg_content: format!("Couldn't save {}: {e}", self.deckname),
+                                byte_pos: 0,
+                                source_phase_index: 0,
+                            });
+                        }
                     }
                     KeyCode::Esc => {
                         self.input_mode = InputMode::Normal;
@@ -352,16 +1264,91 @@ impl App {
                     }
                     _ => {}
                 },
+                InputMode::Replacing { saving } if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('y') => {
+                        self.input_mode = InputMode::Normal;
+                        self.store_deck(true);
+                        if saving {
+                            self.snapshot_version();
+                        }
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => {
+                        self.input_mode = InputMode::Normal;
+                        self.err_line = Some(Message {
+                            msg_type: Warning,
+                            msg_content: format!(
+                                "Kept the stored {}; save this deck (Ctrl-S) under another name to store it",
+                                self.deckname
+                            ),
+                            byte_pos: 0,
+                            source_phase_index: 0,
+                        });
+                    }
+                    _ => {}
+                },
+                InputMode::Reviewing if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Enter => {
+                        let index = self.review.remove(0);
+                        let candidate = self.reconciliation.lines[index]
+                            .resolution
+                            .candidates()
+                            .get(self.review_selected)
+                            .cloned();
+                        match candidate {
+                            Some(candidate) => self.reconciliation.confirm(index, candidate),
+                            None => self.reconciliation.skip(index),
+                        }
+
+                        self.review_selected = 0;
+                        if self.review.is_empty() {
+                            self.finish_review();
+                        }
+                    }
+                    KeyCode::Char('j') => {
+                        let options = self.review_options();
+                        self.review_selected = (self.review_selected + 1) % options;
+                    }
+                    KeyCode::Char('k') => {
+                        let options = self.review_options();
+                        self.review_selected = (self.review_selected + options - 1) % options;
+                    }
+                    //whatever's left goes in as it was matched
+                    KeyCode::Esc => self.finish_review(),
+                    _ => {}
+                },
                 InputMode::Opening if key.kind == KeyEventKind::Press => match key.code {
                     KeyCode::Enter => {
-                        self.decklist = load_decklist(
-                            &(std::env::home_dir()
-                                .unwrap_or("".into())
-                                .join("Downloads")
-                                .join(self.files[self.file_selected].clone())
-                                .as_path()),
-                        )?;
-                        self.input_mode = InputMode::Normal;
+                        let Some(sources) = &self.sources else {
+                            return Ok(false);
+                        };
+                        let path = std::env::home_dir()
+                            .unwrap_or("".into())
+                            .join("Downloads")
+                            .join(self.files[self.file_selected].clone());
+                        //a file that doesn't parse shouldn't close the app; pick another
+                        let lines = match load_decklist(&path) {
+                            Ok(lines) => lines,
+                            Err(e) => {
+                                self.err_line = Some(Message {
+                                    msg_type: Error,
+                                    msg_content: format!("Couldn't open {}: {e}", path.display()),
+                                    byte_pos: 0,
+                                    source_phase_index: 0,
+                                });
+                                return Ok(false);
+                            }
+                        };
+                        if let Some(stem) = path.file_stem().and_then(|x| x.to_str()) {
+                            self.deckname = stem.to_string();
+                        }
+                        self.reconciliation = Reconciler::new(&sources.cards).reconcile(lines);
+                        self.review = self.reconciliation.needs_review();
+                        self.review_selected = 0;
+                        if self.review.is_empty() {
+                            self.finish_review();
+                        } else {
+                            self.input_mode = InputMode::Reviewing;
+                        }
                     }
                     KeyCode::Char('j') => {
                         if self.files.len() > 0 {
@@ -389,28 +1376,33 @@ impl App {
 
                 _ => {}
             }
-            if key.modifiers.contains(KeyModifiers::CONTROL) {
-                match key.code {
-                    KeyCode::Char('s') => self.input_mode = InputMode::Saving,
-                    KeyCode::Char('o') => {
-                        self.input_mode = InputMode::Opening;
-                        self.files =
-                            read_dir(std::env::home_dir().unwrap_or("".into()).join("Downloads"))
-                                .unwrap()
-                                .filter_map(|f| f.ok())
-                                .filter(|f| f.file_name().into_string().unwrap().ends_with(".txt"))
-                                .map(|f| f.file_name().into_string().unwrap())
-                                .collect();
-                    }
-                    _ => (),
-                }
-            }
         }
 
         Ok(true)
     }
 }
 
+fn message_spans(msg: &Message) -> [Span<'_>; 3] {
+    [
+        match msg.msg_type {
+            Warning => Span::styled("", (Color::Yellow, Modifier::BOLD)),
+            Error => Span::styled("", (Color::Red, Modifier::BOLD)),
+        },
+        Span::from(" "),
+        Span::styled(
+            &msg.msg_content,
+            match msg.msg_type {
+                Warning => Color::Yellow,
+                Error => Color::Red,
+            },
+        ),
+    ]
+}
+
+fn dollars(cents: usize) -> String {
+    format!("${}.{:02}", cents / 100, cents % 100)
+}
+
 fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
     let [area] = Layout::horizontal([horizontal])
         .flex(Flex::Center)
@@ -419,43 +1411,39 @@ fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
     area
 }
 
-fn save_decklist(deck: &Vec<String>, deckname: &String) -> std::io::Result<()> {
-    use std::io::Write;
+fn next_section(section: DeckSection) -> DeckSection {
+    let i = DeckSection::ALL.iter().position(|x| *x == section).unwrap_or(0);
+    DeckSection::ALL[(i + 1) % DeckSection::ALL.len()]
+}
+
+fn next_format(format: GameFormat) -> GameFormat {
+    let i = GameFormat::ALL.iter().position(|x| *x == format).unwrap_or(0);
+    GameFormat::ALL[(i + 1) % GameFormat::ALL.len()]
+}
+
+fn save_decklist(db: &AllCardsDb, deck: &Deck, deckname: &String) -> std::io::Result<()> {
     let mut deck_filename = std::env::home_dir()
         .unwrap_or("".into())
         .join("Downloads")
         .join(deckname);
-    deck_filename.set_extension("txt");
-    let mut deck_file = File::create(deck_filename)?;
-
-    for card in deck.iter() {
-        writeln!(&mut deck_file, "1 {card}")?;
-    }
+    deck_filename.set_extension(DeckFormat::Text.extension());
 
-    Ok(())
+    //written out in full first, so a deck that can't be written doesn't wipe the old file
+    let mut contents = Vec::new();
+    DeckFormat::Text.write(db, deckname, deck, &mut contents)?;
+    std::fs::write(deck_filename, contents)
 }
 
-fn load_decklist(deck_file: &Path) -> std::io::Result<Vec<String>> {
-    let file = File::open(deck_file)?;
-
-    let mut r = Vec::<String>::new();
-
-    for line in BufReader::new(file).lines() {
-        let mut line = line?;
-        //split_off will remove the name for us, which lets us simply parse
-        // the number of cards from the remaining line content.
-
-        let card_name = line.split_off(line.find(" ").unwrap_or_default());
-        let card_name_trimmed = card_name.trim();
-
-        let num_repeats: usize = line.parse().unwrap_or(1);
-
-        for _ in 0..num_repeats {
-            r.push(card_name_trimmed.to_string());
-        }
-    }
+fn load_decklist(deck_file: &Path) -> std::io::Result<Vec<DeckLine>> {
+    let format = deck_file
+        .extension()
+        .and_then(|x| x.to_str())
+        .and_then(DeckFormat::from_extension)
+        .unwrap_or(DeckFormat::Text);
 
-    Ok(r)
+    format
+        .parse(&read_to_string(deck_file)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", e.line, e.message)))
 }
 
 // MAKES IT RUN
@@ -12,6 +12,28 @@ pub fn run_command(cmd: &str) {
     assert!(status.success());
 }
 
+//the fields of each card that can differ between printings
+fn printing_details(card: &serde_json::Value) -> serde_json::Value {
+    let mut details = Map::new();
+
+    for field in [
+        "artist",
+        "flavorText",
+        "watermark",
+        "frameVersion",
+        "borderColor",
+        "uuid",
+    ] {
+        details.insert(field.to_string(), card[field].to_owned());
+    }
+    //MTGO's catalog IDs, which `.dek` files use
+    for field in ["mtgoId", "mtgoFoilId"] {
+        details.insert(field.to_string(), card["identifiers"][field].to_owned());
+    }
+
+    serde_json::Value::Object(details)
+}
+
 pub fn main() {
     if !fs::exists("data/AllSetFiles.tar.xz").unwrap() {
         run_command(
@@ -24,6 +46,14 @@ pub fn main() {
     }
 
     let mut results = Vec::new();
+    let mut set_infos = Vec::new();
+    //rulings are the same on every printing, so only keep one copy per card name
+    let mut rulings = Map::new();
+    //card name to {language: name}. Printings only list the languages they were printed in,
+    // so these get merged across all of the card's printings.
+    let mut foreign_names = Map::new();
+    //card name to {format: status}. These are the current statuses, so every printing agrees.
+    let mut legalities = Map::new();
 
     for file in fs::read_dir("data/AllSetFiles").unwrap() {
         let file = file.unwrap();
@@ -40,12 +70,71 @@ pub fn main() {
 
         let set_code = json["data"]["code"].as_str().unwrap().to_string();
 
+        let mut set_info = Map::new();
+        for field in [
+            "code",
+            "name",
+            "releaseDate",
+            "type",
+            "block",
+            "parentCode",
+            "totalSetSize",
+        ] {
+            set_info.insert(field.to_string(), json["data"][field].to_owned());
+        }
+        set_infos.push(serde_json::Value::Object(set_info));
+
+        for card in json["data"]["cards"].as_array().unwrap() {
+            let (Some(name), Some(card_rulings)) = (card["name"].as_str(), card["rulings"].as_array())
+            else {
+                continue;
+            };
+            if !card_rulings.is_empty() && !rulings.contains_key(name) {
+                rulings.insert(name.to_string(), card["rulings"].to_owned());
+            }
+        }
+
+        for card in json["data"]["cards"].as_array().unwrap() {
+            let (Some(name), Some(card_legalities)) = (card["name"].as_str(), card["legalities"].as_object())
+            else {
+                continue;
+            };
+            if !card_legalities.is_empty() && !legalities.contains_key(name) {
+                legalities.insert(name.to_string(), card["legalities"].to_owned());
+            }
+        }
+
+        for card in json["data"]["cards"].as_array().unwrap() {
+            let (Some(name), Some(foreign_data)) = (card["name"].as_str(), card["foreignData"].as_array())
+            else {
+                continue;
+            };
+
+            let names = foreign_names
+                .entry(name.to_string())
+                .or_insert_with(|| serde_json::Value::Object(Map::new()))
+                .as_object_mut()
+                .unwrap();
+
+            for foreign in foreign_data {
+                if let (Some(language), Some(foreign_name)) =
+                    (foreign["language"].as_str(), foreign["name"].as_str())
+                {
+                    names.insert(language.to_string(), foreign_name.into());
+                }
+            }
+        }
+
         let cardname_keyvals = json["data"]["cards"]
             .as_array()
             .unwrap()
             .iter()
             .map(|card| {
-                serde_json::Value::Array(vec![card["name"].to_owned(), card["number"].to_owned()])
+                serde_json::Value::Array(vec![
+                    card["name"].to_owned(),
+                    card["number"].to_owned(),
+                    printing_details(card),
+                ])
             })
             .collect();
 
@@ -60,6 +149,7 @@ pub fn main() {
                     serde_json::Value::Array(vec![
                         card["name"].to_owned(),
                         card["number"].to_owned(),
+                        printing_details(card),
                     ])
                 });
 
@@ -85,4 +175,28 @@ pub fn main() {
         .unwrap();
 
     serde_json::to_writer_pretty(output, &results).unwrap();
+
+    let output = fs::File::create("data/setinfo.json")
+        .map(BufWriter::new)
+        .unwrap();
+
+    serde_json::to_writer_pretty(output, &set_infos).unwrap();
+
+    let output = fs::File::create("data/rulings.json")
+        .map(BufWriter::new)
+        .unwrap();
+
+    serde_json::to_writer_pretty(output, &serde_json::Value::Object(rulings)).unwrap();
+
+    let output = fs::File::create("data/foreignnames.json")
+        .map(BufWriter::new)
+        .unwrap();
+
+    serde_json::to_writer_pretty(output, &serde_json::Value::Object(foreign_names)).unwrap();
+
+    let output = fs::File::create("data/legalities.json")
+        .map(BufWriter::new)
+        .unwrap();
+
+    serde_json::to_writer_pretty(output, &serde_json::Value::Object(legalities)).unwrap();
 }
@@ -3,13 +3,16 @@ use std::{
     str::FromStr,
 };
 
-use crate::data_model::oddities::{Stringish, StringishUsize};
+use crate::data_model::{
+    date::Date,
+    oddities::{Stringish, StringishUsize},
+};
 
 ///
 /// Reference to a specific card, can be as specific as needed or vague to be only set + collector number.
 /// Something will be made where code can exchange this for a full `Card`
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CardRef {
     pub set: String,
     pub collector_number: StringishUsize,
@@ -20,10 +23,255 @@ pub struct CardRef {
 /// One physical card. Users may have more than one `PhysicalCard` with the same `CardRef` in their collection; this might be
 /// implemented differently (i.e. many `PhysicalCards` or one `PhysicalCard` with `duplicates`)
 /// depending on how the user choses to arrange their collection.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PhysicalCard {
     pub card: CardRef,
     pub duplicates: usize,
+    pub finish: Finish,
+    pub condition: Condition,
+    pub language: Language,
+    pub acquired: Option<Date>,
+}
+
+impl PhysicalCard {
+    pub fn new(card: CardRef, duplicates: usize) -> Self {
+        Self {
+            card,
+            duplicates,
+            finish: Finish::Nonfoil,
+            condition: Condition::NearMint,
+            language: Language::English,
+            acquired: None,
+        }
+    }
+
+    ///
+    /// Whether the two are indistinguishable apart from how many there are,
+    /// i.e. whether they can be stacked into one entry.
+    pub fn same_kind_as(&self, other: &PhysicalCard) -> bool {
+        self.card == other.card
+            && self.finish == other.finish
+            && self.condition == other.condition
+            && self.language == other.language
+            && self.acquired == other.acquired
+    }
+}
+
+///
+/// The usual grading scale used by TCGplayer and Cardmarket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Condition {
+    Mint,
+    #[default]
+    NearMint,
+    LightlyPlayed,
+    ModeratelyPlayed,
+    HeavilyPlayed,
+    Damaged,
+}
+
+impl Condition {
+    pub const ALL: [Condition; 6] = [
+        Condition::Mint,
+        Condition::NearMint,
+        Condition::LightlyPlayed,
+        Condition::ModeratelyPlayed,
+        Condition::HeavilyPlayed,
+        Condition::Damaged,
+    ];
+
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Condition::Mint => "M",
+            Condition::NearMint => "NM",
+            Condition::LightlyPlayed => "LP",
+            Condition::ModeratelyPlayed => "MP",
+            Condition::HeavilyPlayed => "HP",
+            Condition::Damaged => "DMG",
+        }
+    }
+
+    pub fn full_name(&self) -> &'static str {
+        match self {
+            Condition::Mint => "Mint",
+            Condition::NearMint => "Near Mint",
+            Condition::LightlyPlayed => "Lightly Played",
+            Condition::ModeratelyPlayed => "Moderately Played",
+            Condition::HeavilyPlayed => "Heavily Played",
+            Condition::Damaged => "Damaged",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Finish {
+    Nonfoil,
+    Foil,
+    Etched,
+}
+
+impl Finish {
+    pub const ALL: [Finish; 3] = [Finish::Nonfoil, Finish::Foil, Finish::Etched];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Finish::Nonfoil => "nonfoil",
+            Finish::Foil => "foil",
+            Finish::Etched => "etched",
+        }
+    }
+}
+
+///
+/// The languages that cards have been printed in, as MTGJSON lists them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    French,
+    Italian,
+    Spanish,
+    Portuguese,
+    Japanese,
+    Korean,
+    Russian,
+    ChineseSimplified,
+    ChineseTraditional,
+    Hebrew,
+    Latin,
+    AncientGreek,
+    Arabic,
+    Sanskrit,
+    Phyrexian,
+    Quenya,
+}
+
+#[derive(Debug)]
+pub struct UnknownLanguageErr;
+
+impl Language {
+    pub const ALL: [Language; 18] = [
+        Language::English,
+        Language::German,
+        Language::French,
+        Language::Italian,
+        Language::Spanish,
+        Language::Portuguese,
+        Language::Japanese,
+        Language::Korean,
+        Language::Russian,
+        Language::ChineseSimplified,
+        Language::ChineseTraditional,
+        Language::Hebrew,
+        Language::Latin,
+        Language::AncientGreek,
+        Language::Arabic,
+        Language::Sanskrit,
+        Language::Phyrexian,
+        Language::Quenya,
+    ];
+
+    /// The name MTGJSON uses in `foreignData`
+    pub fn mtgjson_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "German",
+            Language::French => "French",
+            Language::Italian => "Italian",
+            Language::Spanish => "Spanish",
+            Language::Portuguese => "Portuguese (Brazil)",
+            Language::Japanese => "Japanese",
+            Language::Korean => "Korean",
+            Language::Russian => "Russian",
+            Language::ChineseSimplified => "Chinese Simplified",
+            Language::ChineseTraditional => "Chinese Traditional",
+            Language::Hebrew => "Hebrew",
+            Language::Latin => "Latin",
+            Language::AncientGreek => "Ancient Greek",
+            Language::Arabic => "Arabic",
+            Language::Sanskrit => "Sanskrit",
+            Language::Phyrexian => "Phyrexian",
+            Language::Quenya => "Quenya",
+        }
+    }
+
+    /// The short code Scryfall uses, e.g. `de` or `zhs`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+            Language::French => "fr",
+            Language::Italian => "it",
+            Language::Spanish => "es",
+            Language::Portuguese => "pt",
+            Language::Japanese => "ja",
+            Language::Korean => "ko",
+            Language::Russian => "ru",
+            Language::ChineseSimplified => "zhs",
+            Language::ChineseTraditional => "zht",
+            Language::Hebrew => "he",
+            Language::Latin => "la",
+            Language::AncientGreek => "grc",
+            Language::Arabic => "ar",
+            Language::Sanskrit => "sa",
+            Language::Phyrexian => "ph",
+            Language::Quenya => "qya",
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_u8(b: u8) -> Option<Self> {
+        Self::ALL.get(b as usize).copied()
+    }
+}
+
+///
+/// Accepts either the code or the MTGJSON name, ignoring case.
+/// `chinese` alone is taken to mean simplified Chinese, like Scryfall does.
+impl FromStr for Language {
+    type Err = UnknownLanguageErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("chinese") {
+            return Ok(Language::ChineseSimplified);
+        }
+
+        Self::ALL
+            .into_iter()
+            .find(|l| l.code().eq_ignore_ascii_case(s) || l.mtgjson_name().eq_ignore_ascii_case(s))
+            .ok_or(UnknownLanguageErr)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignName {
+    pub language: Language,
+    pub name: String,
+}
+
+///
+/// All of the non-English names that a card has been printed with.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ForeignNames(pub Vec<ForeignName>);
+
+///
+/// The details of a card which can differ between its printings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Printing {
+    /// This printing
+    pub card_ref: CardRef,
+    /// The card this is a printing of, as keyed in the all-cards DB
+    pub card: CardRef,
+    pub artist: String,
+    pub flavor_text: String,
+    pub watermark: Option<String>,
+    /// MTGJSON's `frameVersion`, e.g. `1997` or `future`
+    pub frame: String,
+    pub border: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -50,6 +298,31 @@ pub struct Card {
     pub game_changer: bool,
 }
 
+#[cfg(test)]
+impl Card {
+    /// A card with nothing but its name, for tests to fill in the rest of
+    pub(crate) fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            mana_cost: ManaCost(Vec::new()),
+            mana_value_times_4: 0,
+            color: ColorCombination::default(),
+            color_id: ColorCombination::default(),
+            super_types: Vec::new(),
+            types: Vec::new(),
+            subtypes: Vec::new(),
+            rarity: Rarity::Common,
+            oracle_text: String::new(),
+            power: CardDynamicNumber::default(),
+            toughness: CardDynamicNumber::default(),
+            loyalty: CardDynamicNumber::default(),
+            defense: 0,
+            sets_released: Vec::new(),
+            game_changer: false,
+        }
+    }
+}
+
 ///
 /// Represents some non-negative integer on a MtG card which
 /// can be a set value or can be controlled by some
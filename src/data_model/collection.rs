@@ -0,0 +1,13 @@
+use crate::data_model::card::PhysicalCard;
+
+///
+/// Identifies one entry in the collection. Entries are stacks of identical
+/// `PhysicalCard`s, so the ID stays the same when the quantity changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CollectionEntryId(pub usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionEntry {
+    pub id: CollectionEntryId,
+    pub card: PhysicalCard,
+}
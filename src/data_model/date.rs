@@ -0,0 +1,135 @@
+use std::{fmt::Display, str::FromStr, time::SystemTime};
+
+///
+/// A calendar date, in the same shape MTGJSON uses (`YYYY-MM-DD`).
+/// Field order makes the derived ordering chronological.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: usize,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug)]
+pub struct BadDateErr;
+
+impl Date {
+    pub fn first_of_year(year: usize) -> Self {
+        Self {
+            year,
+            month: 1,
+            day: 1,
+        }
+    }
+
+    pub fn last_of_year(year: usize) -> Self {
+        Self {
+            year,
+            month: 12,
+            day: 31,
+        }
+    }
+
+    /// Packs the date as the number YYYYMMDD, which sorts the same way the date does.
+    pub fn as_packed(&self) -> usize {
+        self.year * 10000 + self.month as usize * 100 + self.day as usize
+    }
+
+    /// Today's date in UTC, according to the system clock.
+    pub fn today() -> Self {
+        let days = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|x| x.as_secs() / 86400)
+            .unwrap_or_default();
+
+        Self::from_days_since_epoch(days as usize)
+    }
+
+    //Howard Hinnant's `civil_from_days`, restricted to dates after 1970
+    pub(crate) fn from_days_since_epoch(days: usize) -> Self {
+        let z = days + 719468;
+        let era = z / 146097;
+        let day_of_era = z % 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + (month <= 2) as usize;
+
+        Self {
+            year,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+
+    pub fn from_packed(packed: usize) -> Self {
+        Self {
+            year: packed / 10000,
+            month: ((packed / 100) % 100) as u8,
+            day: (packed % 100) as u8,
+        }
+    }
+}
+
+impl FromStr for Date {
+    type Err = BadDateErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, '-');
+
+        let year = parts
+            .next()
+            .filter(|x| x.len() == 4)
+            .and_then(|x| x.parse().ok())
+            .ok_or(BadDateErr)?;
+        let month = parts
+            .next()
+            .and_then(|x| x.parse().ok())
+            .filter(|x| (1..=12).contains(x))
+            .ok_or(BadDateErr)?;
+        let day = parts
+            .next()
+            .and_then(|x| x.parse().ok())
+            .filter(|x| (1..=31).contains(x))
+            .ok_or(BadDateErr)?;
+
+        Ok(Self { year, month, day })
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_date_parse() {
+    let d: Date = "2019-01-31".parse().unwrap();
+    assert_eq!(
+        d,
+        Date {
+            year: 2019,
+            month: 1,
+            day: 31
+        }
+    );
+    assert_eq!(d.to_string(), "2019-01-31");
+    assert!(Date::last_of_year(2018) < d);
+
+    assert!("2019-13-01".parse::<Date>().is_err());
+    assert!("19-01-01".parse::<Date>().is_err());
+    assert!("2019-01".parse::<Date>().is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn test_date_from_days() {
+    assert_eq!(Date::from_days_since_epoch(0).to_string(), "1970-01-01");
+    assert_eq!(Date::from_days_since_epoch(11016).to_string(), "2000-02-29");
+    assert_eq!(Date::from_days_since_epoch(17927).to_string(), "2019-01-31");
+}
@@ -0,0 +1,328 @@
+use crate::data_model::card::CardRef;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DeckSection {
+    Commander,
+    Companion,
+    Main,
+    Side,
+    /// Cards being considered for the deck, which aren't part of it yet
+    Maybe,
+}
+
+impl DeckSection {
+    /// In the order they're listed in decklists
+    pub const ALL: [DeckSection; 5] = [
+        DeckSection::Commander,
+        DeckSection::Companion,
+        DeckSection::Main,
+        DeckSection::Side,
+        DeckSection::Maybe,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeckSection::Commander => "Commander",
+            DeckSection::Companion => "Companion",
+            DeckSection::Main => "Deck",
+            DeckSection::Side => "Sideboard",
+            DeckSection::Maybe => "Maybeboard",
+        }
+    }
+
+    ///
+    /// Recognizes the section headers used by the common decklist formats,
+    /// e.g. `Sideboard`, `SB:`, or `// Commander`.
+    pub fn from_header(header: &str) -> Option<Self> {
+        let header = header
+            .trim()
+            .trim_start_matches('/')
+            .trim()
+            .trim_end_matches(':')
+            .to_ascii_lowercase();
+
+        Some(match header.as_str() {
+            "commander" | "commanders" => DeckSection::Commander,
+            "companion" => DeckSection::Companion,
+            "deck" | "main" | "mainboard" | "maindeck" => DeckSection::Main,
+            "sideboard" | "side" | "sb" => DeckSection::Side,
+            "maybeboard" | "maybe" | "considering" => DeckSection::Maybe,
+            _ => return None,
+        })
+    }
+
+    ///
+    /// Whether the cards in the section need to be physically in the deck.
+    /// Only the maybeboard doesn't.
+    pub fn is_played(&self) -> bool {
+        *self != DeckSection::Maybe
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckEntry {
+    pub section: DeckSection,
+    pub card: CardRef,
+    pub quantity: usize,
+}
+
+///
+/// A decklist: how many of each card are in each section.
+/// Entries are kept in section order, and each card is only listed once per section.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Deck {
+    pub entries: Vec<DeckEntry>,
+}
+
+impl Deck {
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, section: DeckSection, card: CardRef, quantity: usize) {
+        if quantity == 0 {
+            return;
+        }
+
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|x| x.section == section && x.card == card)
+        {
+            entry.quantity += quantity;
+            return;
+        }
+
+        let position = self
+            .entries
+            .iter()
+            .position(|x| x.section > section)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(
+            position,
+            DeckEntry {
+                section,
+                card,
+                quantity,
+            },
+        );
+    }
+
+    /// Returns how many were actually removed
+    pub fn remove(&mut self, section: DeckSection, card: &CardRef, quantity: usize) -> usize {
+        let Some(i) = self
+            .entries
+            .iter()
+            .position(|x| x.section == section && x.card == *card)
+        else {
+            return 0;
+        };
+
+        let removed = quantity.min(self.entries[i].quantity);
+        self.entries[i].quantity -= removed;
+        if self.entries[i].quantity == 0 {
+            self.entries.remove(i);
+        }
+        removed
+    }
+
+    pub fn section(&self, section: DeckSection) -> impl Iterator<Item = &DeckEntry> {
+        self.entries.iter().filter(move |x| x.section == section)
+    }
+
+    ///
+    /// Moves every copy of the card in one section to another,
+    /// merging with any copies already there.
+    pub fn move_to(&mut self, from: DeckSection, card: &CardRef, to: DeckSection) {
+        let quantity = self.remove(from, card, usize::MAX);
+        self.add(to, card.clone(), quantity);
+    }
+
+    pub fn count_in(&self, section: DeckSection) -> usize {
+        self.section(section).map(|x| x.quantity).sum()
+    }
+
+    /// Copies of the card in the sections which are played
+    pub fn count_of(&self, card: &CardRef) -> usize {
+        self.entries
+            .iter()
+            .filter(|x| x.section.is_played() && x.card == *card)
+            .map(|x| x.quantity)
+            .sum()
+    }
+
+    /// Copies in the sections which are played
+    pub fn total(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|x| x.section.is_played())
+            .map(|x| x.quantity)
+            .sum()
+    }
+}
+
+///
+/// How many copies of a card one section had before and after a change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionChange {
+    pub section: DeckSection,
+    pub card: CardRef,
+    pub before: usize,
+    pub after: usize,
+}
+
+impl SectionChange {
+    /// Copies that came in; zero if the count went down
+    pub fn added(&self) -> usize {
+        self.after.saturating_sub(self.before)
+    }
+
+    /// Copies that went out; zero if the count went up
+    pub fn removed(&self) -> usize {
+        self.before.saturating_sub(self.after)
+    }
+}
+
+///
+/// The differences between two versions of a deck, in section order.
+/// Cards whose counts didn't change aren't listed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DeckDiff {
+    pub changes: Vec<SectionChange>,
+}
+
+impl DeckDiff {
+    pub fn between(old: &Deck, new: &Deck) -> Self {
+        let count = |deck: &Deck, section: DeckSection, card: &CardRef| {
+            deck.section(section)
+                .find(|x| x.card == *card)
+                .map_or(0, |x| x.quantity)
+        };
+
+        let mut changes = Vec::new();
+        for section in DeckSection::ALL {
+            //old entries first, so cards that were already there keep their place
+            let mut cards: Vec<&CardRef> = old.section(section).map(|x| &x.card).collect();
+            cards.extend(
+                new.section(section)
+                    .map(|x| &x.card)
+                    .filter(|card| count(old, section, card) == 0),
+            );
+
+            for card in cards {
+                let before = count(old, section, card);
+                let after = count(new, section, card);
+                if before != after {
+                    changes.push(SectionChange {
+                        section,
+                        card: card.clone(),
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// `(section, card, copies added)`
+    pub fn cards_in(&self) -> impl Iterator<Item = (DeckSection, &CardRef, usize)> {
+        self.changes
+            .iter()
+            .filter(|x| x.added() > 0)
+            .map(|x| (x.section, &x.card, x.added()))
+    }
+
+    /// `(section, card, copies removed)`
+    pub fn cards_out(&self) -> impl Iterator<Item = (DeckSection, &CardRef, usize)> {
+        self.changes
+            .iter()
+            .filter(|x| x.removed() > 0)
+            .map(|x| (x.section, &x.card, x.removed()))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_deck_sections() {
+    let card = |n: usize| CardRef {
+        set: "TST".to_string(),
+        collector_number: n.into(),
+        printing: None,
+    };
+
+    let mut deck = Deck::new();
+    deck.add(DeckSection::Side, card(1), 2);
+    deck.add(DeckSection::Main, card(1), 4);
+    deck.add(DeckSection::Commander, card(2), 1);
+    deck.add(DeckSection::Main, card(1), 1);
+    deck.add(DeckSection::Maybe, card(3), 1);
+
+    let sections = deck.entries.iter().map(|x| x.section).collect::<Vec<_>>();
+    assert_eq!(
+        sections,
+        [
+            DeckSection::Commander,
+            DeckSection::Main,
+            DeckSection::Side,
+            DeckSection::Maybe
+        ]
+    );
+    assert_eq!(deck.count_in(DeckSection::Main), 5);
+    assert_eq!(deck.count_of(&card(1)), 7);
+    assert_eq!(deck.total(), 8);
+
+    assert_eq!(deck.remove(DeckSection::Side, &card(1), 5), 2);
+    assert_eq!(deck.count_of(&card(1)), 5);
+
+    assert_eq!(DeckSection::from_header("SB:"), Some(DeckSection::Side));
+    assert_eq!(DeckSection::from_header("// Commander"), Some(DeckSection::Commander));
+    assert_eq!(DeckSection::from_header("Lightning Bolt"), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_deck_diff() {
+    let card = |n: usize| CardRef {
+        set: "TST".to_string(),
+        collector_number: n.into(),
+        printing: None,
+    };
+
+    let mut old = Deck::new();
+    old.add(DeckSection::Main, card(1), 4);
+    old.add(DeckSection::Main, card(2), 2);
+    old.add(DeckSection::Side, card(3), 3);
+
+    let mut new = old.clone();
+    new.remove(DeckSection::Main, &card(1), 1);
+    new.move_to(DeckSection::Side, &card(3), DeckSection::Main);
+    new.add(DeckSection::Maybe, card(4), 1);
+
+    let diff = DeckDiff::between(&old, &new);
+    let cards_in = diff.cards_in().collect::<Vec<_>>();
+    let cards_out = diff.cards_out().collect::<Vec<_>>();
+    assert_eq!(
+        cards_in,
+        [
+            (DeckSection::Main, &card(3), 3),
+            (DeckSection::Maybe, &card(4), 1)
+        ]
+    );
+    assert_eq!(
+        cards_out,
+        [
+            (DeckSection::Main, &card(1), 1),
+            (DeckSection::Side, &card(3), 3)
+        ]
+    );
+
+    assert!(DeckDiff::between(&new, &new).is_empty());
+}
@@ -1,16 +1,77 @@
-use crate::data_model::card::CardRef;
+use crate::data_model::{card::CardRef, deck::Deck};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GroupId(pub usize);
 
 ///
 /// Generic name for a deck, group, box, etc.
 ///
-struct CardGroup {
-    group_type: CardGroupVariety,
-    name: String,
-    cards: Vec<CardRef>,
-    exclusive: bool,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardGroup {
+    pub group_type: CardGroupVariety,
+    pub name: String,
+    /// Groups other than decks only use the main section
+    pub cards: Deck,
+    pub exclusive: bool,
+    /// The folder this is in, if any
+    pub parent: Option<GroupId>,
+}
+
+impl CardGroup {
+    pub fn new(group_type: CardGroupVariety, name: impl Into<String>) -> Self {
+        Self {
+            group_type,
+            name: name.into(),
+            cards: Deck::new(),
+            exclusive: false,
+            parent: None,
+        }
+    }
+
+    pub fn count_of(&self, card: &CardRef) -> usize {
+        self.cards.count_of(card)
+    }
 }
 
-// TODO: have Wil add more varieties based on what's actually used
-enum CardGroupVariety {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CardGroupVariety {
     Deck,
+    Binder,
+    StorageBox,
+    /// Cards that the user wants, rather than has
+    Wishlist,
+    /// Cards that the user is willing to trade away
+    TradeBinder,
+    Cube,
+    /// Holds other groups instead of cards
+    Folder,
+}
+
+impl CardGroupVariety {
+    pub const ALL: [CardGroupVariety; 7] = [
+        CardGroupVariety::Deck,
+        CardGroupVariety::Binder,
+        CardGroupVariety::StorageBox,
+        CardGroupVariety::Wishlist,
+        CardGroupVariety::TradeBinder,
+        CardGroupVariety::Cube,
+        CardGroupVariety::Folder,
+    ];
+
+    /// The word used for it in searches, e.g. `in:binder:"Name"`
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            CardGroupVariety::Deck => "deck",
+            CardGroupVariety::Binder => "binder",
+            CardGroupVariety::StorageBox => "box",
+            CardGroupVariety::Wishlist => "wishlist",
+            CardGroupVariety::TradeBinder => "trade",
+            CardGroupVariety::Cube => "cube",
+            CardGroupVariety::Folder => "folder",
+        }
+    }
+
+    pub fn holds_cards(&self) -> bool {
+        *self != CardGroupVariety::Folder
+    }
 }
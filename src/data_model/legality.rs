@@ -0,0 +1,103 @@
+///
+/// The formats we check decks against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameFormat {
+    Standard,
+    Pioneer,
+    Modern,
+    Legacy,
+    Vintage,
+    Pauper,
+    Commander,
+}
+
+impl GameFormat {
+    pub const ALL: [GameFormat; 7] = [
+        GameFormat::Standard,
+        GameFormat::Pioneer,
+        GameFormat::Modern,
+        GameFormat::Legacy,
+        GameFormat::Vintage,
+        GameFormat::Pauper,
+        GameFormat::Commander,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameFormat::Standard => "Standard",
+            GameFormat::Pioneer => "Pioneer",
+            GameFormat::Modern => "Modern",
+            GameFormat::Legacy => "Legacy",
+            GameFormat::Vintage => "Vintage",
+            GameFormat::Pauper => "Pauper",
+            GameFormat::Commander => "Commander",
+        }
+    }
+
+    /// The key MTGJSON uses for the format in a card's `legalities`
+    pub fn mtgjson_key(&self) -> &'static str {
+        match self {
+            GameFormat::Standard => "standard",
+            GameFormat::Pioneer => "pioneer",
+            GameFormat::Modern => "modern",
+            GameFormat::Legacy => "legacy",
+            GameFormat::Vintage => "vintage",
+            GameFormat::Pauper => "pauper",
+            GameFormat::Commander => "commander",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|x| x == self).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Legality {
+    Legal,
+    #[default]
+    NotLegal,
+    Banned,
+    /// Only one copy allowed
+    Restricted,
+}
+
+impl Legality {
+    pub const ALL: [Legality; 4] = [
+        Legality::Legal,
+        Legality::NotLegal,
+        Legality::Banned,
+        Legality::Restricted,
+    ];
+
+    /// MTGJSON leaves formats a card isn't legal in out, rather than naming them
+    pub fn from_mtgjson(status: &str) -> Self {
+        match status {
+            "Legal" => Legality::Legal,
+            "Banned" => Legality::Banned,
+            "Restricted" => Legality::Restricted,
+            _ => Legality::NotLegal,
+        }
+    }
+}
+
+///
+/// A card's status in each of the formats in `GameFormat::ALL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CardLegalities(pub [Legality; GameFormat::ALL.len()]);
+
+impl CardLegalities {
+    pub fn get(&self, format: GameFormat) -> Legality {
+        self.0[format.index()]
+    }
+
+    pub fn set(&mut self, format: GameFormat, legality: Legality) {
+        self.0[format.index()] = legality;
+    }
+}
@@ -1,3 +1,11 @@
 pub mod card;
+pub mod collection;
+pub mod date;
+pub mod deck;
 pub mod group;
+pub mod legality;
 pub mod oddities;
+pub mod price;
+pub mod ruling;
+pub mod set;
+pub mod tag;
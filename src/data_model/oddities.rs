@@ -1,6 +1,6 @@
 // Code to help generically deal with odd behaviours
 
-use std::{convert::Infallible, str::FromStr};
+use std::{convert::Infallible, fmt::Display, str::FromStr};
 
 use minimal_storage::{
     bit_sections::BitSection,
@@ -63,7 +63,7 @@ impl<T: FromStr + DeserializeFromMinimal> DeserializeFromMinimal for Stringish<T
 ///
 /// A string that is normally a number, but can be something else.
 /// This is a workaround for Rust's limits on specialization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum StringishUsize {
     Number(usize),
     String(String),
@@ -88,6 +88,15 @@ impl FromStr for StringishUsize {
     }
 }
 
+impl Display for StringishUsize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StringishUsize::Number(n) => write!(f, "{n}"),
+            StringishUsize::String(s) => write!(f, "{s}"),
+        }
+    }
+}
+
 impl From<usize> for StringishUsize {
     fn from(value: usize) -> Self {
         Self::Number(value)
@@ -0,0 +1,26 @@
+use crate::data_model::{
+    card::{CardRef, Finish},
+    date::Date,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Currency {
+    /// TCGplayer's retail price
+    Usd,
+    /// Cardmarket's retail price
+    Eur,
+    /// Cardhoarder's MTGO price
+    Tix,
+}
+
+///
+/// The price of one printing+finish on one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PricePoint {
+    pub card_ref: CardRef,
+    pub finish: Finish,
+    pub currency: Currency,
+    pub date: Date,
+    /// Hundredths of the currency, so that prices are exact
+    pub cents: usize,
+}
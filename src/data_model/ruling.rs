@@ -0,0 +1,31 @@
+use crate::data_model::date::Date;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ruling {
+    pub date: Date,
+    pub text: String,
+}
+
+///
+/// Oracle text that a card used to have, before an update replaced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OracleChange {
+    /// The day that `build_card_db` noticed the new text
+    pub replaced: Date,
+    pub old_text: String,
+}
+
+///
+/// Everything we know about how a card has been ruled on and reworded.
+/// Both lists are oldest first.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CardRulings {
+    pub rulings: Vec<Ruling>,
+    pub oracle_changes: Vec<OracleChange>,
+}
+
+impl CardRulings {
+    pub fn is_empty(&self) -> bool {
+        self.rulings.is_empty() && self.oracle_changes.is_empty()
+    }
+}
@@ -0,0 +1,16 @@
+use crate::data_model::date::Date;
+
+///
+/// Metadata about one set, as listed in MTGJSON's set files.
+/// Cards only refer to sets by `code`; this is what the code means.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetInfo {
+    pub code: String,
+    pub name: String,
+    pub release_date: Date,
+    /// MTGJSON's `type`, e.g. `expansion`, `core`, `commander`
+    pub set_type: String,
+    pub block: Option<String>,
+    pub parent_code: Option<String>,
+    pub card_count: usize,
+}
@@ -0,0 +1,32 @@
+use crate::data_model::{card::CardRef, collection::CollectionEntryId};
+
+///
+/// What a tag is attached to: every copy of a card, or one entry in the collection.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TagTarget {
+    /// The card itself (not a printing), as keyed in the all-cards DB
+    Card(CardRef),
+    Copy(CollectionEntryId),
+}
+
+///
+/// Tags are case-insensitive and can't contain whitespace, so that `tag:` can find them.
+/// Returns `None` for tags which would be empty.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag
+        .trim()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+
+    (!tag.is_empty()).then_some(tag)
+}
+
+#[cfg(test)]
+#[test]
+fn test_normalize_tag() {
+    assert_eq!(normalize_tag("Ramp").as_deref(), Some("ramp"));
+    assert_eq!(normalize_tag(" card draw ").as_deref(), Some("card-draw"));
+    assert_eq!(normalize_tag("   "), None);
+}
@@ -1,11 +1,17 @@
 use crate::{
-    data_model::card::ColorCombination,
+    data_model::{
+        card::{ColorCombination, ForeignNames, Printing},
+        legality::CardLegalities,
+        ruling::CardRulings,
+        set::SetInfo,
+    },
     dbs::{
         allcards::{Card, DBTree},
         indexes::{
             mana_cost::{self, ManaCostCount},
             stats::card_stats,
-            string_lpm, string_trigram,
+            string_lpm,
+            string_trigram::trigram,
         },
     },
 };
@@ -15,12 +21,22 @@ use minimal_storage::{
     serialize_min::{DeserializeFromMinimal, SerializeMinimal},
 };
 
+//Tables hold their own values (looked up by key), while indexes
+//map their keys to the u128 IDs of cards in the `cards` table.
 macro_rules! layout_all_cards_db {
-    ( $($index_name:ident : $index_type:ty : $index_dim:literal dimensional $(,)? )* ) => {
+    (
+        tables {
+            $( $table_name:ident : $table_key:ty => $table_value:ty : $table_dim:literal dimensional $(,)? )*
+        }
+        indexes {
+            $( $index_name:ident : $index_type:ty : $index_dim:literal dimensional $(,)? )*
+        }
+    ) => {
         pub struct AllCardsDbLayout {
             pub num_cards: usize,
             pub cards_page: PageId<{ tree::PAGE_SIZE }>,
 
+            $( pub $table_name: PageId<{ tree::PAGE_SIZE }>, )*
             $( pub $index_name: PageId<{ tree::PAGE_SIZE }>, )*
         }
 
@@ -35,6 +51,7 @@ macro_rules! layout_all_cards_db {
                 self.num_cards.minimally_serialize(write_to, ())?;
                 self.cards_page.minimally_serialize(write_to, ())?;
 
+                $( self.$table_name.minimally_serialize(write_to, ())?; )*
                 $( self.$index_name.minimally_serialize(write_to, ())?; )*
 
                 Ok(())
@@ -51,11 +68,13 @@ macro_rules! layout_all_cards_db {
                 let num_cards = usize::deserialize_minimal(from, ())?;
                 let cards_page = PageId::deserialize_minimal(from, ())?;
 
+                $( let $table_name = PageId::deserialize_minimal(from, ())?; )*
                 $( let $index_name = PageId::deserialize_minimal(from, ())?; )*
 
                 Ok(Self {
                     num_cards,
                     cards_page,
+                    $($table_name,)*
                     $($index_name),*
                 })
             }
@@ -64,12 +83,14 @@ macro_rules! layout_all_cards_db {
             pub(super) num_cards: std::sync::atomic::AtomicUsize,
             pub(super) cards: DBTree<1, u128, Card>,
 
+            $( pub(super) $table_name: DBTree<$table_dim, $table_key, $table_value>, )*
             $( pub(super) $index_name: DBTree<$index_dim, $index_type, u128>, )*
         }
         impl AllCardsDb {
             pub fn condense(&mut self) {
                 self.cards.condense();
 
+                $(self.$table_name.condense();)*
                 $(self.$index_name.condense();)*
             }
         }
@@ -86,11 +107,13 @@ macro_rules! layout_all_cards_db {
                 );
                 use tree::tree_traits::MultidimensionalParent;
 
+                $( let $table_name = tree::sparse::open_storage(<$table_key as tree::tree_traits::MultidimensionalKey<$table_dim>>::Parent::UNIVERSE, storage, Some(layout_read.$table_name)); )*
                 $( let $index_name = tree::sparse::open_storage(<$index_type as tree::tree_traits::MultidimensionalKey<$index_dim>>::Parent::UNIVERSE, storage, Some(layout_read.$index_name)); )*
 
                 Ok(AllCardsDb {
                     num_cards: layout_read.num_cards.into(),
                     cards,
+                    $( $table_name, )*
                     $( $index_name, )*
                 })
             }
@@ -102,6 +125,10 @@ macro_rules! layout_all_cards_db {
 
                     use tree::tree_traits::MultidimensionalParent;
 
+                    $(
+                        let mut $table_name = (tree::sparse::open_storage(<$table_key as tree::tree_traits::MultidimensionalKey<$table_dim>>::Parent::UNIVERSE, storage, None), known_layout_page_id);
+                        $table_name.1 = $table_name.0.root_page_id();
+                    )*
                     $(
                                                     //just reusing the known_layout_page_id to have something to put there for now. It will be overwritten
                                                     //in the next statement.
@@ -112,12 +139,14 @@ macro_rules! layout_all_cards_db {
                     db_swap = Some(AllCardsDb {
                         num_cards: 0.into(),
                         cards,
+                        $( $table_name: $table_name.0, )*
                         $( $index_name: $index_name.0, )*
                     });
 
                     AllCardsDbLayout {
                         num_cards: 0,
                         cards_page,
+                        $( $table_name: $table_name.1, )*
                         $( $index_name: $index_name.1, )*
                     }
                 });
@@ -132,10 +161,36 @@ macro_rules! layout_all_cards_db {
 }
 
 layout_all_cards_db! {
-    color: ColorCombination: 6 dimensional,
-    color_id: ColorCombination:  6 dimensional,
-    mana_cost: ManaCostCount::Key: 12 dimensional,
-    types: string_lpm::StringPrefix: 1 dimensional,
-    card_names: string_lpm::StringPrefix: 1 dimensional,
-    stats: card_stats::Key: 6 dimensional,
+    tables {
+        sets: string_lpm::StringPrefix => SetInfo: 1 dimensional,
+        printings: u128 => Printing: 1 dimensional,
+        //keyed by card ID
+        rulings: u128 => CardRulings: 1 dimensional,
+        //keyed by card ID
+        foreign_names: u128 => ForeignNames: 1 dimensional,
+        //keyed by card ID
+        legalities: u128 => CardLegalities: 1 dimensional,
+        //keyed by printing ID, to MTGJSON's UUID for the printing
+        uuids: u128 => u128: 1 dimensional,
+        //keyed by printing ID, to MTGO's catalog ID for its nonfoil version
+        mtgo_ids: u128 => u128: 1 dimensional,
+    }
+    indexes {
+        color: ColorCombination: 6 dimensional,
+        color_id: ColorCombination:  6 dimensional,
+        mana_cost: ManaCostCount::Key: 12 dimensional,
+        types: string_lpm::StringPrefix: 1 dimensional,
+        card_names: string_lpm::StringPrefix: 1 dimensional,
+        stats: card_stats::Key: 6 dimensional,
+        //card ID to the IDs of its printings
+        printings_of: u128: 1 dimensional,
+        //MTGJSON's UUID for a printing to its ID
+        printing_uuids: u128: 1 dimensional,
+        //MTGO's catalog IDs for a printing (foil and nonfoil) to its ID
+        printing_mtgo_ids: u128: 1 dimensional,
+        //non-English names, for exact and prefix searches
+        foreign_name_prefixes: string_lpm::StringPrefix: 1 dimensional,
+        //non-English names, for substring searches. The trigram's field is the language.
+        foreign_name_trigrams: trigram::Key: 2 dimensional,
+    }
 }
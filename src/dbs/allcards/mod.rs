@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeMap, BinaryHeap},
+    collections::{BTreeMap, BinaryHeap, HashSet},
     ops::{AddAssign, Deref},
     path::Path,
     u128,
@@ -15,9 +15,14 @@ use tree::{
 };
 
 use crate::{
-    data_model::card::{Card, CardRef},
+    data_model::{
+        card::{Card, CardRef, ForeignName, ForeignNames, Printing},
+        legality::CardLegalities,
+        ruling::{CardRulings, OracleChange, Ruling},
+        set::SetInfo,
+    },
     dbs::{
-        allcards::cardref_key::card_ref_to_index,
+        allcards::cardref_key::{card_ref_to_index, index_to_card_ref},
         indexes::{
             color_combination::ColorCombinationMaybe,
             mana_cost::ManaCostCount,
@@ -33,7 +38,7 @@ mod db_layout;
 
 pub use db_layout::AllCardsDb;
 
-type DBTree<const DIMENSIONS: usize, Key, Value> = tree::sparse::StoredTree<
+pub(crate) type DBTree<const DIMENSIONS: usize, Key, Value> = tree::sparse::StoredTree<
     DIMENSIONS,
     8000,
     Key,
@@ -66,12 +71,55 @@ impl AllCardsDb {
             .flat_map(|x| self.cards.get_owned(&x))
     }
 
+    ///
+    /// Searches names in every language. A card which matches in more than one
+    /// language is only returned once.
     pub fn query_name<'a>(
         &'a self,
         query: &'a LongestPrefixMatch,
     ) -> impl Iterator<Item = Card> + 'a {
+        let mut seen = HashSet::new();
+
         self.card_names
             .find_items_in_box(&query)
+            .chain(self.foreign_name_prefixes.find_items_in_box(&query))
+            .filter(move |x| seen.insert(*x))
+            .flat_map(|x| self.cards.get_owned(&x))
+    }
+
+    ///
+    /// Cards with a non-English name that contains `needle`, ignoring case.
+    /// Needles shorter than a trigram can't use the index, so they find nothing.
+    pub fn query_foreign_name<'a>(&'a self, needle: &str) -> impl Iterator<Item = Card> + 'a {
+        let needle = needle.to_lowercase();
+        let mut candidates = None::<HashSet<u128>>;
+
+        if needle.len() >= 3 {
+            for needle_trigram in string_trigrams(0, &needle) {
+                let found = self
+                    .foreign_name_trigrams
+                    .find_items_in_box(&trigram::Query::any_field(&needle_trigram))
+                    .collect::<HashSet<_>>();
+
+                candidates = Some(match candidates {
+                    Some(c) => c.intersection(&found).copied().collect(),
+                    None => found,
+                });
+            }
+        }
+
+        //trigrams don't care about order, so check the whole string
+        candidates
+            .unwrap_or_default()
+            .into_iter()
+            .filter(move |id| {
+                self.foreign_names.get_owned(id).is_some_and(|names| {
+                    names
+                        .0
+                        .iter()
+                        .any(|x| x.name.to_lowercase().contains(&needle))
+                })
+            })
             .flat_map(|x| self.cards.get_owned(&x))
     }
 
@@ -116,6 +164,125 @@ impl AllCardsDb {
         self.cards.get_readref(&card.0)
     }
 
+    pub fn card_by_name(&self, name: &str) -> Option<Card> {
+        self.card_names
+            .find_items_in_box(&LongestPrefixMatch::new_prefix(name))
+            .flat_map(|x| self.cards.get_owned(&x))
+            .find(|c| c.name == name)
+    }
+
+    ///
+    /// The card that a `CardRef` refers to, whether it's the card's own ref or one of its printings.
+    pub fn card_by_ref(&self, card_ref: &CardRef) -> Option<Card> {
+        let card = self
+            .printing(card_ref)
+            .map(|x| x.card)
+            .unwrap_or_else(|| card_ref.clone());
+
+        self.cards.get_owned(&card_ref_to_index(&card))
+    }
+
+    /// The `CardRef` which the card is keyed by in this DB
+    pub fn card_ref(&self, card: &Card) -> Option<CardRef> {
+        self.card_id(card).map(|CardDbId(id)| index_to_card_ref(id))
+    }
+
+    pub fn card_id(&self, card: &Card) -> Option<CardDbId> {
+        self.card_names
+            .find_items_in_box(&LongestPrefixMatch::new_prefix(card.name.as_str()))
+            //names longer than the prefix can collide, so check the whole name
+            .find(|id| {
+                self.cards
+                    .get_readref(id)
+                    .is_some_and(|c| c.as_ref().name == card.name)
+            })
+            .map(CardDbId)
+    }
+
+    pub fn printing(&self, card_ref: &CardRef) -> Option<Printing> {
+        self.printings.get_owned(&card_ref_to_index(card_ref))
+    }
+
+    pub fn printing_by_uuid(&self, uuid: &str) -> Option<Printing> {
+        let uuid = parse_uuid(uuid)?;
+
+        self.printing_uuids
+            .find_items_in_box(&(uuid..=uuid))
+            .find_map(|x| self.printings.get_owned(&x))
+    }
+
+    /// MTGJSON's UUID for the printing, in its usual 8-4-4-4-12 form
+    pub fn printing_uuid(&self, card_ref: &CardRef) -> Option<String> {
+        let hex = format!("{:032x}", self.uuids.get_owned(&card_ref_to_index(card_ref))?);
+        Some(format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        ))
+    }
+
+    /// The printing MTGO's catalog ID (a `.dek` file's `CatID`) is for, whether it's the foil or nonfoil ID
+    pub fn printing_by_mtgo_id(&self, mtgo_id: usize) -> Option<Printing> {
+        let mtgo_id = mtgo_id as u128;
+
+        self.printing_mtgo_ids
+            .find_items_in_box(&(mtgo_id..=mtgo_id))
+            .find_map(|x| self.printings.get_owned(&x))
+    }
+
+    /// MTGO's catalog ID for the nonfoil version of the printing
+    pub fn mtgo_id(&self, card_ref: &CardRef) -> Option<usize> {
+        self.mtgo_ids
+            .get_owned(&card_ref_to_index(card_ref))
+            .map(|x| x as usize)
+    }
+
+    pub fn printings(&self, card: &Card) -> Vec<Printing> {
+        let Some(CardDbId(id)) = self.card_id(card) else {
+            return Vec::new();
+        };
+
+        self.printings_of
+            .find_items_in_box(&(id..=id))
+            .flat_map(|x| self.printings.get_owned(&x))
+            .collect()
+    }
+
+    pub fn foreign_names(&self, card: &Card) -> Vec<ForeignName> {
+        self.card_id(card)
+            .and_then(|CardDbId(id)| self.foreign_names.get_owned(&id))
+            .map(|x| x.0)
+            .unwrap_or_default()
+    }
+
+    /// Oldest first
+    pub fn rulings(&self, card: &Card) -> Vec<Ruling> {
+        self.card_rulings(card).rulings
+    }
+
+    ///
+    /// The oracle texts that this card used to have. This only goes back as far
+    /// as the first DB which was built by a version which tracked it.
+    pub fn oracle_history(&self, card: &Card) -> Vec<OracleChange> {
+        self.card_rulings(card).oracle_changes
+    }
+
+    fn card_rulings(&self, card: &Card) -> CardRulings {
+        self.card_id(card)
+            .and_then(|CardDbId(id)| self.rulings.get_owned(&id))
+            .unwrap_or_default()
+    }
+
+    ///
+    /// The card's status in each format, if the DB was built with legality data.
+    pub fn legalities(&self, card: &Card) -> Option<CardLegalities> {
+        self.card_id(card)
+            .and_then(|CardDbId(id)| self.legalities.get_owned(&id))
+    }
+
     pub fn add(&self, cardref: &CardRef, card: Card) {
         let id = card_ref_to_index(cardref);
         let _increasing_idx = self
@@ -147,6 +314,162 @@ impl AllCardsDb {
 
         self.cards.insert(id, card);
     }
+
+    pub fn add_printing(&self, printing: Printing) {
+        let id = card_ref_to_index(&printing.card_ref);
+
+        self.printings_of
+            .insert(card_ref_to_index(&printing.card), id);
+        self.printings.insert(id, printing);
+    }
+
+    pub fn add_printing_uuid(&self, uuid: &str, card_ref: &CardRef) {
+        if let Some(uuid) = parse_uuid(uuid) {
+            self.printing_uuids.insert(uuid, card_ref_to_index(card_ref));
+            self.uuids.insert(card_ref_to_index(card_ref), uuid);
+        }
+    }
+
+    /// MTGO has separate catalog IDs for a printing's foil and nonfoil versions
+    pub fn add_mtgo_ids(&self, card_ref: &CardRef, nonfoil: Option<usize>, foil: Option<usize>) {
+        let id = card_ref_to_index(card_ref);
+
+        if let Some(nonfoil) = nonfoil {
+            self.mtgo_ids.insert(id, nonfoil as u128);
+        }
+        for mtgo_id in nonfoil.into_iter().chain(foil) {
+            self.printing_mtgo_ids.insert(mtgo_id as u128, id);
+        }
+    }
+
+    pub fn add_rulings(&self, cardref: &CardRef, rulings: CardRulings) {
+        if !rulings.is_empty() {
+            self.rulings.insert(card_ref_to_index(cardref), rulings);
+        }
+    }
+
+    pub fn add_legalities(&self, cardref: &CardRef, legalities: CardLegalities) {
+        self.legalities.insert(card_ref_to_index(cardref), legalities);
+    }
+
+    pub fn add_foreign_names(&self, cardref: &CardRef, names: ForeignNames) {
+        if names.0.is_empty() {
+            return;
+        }
+
+        let id = card_ref_to_index(cardref);
+        for foreign in names.0.iter() {
+            self.foreign_name_prefixes
+                .insert(StringPrefix::new_prefix(foreign.name.to_lowercase()), id);
+
+            for key in string_trigrams(foreign.language.as_u8(), &foreign.name.to_lowercase()) {
+                self.foreign_name_trigrams.insert(key, id);
+            }
+        }
+        self.foreign_names.insert(id, names);
+    }
+
+    pub fn add_set(&self, set: SetInfo) {
+        let key = StringPrefix::new_prefix(set.code.to_ascii_uppercase());
+        self.sets.insert(key, set);
+    }
+
+    ///
+    /// Set codes are matched case-insensitively, since different data sources
+    /// disagree on whether they're upper or lower case.
+    pub fn set_info(&self, code: &str) -> Option<SetInfo> {
+        let code = code.to_ascii_uppercase();
+        let query = LongestPrefixMatch::new_prefix(code.as_str());
+
+        //prefix matching means that e.g. "M2" will also find "M20", so check the whole code
+        self.sets
+            .find_items_in_box(&query)
+            .find(|set| set.code.eq_ignore_ascii_case(&code))
+    }
+
+    pub fn all_sets(&self) -> impl Iterator<Item = SetInfo> {
+        self.sets.find_items_in_box(&LongestPrefixMatch::UNIVERSE)
+    }
+}
+
+///
+/// MTGJSON's UUIDs are 128 bits of hex with some dashes thrown in, so
+/// they fit exactly into a key.
+fn parse_uuid(uuid: &str) -> Option<u128> {
+    let hex = uuid.replace('-', "");
+    if hex.len() != 32 {
+        return None;
+    }
+    u128::from_str_radix(&hex, 16).ok()
+}
+
+///
+/// A DB holding only these cards, for tests. Each is keyed as `TST <n>`, counting from 1,
+/// and has one printing with the same ref.
+#[cfg(test)]
+pub(crate) fn test_all_cards_db(name: &str, cards: Vec<Card>) -> (AllCardsDb, Vec<CardRef>) {
+    let db = AllCardsDb::open(crate::dbs::folders::test_db_path(name)).unwrap();
+
+    let refs = (1..=cards.len())
+        .map(|n| CardRef {
+            set: "TST".to_string(),
+            collector_number: n.into(),
+            printing: None,
+        })
+        .collect::<Vec<_>>();
+    for (card_ref, card) in refs.iter().zip(cards) {
+        db.add(card_ref, card);
+        db.add_printing(Printing {
+            card_ref: card_ref.clone(),
+            card: card_ref.clone(),
+            artist: String::new(),
+            flavor_text: String::new(),
+            watermark: None,
+            frame: "2015".to_string(),
+            border: "black".to_string(),
+        });
+    }
+
+    (db, refs)
+}
+
+#[cfg(test)]
+#[test]
+fn test_foreign_names() {
+    use crate::data_model::card::Language;
+
+    let (db, refs) = test_all_cards_db(
+        "allcards-foreign",
+        vec![Card::named("Lightning Bolt"), Card::named("Shock")],
+    );
+    db.add_foreign_names(
+        &refs[0],
+        ForeignNames(vec![
+            ForeignName {
+                language: Language::German,
+                name: "Blitzschlag".to_string(),
+            },
+            ForeignName {
+                language: Language::Russian,
+                name: "Молния".to_string(),
+            },
+        ]),
+    );
+
+    //names are looked up in lower case, and not just ASCII lower case
+    let by_name = |name: &str| {
+        db.query_name(&LongestPrefixMatch::new_prefix(name.to_lowercase()))
+            .map(|x| x.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(by_name("Blitzschlag"), ["Lightning Bolt"]);
+    assert_eq!(by_name("МОЛНИЯ"), ["Lightning Bolt"]);
+    assert_eq!(by_name("Foudre"), Vec::<String>::new());
+
+    let containing = |needle: &str| db.query_foreign_name(needle).map(|x| x.name).collect::<Vec<_>>();
+    assert_eq!(containing("SCHLAG"), ["Lightning Bolt"]);
+    assert_eq!(containing("олни"), ["Lightning Bolt"]);
+    assert_eq!(containing("schock"), Vec::<String>::new());
 }
 
 mod build {
@@ -0,0 +1,425 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::ErrorKind,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    data_model::{
+        card::{CardRef, PhysicalCard},
+        collection::{CollectionEntry, CollectionEntryId},
+    },
+    dbs::{
+        folders::save_directory,
+        journal::{Journal, JournalEdit},
+        paged_db::layout_paged_db,
+    },
+};
+
+///
+/// One change to the collection. The collection is stored as the log of every
+/// change made to it. Condensing takes a snapshot of the whole collection, so
+/// opening it only replays the changes since the last snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollectionChange {
+    Add {
+        id: CollectionEntryId,
+        card: PhysicalCard,
+    },
+    Remove {
+        id: CollectionEntryId,
+    },
+    SetQuantity {
+        id: CollectionEntryId,
+        quantity: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CollectionLogRecord {
+    /// Position in the log. Trees don't give their keys back, so this is kept alongside.
+    pub seq: u128,
+    pub change: CollectionChange,
+}
+
+///
+/// A snapshot of the collection, holding every change before `seq`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CollectionSnapshot {
+    pub seq: u128,
+    pub next_id: usize,
+}
+
+layout_paged_db! {
+    CollectionLog {
+        changes: u128 => CollectionLogRecord: 1 dimensional,
+        //keyed by the seq they were taken at
+        snapshots: u128 => CollectionSnapshot: 1 dimensional,
+        //each snapshot's entries, as `Add`s keyed and numbered by the snapshot's seq
+        snapshot_entries: u128 => CollectionLogRecord: 1 dimensional,
+    }
+}
+
+#[derive(Default)]
+struct CollectionState {
+    entries: BTreeMap<CollectionEntryId, PhysicalCard>,
+    /// The entries for each printing, so they can be looked up card by card
+    by_printing: HashMap<CardRef, BTreeSet<CollectionEntryId>>,
+    next_id: usize,
+    next_seq: u128,
+    /// Where the latest snapshot was taken
+    snapshot_seq: Option<u128>,
+}
+
+impl CollectionState {
+    fn apply(&mut self, change: &CollectionChange) {
+        match change {
+            CollectionChange::Add { id, card } => {
+                //undo can put an entry back over a changed one
+                if let Some(old) = self.entries.insert(*id, card.clone()) {
+                    if let Some(ids) = self.by_printing.get_mut(&old.card) {
+                        ids.remove(id);
+                    }
+                }
+                self.by_printing
+                    .entry(card.card.clone())
+                    .or_default()
+                    .insert(*id);
+                self.next_id = self.next_id.max(id.0 + 1);
+            }
+            CollectionChange::Remove { id } => {
+                if let Some(card) = self.entries.remove(id) {
+                    if let Some(ids) = self.by_printing.get_mut(&card.card) {
+                        ids.remove(id);
+                    }
+                }
+            }
+            CollectionChange::SetQuantity { id, quantity } => {
+                if let Some(card) = self.entries.get_mut(id) {
+                    card.duplicates = *quantity;
+                }
+            }
+        }
+    }
+}
+
+///
+/// The user's own cards.
+pub struct CollectionDb {
+    log: CollectionLog,
+    state: RwLock<CollectionState>,
+    journal: Option<Arc<Journal>>,
+}
+
+impl CollectionDb {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let log = CollectionLog::open_trees(path)?;
+
+        let mut state = CollectionState::default();
+        let snapshot = log
+            .snapshots
+            .find_items_in_box(&(u128::MIN..=u128::MAX))
+            .max_by_key(|x| x.seq);
+        if let Some(snapshot) = snapshot {
+            for record in log
+                .snapshot_entries
+                .find_items_in_box(&(snapshot.seq..=snapshot.seq))
+            {
+                state.apply(&record.change);
+            }
+            //entries that were removed before the snapshot still used up their IDs
+            state.next_id = state.next_id.max(snapshot.next_id);
+            state.next_seq = snapshot.seq;
+            state.snapshot_seq = Some(snapshot.seq);
+        }
+
+        let mut records = log
+            .changes
+            .find_items_in_box(&(state.next_seq..=u128::MAX))
+            .collect::<Vec<_>>();
+        records.sort_by_key(|x| x.seq);
+
+        for record in records.iter() {
+            state.apply(&record.change);
+            state.next_seq = record.seq + 1;
+        }
+
+        Ok(Self {
+            log,
+            state: RwLock::new(state),
+            journal: None,
+        })
+    }
+
+    /// Records every change from now on in the journal, so it can be undone
+    pub fn with_journal(mut self, journal: Arc<Journal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    pub fn open_in_save_directory() -> std::io::Result<Self> {
+        let dir = save_directory().ok_or(ErrorKind::NotFound)?;
+        Self::open(dir.join("collection.db"))
+    }
+
+    fn record(&self, state: &mut CollectionState, change: CollectionChange) -> JournalEdit {
+        let seq = state.next_seq;
+        state.next_seq += 1;
+
+        let id = match &change {
+            CollectionChange::Add { id, .. }
+            | CollectionChange::Remove { id }
+            | CollectionChange::SetQuantity { id, .. } => *id,
+        };
+        let before = state.entries.get(&id).cloned();
+        state.apply(&change);
+        let after = state.entries.get(&id).cloned();
+
+        self.log
+            .changes
+            .insert(seq, CollectionLogRecord { seq, change });
+
+        JournalEdit::Collection { id, before, after }
+    }
+
+    //only called once the state's lock is released, since undoing goes the other way around
+    fn journal(&self, edit: JournalEdit) {
+        if let Some(journal) = &self.journal {
+            journal.step(vec![edit]);
+        }
+    }
+
+    ///
+    /// Puts an entry into the given state without journaling it; this is what undo and redo use.
+    pub(crate) fn restore(&self, id: CollectionEntryId, card: Option<PhysicalCard>) {
+        let mut state = self.state.write().unwrap();
+
+        match card {
+            Some(card) => {
+                self.record(&mut state, CollectionChange::Add { id, card });
+            }
+            None if state.entries.contains_key(&id) => {
+                self.record(&mut state, CollectionChange::Remove { id });
+            }
+            None => {}
+        }
+    }
+
+    ///
+    /// Adds cards to the collection. If there's already an entry for the same kind
+    /// of card (same printing, finish, condition, etc.), they're stacked onto it.
+    pub fn add(&self, card: PhysicalCard) -> CollectionEntryId {
+        let mut state = self.state.write().unwrap();
+
+        let existing = state.by_printing.get(&card.card).and_then(|ids| {
+            ids.iter()
+                .filter_map(|id| Some((*id, state.entries.get(id)?)))
+                .find(|(_, c)| c.same_kind_as(&card))
+                .map(|(id, c)| (id, c.duplicates))
+        });
+
+        let (id, edit) = match existing {
+            Some((id, quantity)) => {
+                let quantity = quantity + card.duplicates;
+                (id, self.record(&mut state, CollectionChange::SetQuantity { id, quantity }))
+            }
+            None => {
+                let id = CollectionEntryId(state.next_id);
+                (id, self.record(&mut state, CollectionChange::Add { id, card }))
+            }
+        };
+        drop(state);
+
+        self.journal(edit);
+        id
+    }
+
+    /// Returns whether there was such an entry
+    pub fn remove(&self, id: CollectionEntryId) -> bool {
+        let mut state = self.state.write().unwrap();
+
+        if !state.entries.contains_key(&id) {
+            return false;
+        }
+        let edit = self.record(&mut state, CollectionChange::Remove { id });
+        drop(state);
+
+        self.journal(edit);
+        true
+    }
+
+    //the entry has to exist
+    fn record_quantity(&self, state: &mut CollectionState, id: CollectionEntryId, quantity: usize) -> JournalEdit {
+        if quantity == 0 {
+            self.record(state, CollectionChange::Remove { id })
+        } else {
+            self.record(state, CollectionChange::SetQuantity { id, quantity })
+        }
+    }
+
+    ///
+    /// Setting the quantity to 0 removes the entry. Returns whether there was such an entry.
+    pub fn set_quantity(&self, id: CollectionEntryId, quantity: usize) -> bool {
+        let mut state = self.state.write().unwrap();
+
+        if !state.entries.contains_key(&id) {
+            return false;
+        }
+        let edit = self.record_quantity(&mut state, id, quantity);
+        drop(state);
+
+        self.journal(edit);
+        true
+    }
+
+    ///
+    /// Adds `delta` to the quantity (which can't go below 0) and returns the new quantity,
+    /// or `None` if there's no such entry. Going down to 0 removes the entry.
+    pub fn adjust_quantity(&self, id: CollectionEntryId, delta: isize) -> Option<usize> {
+        let mut state = self.state.write().unwrap();
+
+        //read and written under the same lock, so concurrent adjustments don't get lost
+        let quantity = state.entries.get(&id)?.duplicates.saturating_add_signed(delta);
+        let edit = self.record_quantity(&mut state, id, quantity);
+        drop(state);
+
+        self.journal(edit);
+        Some(quantity)
+    }
+
+    pub fn entry(&self, id: CollectionEntryId) -> Option<PhysicalCard> {
+        self.state.read().unwrap().entries.get(&id).cloned()
+    }
+
+    pub fn entries(&self) -> Vec<CollectionEntry> {
+        self.state
+            .read()
+            .unwrap()
+            .entries
+            .iter()
+            .map(|(id, card)| CollectionEntry {
+                id: *id,
+                card: card.clone(),
+            })
+            .collect()
+    }
+
+    /// Every entry for this printing
+    pub fn entries_of(&self, card_ref: &CardRef) -> Vec<CollectionEntry> {
+        let state = self.state.read().unwrap();
+
+        let Some(ids) = state.by_printing.get(card_ref) else {
+            return Vec::new();
+        };
+        ids.iter()
+            .filter_map(|id| {
+                state.entries.get(id).map(|card| CollectionEntry {
+                    id: *id,
+                    card: card.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// How many copies of this printing are in the collection, across all entries
+    pub fn count_of(&self, card_ref: &CardRef) -> usize {
+        self.entries_of(card_ref)
+            .iter()
+            .map(|x| x.card.duplicates)
+            .sum()
+    }
+
+    ///
+    /// Takes a snapshot of the collection, so the changes so far don't have to be replayed
+    /// on the next open, then compacts the file.
+    pub fn condense(&mut self) {
+        let state = self.state.get_mut().unwrap();
+        let seq = state.next_seq;
+
+        //nothing's changed since the last one
+        if state.snapshot_seq != Some(seq) {
+            for (id, card) in state.entries.iter() {
+                let change = CollectionChange::Add {
+                    id: *id,
+                    card: card.clone(),
+                };
+                self.log
+                    .snapshot_entries
+                    .insert(seq, CollectionLogRecord { seq, change });
+            }
+            //written last, so a snapshot that was cut short is never used
+            self.log.snapshots.insert(
+                seq,
+                CollectionSnapshot {
+                    seq,
+                    next_id: state.next_id,
+                },
+            );
+            state.snapshot_seq = Some(seq);
+        }
+
+        self.log.condense();
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_adjust_quantity() {
+    let collection = CollectionDb::open(crate::dbs::folders::test_db_path("adjust-collection")).unwrap();
+    let card = CardRef {
+        set: "TST".to_string(),
+        collector_number: 1.into(),
+        printing: None,
+    };
+
+    let id = collection.add(PhysicalCard::new(card.clone(), 2));
+    assert_eq!(collection.adjust_quantity(id, 3), Some(5));
+    assert_eq!(collection.adjust_quantity(id, -2), Some(3));
+    assert_eq!(collection.count_of(&card), 3);
+
+    //going below 0 stops at 0, which removes the entry
+    assert_eq!(collection.adjust_quantity(id, -10), Some(0));
+    assert_eq!(collection.entry(id), None);
+    assert_eq!(collection.adjust_quantity(id, 1), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_collection_snapshot() {
+    let path = crate::dbs::folders::test_db_path("snapshot-collection");
+    let card = |n: usize| CardRef {
+        set: "TST".to_string(),
+        collector_number: n.into(),
+        printing: None,
+    };
+
+    let mut collection = CollectionDb::open(&path).unwrap();
+    let first = collection.add(PhysicalCard::new(card(1), 2));
+    let removed = collection.add(PhysicalCard::new(card(2), 1));
+    collection.remove(removed);
+    collection.condense();
+    //a second condense with nothing new doesn't take another snapshot
+    collection.condense();
+
+    collection.set_quantity(first, 5);
+    let after = collection.add(PhysicalCard::new(card(3), 1));
+    let expected = collection.entries();
+    drop(collection);
+
+    //the snapshot and the changes after it give the same collection
+    let mut collection = CollectionDb::open(&path).unwrap();
+    assert_eq!(collection.entries(), expected);
+    //IDs of entries removed before the snapshot aren't handed out again
+    let newest = collection.add(PhysicalCard::new(card(4), 1));
+    assert!(newest.0 > after.0 && newest != removed);
+    //stacking onto an existing entry works from the snapshot too
+    assert_eq!(collection.add(PhysicalCard::new(card(1), 1)), first);
+    collection.condense();
+    let expected = collection.entries();
+    drop(collection);
+
+    let collection = CollectionDb::open(&path).unwrap();
+    assert_eq!(collection.entries(), expected);
+    assert_eq!(collection.count_of(&card(1)), 6);
+}
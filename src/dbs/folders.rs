@@ -20,3 +20,13 @@ pub fn save_directory() -> Option<PathBuf> {
 
     return Some(data_dir);
 }
+
+///
+/// A fresh file for a DB to be opened at in tests. It's left behind afterwards,
+/// but removed before each run.
+#[cfg(test)]
+pub(crate) fn test_db_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{APPNAME_DIRECTORY}-test-{}-{name}.db", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
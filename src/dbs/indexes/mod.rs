@@ -3,6 +3,7 @@
 
 pub mod color_combination;
 pub mod mana_cost;
+pub mod price;
 pub mod stats;
 pub mod string_lpm;
 pub mod string_trigram;
@@ -0,0 +1,68 @@
+use crate::{
+    data_model::{
+        card::{CardRef, Finish},
+        price::{Currency, PricePoint},
+    },
+    dbs::{allcards::cardref_key::card_ref_to_index, indexes::helpers::make_index_types},
+};
+
+make_index_types! {
+    key price_point {
+        printing: u128,
+        finish: u8,
+        currency: u8,
+        date: usize,
+    }
+}
+
+pub fn finish_key(f: Finish) -> u8 {
+    match f {
+        Finish::Nonfoil => 0,
+        Finish::Foil => 1,
+        Finish::Etched => 2,
+    }
+}
+
+pub fn currency_key(c: Currency) -> u8 {
+    match c {
+        Currency::Usd => 0,
+        Currency::Eur => 1,
+        Currency::Tix => 2,
+    }
+}
+
+impl price_point::Key {
+    pub fn new(p: &PricePoint) -> Self {
+        Self {
+            printing: card_ref_to_index(&p.card_ref),
+            finish: finish_key(p.finish),
+            currency: currency_key(p.currency),
+            date: p.date.as_packed(),
+        }
+    }
+}
+
+impl price_point::Query {
+    pub fn exactly(key: &price_point::Key) -> Self {
+        Self {
+            printing: key.printing..=key.printing,
+            finish: key.finish..=key.finish,
+            currency: key.currency..=key.currency,
+            date: key.date..=key.date,
+        }
+    }
+
+    /// Every recorded price for the printing, finish, and currency
+    pub fn history(card_ref: &CardRef, finish: Finish, currency: Currency) -> Self {
+        let printing = card_ref_to_index(card_ref);
+        let finish = finish_key(finish);
+        let currency = currency_key(currency);
+
+        Self {
+            printing: printing..=printing,
+            finish: finish..=finish,
+            currency: currency..=currency,
+            date: usize::MIN..=usize::MAX,
+        }
+    }
+}
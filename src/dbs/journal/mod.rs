@@ -0,0 +1,601 @@
+use std::{collections::BTreeMap, io::ErrorKind, path::Path, sync::RwLock, time::SystemTime};
+
+use crate::{
+    data_model::{
+        card::{CardRef, PhysicalCard},
+        collection::CollectionEntryId,
+        date::Date,
+        group::{CardGroup, GroupId},
+    },
+    dbs::{
+        collection::CollectionDb, folders::save_directory, paged_db::layout_paged_db,
+        saving_decks::GroupDb,
+    },
+};
+
+///
+/// What one thing looked like before and after a change. `None` means it didn't exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalEdit {
+    Collection {
+        id: CollectionEntryId,
+        before: Option<PhysicalCard>,
+        after: Option<PhysicalCard>,
+    },
+    Group {
+        id: GroupId,
+        before: Option<CardGroup>,
+        after: Option<CardGroup>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JournalTarget {
+    Collection(CollectionEntryId),
+    Group(GroupId),
+}
+
+impl JournalEdit {
+    pub fn target(&self) -> JournalTarget {
+        match self {
+            JournalEdit::Collection { id, .. } => JournalTarget::Collection(*id),
+            JournalEdit::Group { id, .. } => JournalTarget::Group(*id),
+        }
+    }
+
+    /// How many copies of any of these printings there were, before and after
+    pub fn copies_of(&self, printings: &[CardRef]) -> (usize, usize) {
+        match self {
+            JournalEdit::Collection { before, after, .. } => {
+                let count = |x: &Option<PhysicalCard>| {
+                    x.as_ref()
+                        .filter(|x| printings.contains(&x.card))
+                        .map_or(0, |x| x.duplicates)
+                };
+                (count(before), count(after))
+            }
+            JournalEdit::Group { before, after, .. } => {
+                let count = |x: &Option<CardGroup>| {
+                    x.as_ref().map_or(0, |x| {
+                        printings.iter().map(|card| x.cards.count_of(card)).sum()
+                    })
+                };
+                (count(before), count(after))
+            }
+        }
+    }
+}
+
+///
+/// Everything changed by one call into the collection or group stores.
+/// Undo and redo work a whole step at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalStep {
+    pub seq: u128,
+    /// Seconds since the Unix epoch
+    pub at: usize,
+    pub edits: Vec<JournalEdit>,
+    /// Whether the step is currently undone
+    pub undone: bool,
+}
+
+impl JournalStep {
+    pub fn date(&self) -> Date {
+        Date::from_days_since_epoch(self.at / 86400)
+    }
+
+    pub fn touches(&self, target: JournalTarget) -> bool {
+        self.edits.iter().any(|x| x.target() == target)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum JournalAction {
+    Step(Vec<JournalEdit>),
+    /// Undoes the step with this seq
+    Undo(u128),
+    /// Redoes the undone step with this seq
+    Redo(u128),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JournalRecord {
+    pub seq: u128,
+    pub at: usize,
+    pub action: JournalAction,
+}
+
+layout_paged_db! {
+    JournalLog {
+        records: u128 => JournalRecord: 1 dimensional,
+    }
+}
+
+///
+/// One step of the audit trail for a card: how many copies something held before and after.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardHistoryEntry {
+    pub seq: u128,
+    pub at: usize,
+    pub target: JournalTarget,
+    pub before: usize,
+    pub after: usize,
+    pub undone: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalErr {
+    ///
+    /// Something the step touched has been changed since, without going through the journal,
+    /// so undoing or redoing it would throw that change away.
+    Conflict { seq: u128 },
+}
+
+#[derive(Default)]
+struct JournalState {
+    steps: BTreeMap<u128, JournalStep>,
+    undo: Vec<u128>,
+    redo: Vec<u128>,
+    next_seq: u128,
+}
+
+impl JournalState {
+    fn apply(&mut self, record: JournalRecord) {
+        self.next_seq = record.seq + 1;
+
+        match record.action {
+            JournalAction::Step(edits) => {
+                self.steps.insert(
+                    record.seq,
+                    JournalStep {
+                        seq: record.seq,
+                        at: record.at,
+                        edits,
+                        undone: false,
+                    },
+                );
+                self.undo.push(record.seq);
+                self.redo.clear();
+            }
+            JournalAction::Undo(seq) => {
+                //not always the newest step, when undoing one target's steps
+                if let Some(i) = self.undo.iter().rposition(|x| *x == seq) {
+                    self.undo.remove(i);
+                    self.redo.push(seq);
+                    if let Some(step) = self.steps.get_mut(&seq) {
+                        step.undone = true;
+                    }
+                }
+            }
+            JournalAction::Redo(seq) => {
+                if let Some(i) = self.redo.iter().rposition(|x| *x == seq) {
+                    self.redo.remove(i);
+                    self.undo.push(seq);
+                    if let Some(step) = self.steps.get_mut(&seq) {
+                        step.undone = false;
+                    }
+                }
+            }
+        }
+    }
+}
+
+///
+/// Every change made to the collection and groups, so they can be undone and redone
+/// (even after restarting) and looked back over.
+pub struct Journal {
+    log: JournalLog,
+    state: RwLock<JournalState>,
+}
+
+impl Journal {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let log = JournalLog::open_trees(path)?;
+
+        let mut records = log
+            .records
+            .find_items_in_box(&(u128::MIN..=u128::MAX))
+            .collect::<Vec<_>>();
+        records.sort_by_key(|x| x.seq);
+
+        let mut state = JournalState::default();
+        for record in records {
+            state.apply(record);
+        }
+
+        Ok(Self {
+            log,
+            state: RwLock::new(state),
+        })
+    }
+
+    pub fn open_in_save_directory() -> std::io::Result<Self> {
+        let dir = save_directory().ok_or(ErrorKind::NotFound)?;
+        Self::open(dir.join("journal.db"))
+    }
+
+    fn record(&self, state: &mut JournalState, action: JournalAction) {
+        let at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|x| x.as_secs() as usize)
+            .unwrap_or_default();
+        let record = JournalRecord {
+            seq: state.next_seq,
+            at,
+            action,
+        };
+
+        self.log.records.insert(record.seq, record.clone());
+        state.apply(record);
+    }
+
+    /// Called by the stores after each change
+    pub(crate) fn step(&self, edits: Vec<JournalEdit>) {
+        if edits.is_empty() {
+            return;
+        }
+        self.record(&mut self.state.write().unwrap(), JournalAction::Step(edits));
+    }
+
+    /// Whether the edit's before (or after) state is what's there now
+    fn is_current(
+        edit: &JournalEdit,
+        collection: &CollectionDb,
+        groups: &GroupDb,
+        use_after: bool,
+    ) -> bool {
+        match edit {
+            JournalEdit::Collection { id, before, after } => {
+                collection.entry(*id) == *(if use_after { after } else { before })
+            }
+            JournalEdit::Group { id, before, after } => {
+                groups.get(*id) == *(if use_after { after } else { before })
+            }
+        }
+    }
+
+    /// Puts the edit's before (or after) state back into the store
+    fn put(edit: &JournalEdit, collection: &CollectionDb, groups: &GroupDb, use_after: bool) {
+        match edit {
+            JournalEdit::Collection { id, before, after } => {
+                collection.restore(*id, if use_after { after } else { before }.clone())
+            }
+            JournalEdit::Group { id, before, after } => {
+                groups.restore(*id, if use_after { after } else { before }.clone())
+            }
+        }
+    }
+
+    ///
+    /// Puts back everything the most recent step changed, and returns that step.
+    /// Returns `None` if there's nothing left to undo.
+    pub fn undo(
+        &self,
+        collection: &CollectionDb,
+        groups: &GroupDb,
+    ) -> Result<Option<JournalStep>, JournalErr> {
+        self.undo_where(collection, groups, |_| true)
+    }
+
+    ///
+    /// Makes the most recently undone step's changes again, and returns that step.
+    /// Returns `None` if there's nothing to redo; making any new change clears what can be redone.
+    pub fn redo(
+        &self,
+        collection: &CollectionDb,
+        groups: &GroupDb,
+    ) -> Result<Option<JournalStep>, JournalErr> {
+        self.redo_where(collection, groups, |_| true)
+    }
+
+    ///
+    /// Like `undo`, but for the most recent step which changed `target`, skipping over
+    /// newer steps that changed other things. Steps before `since` are left alone.
+    pub fn undo_for(
+        &self,
+        target: JournalTarget,
+        since: u128,
+        collection: &CollectionDb,
+        groups: &GroupDb,
+    ) -> Result<Option<JournalStep>, JournalErr> {
+        self.undo_where(collection, groups, |x| x.seq >= since && x.touches(target))
+    }
+
+    /// Like `redo`, but for the most recently undone step which changed `target`
+    pub fn redo_for(
+        &self,
+        target: JournalTarget,
+        since: u128,
+        collection: &CollectionDb,
+        groups: &GroupDb,
+    ) -> Result<Option<JournalStep>, JournalErr> {
+        self.redo_where(collection, groups, |x| x.seq >= since && x.touches(target))
+    }
+
+    fn undo_where(
+        &self,
+        collection: &CollectionDb,
+        groups: &GroupDb,
+        filter: impl Fn(&JournalStep) -> bool,
+    ) -> Result<Option<JournalStep>, JournalErr> {
+        let mut state = self.state.write().unwrap();
+        let Some(step) = state
+            .undo
+            .iter()
+            .rev()
+            .filter_map(|x| state.steps.get(x))
+            .find(|x| filter(x))
+            .cloned()
+        else {
+            return Ok(None);
+        };
+
+        //a newer step which changed the same things leaves them different from the step's
+        //after state, so this also stops steps being undone out of order
+        if !step
+            .edits
+            .iter()
+            .all(|x| Self::is_current(x, collection, groups, true))
+        {
+            return Err(JournalErr::Conflict { seq: step.seq });
+        }
+        for edit in step.edits.iter().rev() {
+            Self::put(edit, collection, groups, false);
+        }
+
+        self.record(&mut state, JournalAction::Undo(step.seq));
+        Ok(Some(step))
+    }
+
+    fn redo_where(
+        &self,
+        collection: &CollectionDb,
+        groups: &GroupDb,
+        filter: impl Fn(&JournalStep) -> bool,
+    ) -> Result<Option<JournalStep>, JournalErr> {
+        let mut state = self.state.write().unwrap();
+        let Some(step) = state
+            .redo
+            .iter()
+            .rev()
+            .filter_map(|x| state.steps.get(x))
+            .find(|x| filter(x))
+            .cloned()
+        else {
+            return Ok(None);
+        };
+
+        if !step
+            .edits
+            .iter()
+            .all(|x| Self::is_current(x, collection, groups, false))
+        {
+            return Err(JournalErr::Conflict { seq: step.seq });
+        }
+        for edit in step.edits.iter() {
+            Self::put(edit, collection, groups, true);
+        }
+
+        self.record(&mut state, JournalAction::Redo(step.seq));
+        Ok(Some(step))
+    }
+
+    /// Oldest first, including steps which were undone
+    pub fn steps(&self) -> Vec<JournalStep> {
+        self.state.read().unwrap().steps.values().cloned().collect()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.state.read().unwrap().undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.state.read().unwrap().redo.is_empty()
+    }
+
+    /// The seq the next step will get
+    pub fn next_seq(&self) -> u128 {
+        self.state.read().unwrap().next_seq
+    }
+
+    ///
+    /// Every change to how many copies of a card something held, oldest first.
+    /// Pass all of the card's printings (see `AllCardsDb::printings`) to follow the card
+    /// rather than one printing of it.
+    pub fn card_history(&self, printings: &[CardRef]) -> Vec<CardHistoryEntry> {
+        let state = self.state.read().unwrap();
+
+        state
+            .steps
+            .values()
+            .flat_map(|step| {
+                step.edits.iter().filter_map(|edit| {
+                    let (before, after) = edit.copies_of(printings);
+                    (before != after).then(|| CardHistoryEntry {
+                        seq: step.seq,
+                        at: step.at,
+                        target: edit.target(),
+                        before,
+                        after,
+                        undone: step.undone,
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+fn open_test_stores(name: &str) -> (std::sync::Arc<Journal>, CollectionDb, GroupDb) {
+    use crate::dbs::folders::test_db_path;
+
+    let journal = std::sync::Arc::new(Journal::open(test_db_path(&format!("{name}-journal"))).unwrap());
+    let collection = CollectionDb::open(test_db_path(&format!("{name}-collection")))
+        .unwrap()
+        .with_journal(journal.clone());
+    let groups = GroupDb::open(test_db_path(&format!("{name}-groups")))
+        .unwrap()
+        .with_journal(journal.clone());
+    (journal, collection, groups)
+}
+
+#[cfg(test)]
+fn test_card(n: usize) -> CardRef {
+    CardRef {
+        set: "TST".to_string(),
+        collector_number: n.into(),
+        printing: None,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_undo_redo() {
+    let (journal, collection, groups) = open_test_stores("undo-redo");
+
+    let id = collection.add(PhysicalCard::new(test_card(1), 1));
+    collection.set_quantity(id, 3);
+    collection.set_quantity(id, 5);
+    assert_eq!(journal.steps().len(), 3);
+
+    //several levels back, then forward again
+    journal.undo(&collection, &groups).unwrap().unwrap();
+    journal.undo(&collection, &groups).unwrap().unwrap();
+    assert_eq!(collection.entry(id).unwrap().duplicates, 1);
+    journal.undo(&collection, &groups).unwrap().unwrap();
+    assert_eq!(collection.entry(id), None);
+    assert_eq!(journal.undo(&collection, &groups), Ok(None));
+
+    journal.redo(&collection, &groups).unwrap().unwrap();
+    journal.redo(&collection, &groups).unwrap().unwrap();
+    assert_eq!(collection.entry(id).unwrap().duplicates, 3);
+    assert!(journal.can_redo());
+
+    //a new change drops what could be redone
+    collection.set_quantity(id, 4);
+    assert!(!journal.can_redo());
+    assert_eq!(journal.redo(&collection, &groups), Ok(None));
+
+    journal.undo(&collection, &groups).unwrap().unwrap();
+    assert_eq!(collection.entry(id).unwrap().duplicates, 3);
+}
+
+#[cfg(test)]
+#[test]
+fn test_undo_conflict() {
+    use crate::data_model::{deck::DeckSection, group::CardGroupVariety};
+
+    let (journal, collection, groups) = open_test_stores("conflict");
+
+    let id = groups.create(CardGroup::new(CardGroupVariety::Deck, "Burn")).unwrap();
+    groups.add_card(id, DeckSection::Main, test_card(1), 4).unwrap();
+
+    //changed without going through the journal
+    let mut changed = groups.get(id).unwrap();
+    changed.name = "Red Deck Wins".to_string();
+    groups.restore(id, Some(changed.clone()));
+
+    let seq = journal.steps().last().unwrap().seq;
+    assert_eq!(journal.undo(&collection, &groups), Err(JournalErr::Conflict { seq }));
+    assert_eq!(groups.get(id), Some(changed));
+}
+
+#[cfg(test)]
+#[test]
+fn test_journal_reopen() {
+    use crate::dbs::folders::test_db_path;
+
+    let paths = ["reopen-journal", "reopen-collection", "reopen-groups"].map(test_db_path);
+    let open = || {
+        let journal = std::sync::Arc::new(Journal::open(&paths[0]).unwrap());
+        let collection = CollectionDb::open(&paths[1])
+            .unwrap()
+            .with_journal(journal.clone());
+        let groups = GroupDb::open(&paths[2]).unwrap().with_journal(journal.clone());
+        (journal, collection, groups)
+    };
+
+    let id = {
+        let (journal, collection, groups) = open();
+        let id = collection.add(PhysicalCard::new(test_card(1), 2));
+        collection.set_quantity(id, 6);
+        collection.set_quantity(id, 8);
+        journal.undo(&collection, &groups).unwrap().unwrap();
+        id
+    };
+
+    //the undo is replayed, so the undone step is still there to redo
+    let (journal, collection, groups) = open();
+    assert_eq!(journal.steps().len(), 3);
+    assert!(journal.steps()[2].undone);
+    assert!(journal.can_undo() && journal.can_redo());
+    assert_eq!(collection.entry(id).unwrap().duplicates, 6);
+
+    journal.redo(&collection, &groups).unwrap().unwrap();
+    assert_eq!(collection.entry(id).unwrap().duplicates, 8);
+}
+
+#[cfg(test)]
+#[test]
+fn test_card_history() {
+    use crate::data_model::{deck::DeckSection, group::CardGroupVariety};
+
+    let (journal, collection, groups) = open_test_stores("history");
+
+    let entry = collection.add(PhysicalCard::new(test_card(1), 2));
+    collection.add(PhysicalCard::new(test_card(2), 1));
+    let deck = groups.create(CardGroup::new(CardGroupVariety::Deck, "Burn")).unwrap();
+    groups.add_card(deck, DeckSection::Main, test_card(1), 3).unwrap();
+    groups.rename(deck, "Burn 2").unwrap();
+    collection.set_quantity(entry, 4);
+    journal.undo(&collection, &groups).unwrap().unwrap();
+
+    //renaming and the other card don't change how many copies anything held
+    let history = journal.card_history(&[test_card(1)]);
+    let counts = history
+        .iter()
+        .map(|x| (x.target, x.before, x.after, x.undone))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        counts,
+        [
+            (JournalTarget::Collection(entry), 0, 2, false),
+            (JournalTarget::Group(deck), 0, 3, false),
+            (JournalTarget::Collection(entry), 2, 4, true),
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_undo_for() {
+    use crate::data_model::{deck::DeckSection, group::CardGroupVariety};
+
+    let (journal, collection, groups) = open_test_stores("undo-for");
+
+    let burn = groups.create(CardGroup::new(CardGroupVariety::Deck, "Burn")).unwrap();
+    let since = journal.next_seq();
+    let elves = groups.create(CardGroup::new(CardGroupVariety::Deck, "Elves")).unwrap();
+    groups.add_card(burn, DeckSection::Main, test_card(1), 4).unwrap();
+    groups.add_card(elves, DeckSection::Main, test_card(2), 4).unwrap();
+    let burn_target = JournalTarget::Group(burn);
+
+    //Elves' newer edit is left as it is
+    journal.undo_for(burn_target, since, &collection, &groups).unwrap().unwrap();
+    assert_eq!(groups.get(burn).unwrap().cards.total(), 0);
+    assert_eq!(groups.get(elves).unwrap().cards.total(), 4);
+
+    //creating Burn came before `since`, so it can't be undone from here
+    assert_eq!(journal.undo_for(burn_target, since, &collection, &groups), Ok(None));
+    assert!(groups.get(burn).is_some());
+
+    journal.redo_for(burn_target, since, &collection, &groups).unwrap().unwrap();
+    assert_eq!(groups.get(burn).unwrap().cards.total(), 4);
+    assert_eq!(journal.redo_for(burn_target, since, &collection, &groups), Ok(None));
+
+    //plain undo still goes newest first
+    journal.undo(&collection, &groups).unwrap().unwrap();
+    assert_eq!(groups.get(burn).unwrap().cards.total(), 0);
+    journal.undo(&collection, &groups).unwrap().unwrap();
+    assert_eq!(groups.get(elves).unwrap().cards.total(), 0);
+}
@@ -1,4 +1,10 @@
 pub mod allcards;
-mod folders;
+pub mod collection;
+pub mod folders;
 pub mod indexes;
+pub mod journal;
+mod paged_db;
+pub mod prices;
+pub mod saving_decks;
 mod serialization;
+pub mod tags;
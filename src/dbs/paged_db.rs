@@ -0,0 +1,140 @@
+//Storage shared by the smaller DBs (i.e. everything other than the all-cards DB).
+//Each of these is one file holding a handful of DBTrees, whose root pages are
+//listed in a layout page at a known location.
+
+use std::{io::ErrorKind, path::Path};
+
+use minimal_storage::{
+    multitype_paged_storage::MultitypePagedStorage,
+    paged_storage::PageId,
+    serialize_min::{DeserializeFromMinimal, SerializeMinimal},
+};
+
+pub(crate) type Storage = MultitypePagedStorage<{ tree::PAGE_SIZE }, std::fs::File>;
+
+pub(crate) struct TreeRoots(pub Vec<PageId<{ tree::PAGE_SIZE }>>);
+
+impl SerializeMinimal for TreeRoots {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _external_data: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.0.len().minimally_serialize(write_to, ())?;
+        for page in self.0.iter() {
+            page.minimally_serialize(write_to, ())?;
+        }
+        Ok(())
+    }
+}
+
+impl DeserializeFromMinimal for TreeRoots {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _external_data: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let len = usize::deserialize_minimal(from, ())?;
+        let mut pages = Vec::with_capacity(len);
+        for _ in 0..len {
+            pages.push(PageId::deserialize_minimal(from, ())?);
+        }
+        Ok(Self(pages))
+    }
+}
+
+pub(crate) fn open_storage<P: AsRef<Path>>(path: P) -> std::io::Result<Storage> {
+    let file = std::fs::File::options()
+        .create(true)
+        .append(false)
+        .read(true)
+        .write(true)
+        .open(path)?;
+
+    Ok(MultitypePagedStorage::open(file))
+}
+
+pub(crate) fn layout_page_id() -> PageId<{ tree::PAGE_SIZE }> {
+    //the layout is ALWAYS stored at page #1, same as the all-cards DB.
+    unsafe { PageId::from_index(std::num::NonZero::new(1).unwrap()) }
+}
+
+pub(crate) fn missing_root() -> std::io::Error {
+    std::io::Error::new(
+        ErrorKind::InvalidData,
+        "DB layout lists fewer trees than expected",
+    )
+}
+
+macro_rules! layout_paged_db {
+    (
+        $db_name:ident {
+            $( $tree_name:ident : $tree_key:ty => $tree_value:ty : $tree_dim:literal dimensional $(,)? )*
+        }
+    ) => {
+        pub struct $db_name {
+            $( $tree_name: $crate::dbs::allcards::DBTree<$tree_dim, $tree_key, $tree_value>, )*
+        }
+
+        impl $db_name {
+            fn open_trees<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+                use minimal_storage::multitype_paged_storage::StoreByPage;
+                use tree::tree_traits::{MultidimensionalKey, MultidimensionalParent};
+                use $crate::dbs::paged_db::{self, TreeRoots};
+
+                let storage = paged_db::open_storage(path)?;
+                let layout_page_id = paged_db::layout_page_id();
+
+                match StoreByPage::<TreeRoots>::get(&storage, &layout_page_id, ()) {
+                    Some(layout) => {
+                        let layout_read = layout.read();
+                        let mut roots = layout_read.0.iter();
+
+                        $(
+                            let root = *roots.next().ok_or_else(paged_db::missing_root)?;
+                            let $tree_name = tree::sparse::open_storage(
+                                <$tree_key as MultidimensionalKey<$tree_dim>>::Parent::UNIVERSE,
+                                &storage,
+                                Some(root),
+                            );
+                        )*
+
+                        Ok(Self { $( $tree_name, )* })
+                    }
+                    None => {
+                        let mut db_swap = None::<Self>;
+                        let layout_id = storage.new_page_with(|| {
+                            $(
+                                let $tree_name: $crate::dbs::allcards::DBTree<$tree_dim, $tree_key, $tree_value> =
+                                    tree::sparse::open_storage(
+                                        <$tree_key as MultidimensionalKey<$tree_dim>>::Parent::UNIVERSE,
+                                        &storage,
+                                        None,
+                                    );
+                            )*
+
+                            let roots = TreeRoots(vec![ $( $tree_name.root_page_id(), )* ]);
+
+                            db_swap = Some(Self { $( $tree_name, )* });
+
+                            roots
+                        });
+
+                        debug_assert_eq!(layout_id, layout_page_id);
+
+                        Ok(db_swap.unwrap())
+                    }
+                }
+            }
+
+            pub fn condense(&mut self) {
+                $( self.$tree_name.condense(); )*
+            }
+        }
+    };
+}
+
+pub(crate) use layout_paged_db;
@@ -0,0 +1,227 @@
+use std::{
+    fs::File,
+    io::{BufReader, ErrorKind},
+    path::Path,
+};
+
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+
+use crate::{
+    data_model::{card::Finish, date::Date, price::{Currency, PricePoint}},
+    dbs::{allcards::AllCardsDb, prices::PriceDb},
+};
+
+#[derive(Debug, Default)]
+pub struct PriceImportSummary {
+    pub added: usize,
+    pub already_known: usize,
+    /// UUIDs in the price file that aren't printings in the all-cards DB
+    pub unknown_printings: usize,
+}
+
+//(MTGJSON format, MTGJSON provider, the currency we file it under)
+const PROVIDERS: [(&str, &str, Currency); 3] = [
+    ("paper", "tcgplayer", Currency::Usd),
+    ("paper", "cardmarket", Currency::Eur),
+    ("mtgo", "cardhoarder", Currency::Tix),
+];
+
+const FINISHES: [(&str, Finish); 3] = [
+    ("normal", Finish::Nonfoil),
+    ("foil", Finish::Foil),
+    ("etched", Finish::Etched),
+];
+
+///
+/// Imports a price file in MTGJSON's `AllPrices` or `AllPricesToday` format.
+/// Every dated price in the file is kept, so importing `AllPricesToday` each day
+/// builds up a history. The file's read one printing at a time, since `AllPrices`
+/// is too big to hold in memory all at once.
+pub fn import_mtgjson_prices<P: AsRef<Path>>(
+    path: P,
+    cards: &AllCardsDb,
+    prices: &PriceDb,
+) -> std::io::Result<PriceImportSummary> {
+    let rdr = BufReader::new(File::open(path)?);
+    let mut de = serde_json::Deserializer::from_reader(rdr);
+
+    let mut summary = PriceImportSummary::default();
+    let found_data = (&mut de).deserialize_map(PriceFile {
+        cards,
+        prices,
+        summary: &mut summary,
+    })?;
+    de.end()?;
+
+    if !found_data {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "Price file should have a 'data' object",
+        ));
+    }
+
+    Ok(summary)
+}
+
+fn import_printing(
+    uuid: &str,
+    price_formats: &serde_json::Value,
+    cards: &AllCardsDb,
+    prices: &PriceDb,
+    summary: &mut PriceImportSummary,
+) {
+    let Some(printing) = cards.printing_by_uuid(uuid) else {
+        summary.unknown_printings += 1;
+        return;
+    };
+
+    for (format, provider, currency) in PROVIDERS {
+        let retail = &price_formats[format][provider]["retail"];
+
+        for (finish_name, finish) in FINISHES {
+            let Some(by_date) = retail[finish_name].as_object() else {
+                continue;
+            };
+
+            for (date, price) in by_date.iter() {
+                let (Ok(date), Some(price)) = (date.parse::<Date>(), price.as_f64()) else {
+                    continue;
+                };
+
+                let point = PricePoint {
+                    card_ref: printing.card_ref.clone(),
+                    finish,
+                    currency,
+                    date,
+                    cents: (price * 100.0).round() as usize,
+                };
+
+                if prices.add(point) {
+                    summary.added += 1;
+                } else {
+                    summary.already_known += 1;
+                }
+            }
+        }
+    }
+}
+
+///
+/// The whole price file. Everything but `data` is skipped over; visiting it says
+/// whether there was a `data` object.
+struct PriceFile<'a> {
+    cards: &'a AllCardsDb,
+    prices: &'a PriceDb,
+    summary: &'a mut PriceImportSummary,
+}
+
+impl<'de> Visitor<'de> for PriceFile<'_> {
+    type Value = bool;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an MTGJSON price file")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<bool, A::Error> {
+        let mut found_data = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "data" {
+                map.next_value_seed(PriceData {
+                    cards: self.cards,
+                    prices: self.prices,
+                    summary: &mut *self.summary,
+                })?;
+                found_data = true;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(found_data)
+    }
+}
+
+/// The `data` object, from UUID to that printing's prices
+struct PriceData<'a> {
+    cards: &'a AllCardsDb,
+    prices: &'a PriceDb,
+    summary: &'a mut PriceImportSummary,
+}
+
+impl<'de> DeserializeSeed<'de> for PriceData<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PriceData<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map of printing UUIDs to prices")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some((uuid, price_formats)) = map.next_entry::<String, serde_json::Value>()? {
+            import_printing(&uuid, &price_formats, self.cards, self.prices, &mut *self.summary);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_import_mtgjson_prices() {
+    use crate::{
+        data_model::card::Card,
+        dbs::{allcards::test_all_cards_db, folders::test_db_path},
+    };
+
+    let (cards, refs) = test_all_cards_db("prices-import-cards", vec![Card::named("Lightning Bolt")]);
+    cards.add_printing_uuid("a1b2c3d4-0000-4000-8000-000000000001", &refs[0]);
+    let prices = PriceDb::open(test_db_path("prices-import")).unwrap();
+
+    let path = test_db_path("prices-import-json");
+    std::fs::write(
+        &path,
+        r#"{
+            "meta": {"date": "2025-01-02", "version": "5.2.2"},
+            "data": {
+                "a1b2c3d4-0000-4000-8000-000000000001": {
+                    "paper": {
+                        "tcgplayer": {
+                            "retail": {
+                                "normal": {"2025-01-01": 1.25, "2025-01-02": 1.5},
+                                "foil": {"2025-01-02": 4.0}
+                            },
+                            "buylist": {"normal": {"2025-01-02": 0.5}}
+                        },
+                        "cardmarket": {"retail": {"normal": {"2025-01-02": 0.99}}}
+                    },
+                    "mtgo": {"cardhoarder": {"retail": {"normal": {"2025-01-02": 0.02}}}}
+                },
+                "ffffffff-0000-4000-8000-000000000002": {"paper": {}}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let summary = import_mtgjson_prices(&path, &cards, &prices).unwrap();
+    //buylist prices aren't what the cards cost, so they're left out
+    assert_eq!((summary.added, summary.already_known, summary.unknown_printings), (5, 0, 1));
+
+    let latest = |finish, currency| prices.latest(&refs[0], finish, currency).map(|x| x.cents);
+    assert_eq!(latest(Finish::Nonfoil, Currency::Usd), Some(150));
+    assert_eq!(latest(Finish::Foil, Currency::Usd), Some(400));
+    assert_eq!(latest(Finish::Nonfoil, Currency::Eur), Some(99));
+    assert_eq!(latest(Finish::Nonfoil, Currency::Tix), Some(2));
+    assert_eq!(latest(Finish::Etched, Currency::Usd), None);
+
+    //importing the same file again adds nothing
+    let summary = import_mtgjson_prices(&path, &cards, &prices).unwrap();
+    assert_eq!((summary.added, summary.already_known), (0, 5));
+
+    std::fs::write(&path, r#"{"meta": {}}"#).unwrap();
+    assert!(import_mtgjson_prices(&path, &cards, &prices).is_err());
+}
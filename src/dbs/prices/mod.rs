@@ -0,0 +1,143 @@
+use std::{io::ErrorKind, path::Path};
+
+use crate::{
+    data_model::{
+        card::{CardRef, Finish, Printing},
+        price::{Currency, PricePoint},
+    },
+    dbs::{folders::save_directory, indexes::price::price_point, paged_db::layout_paged_db},
+};
+
+pub mod import;
+
+layout_paged_db! {
+    PriceDb {
+        prices: price_point::Key => PricePoint: 4 dimensional,
+    }
+}
+
+impl PriceDb {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Self::open_trees(path)
+    }
+
+    pub fn open_in_save_directory() -> std::io::Result<Self> {
+        let dir = save_directory().ok_or(ErrorKind::NotFound)?;
+        Self::open(dir.join("prices.db"))
+    }
+
+    ///
+    /// Records a price. Each printing+finish+currency only has one price per day,
+    /// so re-importing the same day is a no-op; returns whether it was new.
+    pub fn add(&self, point: PricePoint) -> bool {
+        let key = price_point::Key::new(&point);
+
+        if self
+            .prices
+            .find_items_in_box(&price_point::Query::exactly(&key))
+            .next()
+            .is_some()
+        {
+            return false;
+        }
+
+        self.prices.insert(key, point);
+        true
+    }
+
+    /// Oldest first
+    pub fn history(&self, card_ref: &CardRef, finish: Finish, currency: Currency) -> Vec<PricePoint> {
+        let mut history = self
+            .prices
+            .find_items_in_box(&price_point::Query::history(card_ref, finish, currency))
+            .collect::<Vec<_>>();
+
+        history.sort_by_key(|x| x.date);
+        history
+    }
+
+    pub fn latest(&self, card_ref: &CardRef, finish: Finish, currency: Currency) -> Option<PricePoint> {
+        self.prices
+            .find_items_in_box(&price_point::Query::history(card_ref, finish, currency))
+            .max_by_key(|x| x.date)
+    }
+
+    ///
+    /// The lowest current price out of any finish of any of the printings.
+    /// This is what we use when a card's printing doesn't matter, like in a decklist.
+    pub fn cheapest<'p>(
+        &self,
+        printings: impl IntoIterator<Item = &'p Printing>,
+        currency: Currency,
+    ) -> Option<PricePoint> {
+        printings
+            .into_iter()
+            .flat_map(|p| {
+                Finish::ALL
+                    .into_iter()
+                    .filter_map(|f| self.latest(&p.card_ref, f, currency))
+            })
+            .min_by_key(|x| x.cents)
+    }
+
+    ///
+    /// Total current value of some cards, in cents. Cards without a known price count as 0.
+    pub fn value_of<'c>(
+        &self,
+        cards: impl IntoIterator<Item = (&'c CardRef, Finish, usize)>,
+        currency: Currency,
+    ) -> usize {
+        cards
+            .into_iter()
+            .filter_map(|(card_ref, finish, quantity)| {
+                self.latest(card_ref, finish, currency)
+                    .map(|x| x.cents * quantity)
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_price_history() {
+    use crate::data_model::date::Date;
+
+    let prices = PriceDb::open(crate::dbs::folders::test_db_path("price-history")).unwrap();
+    let card = CardRef {
+        set: "TST".to_string(),
+        collector_number: 1.into(),
+        printing: None,
+    };
+    let point = |date: &str, finish, cents| PricePoint {
+        card_ref: card.clone(),
+        finish,
+        currency: Currency::Usd,
+        date: date.parse::<Date>().unwrap(),
+        cents,
+    };
+
+    //added out of order
+    assert!(prices.add(point("2025-03-01", Finish::Nonfoil, 150)));
+    assert!(prices.add(point("2025-01-01", Finish::Nonfoil, 100)));
+    assert!(prices.add(point("2025-02-01", Finish::Nonfoil, 125)));
+    assert!(prices.add(point("2025-03-01", Finish::Foil, 900)));
+
+    //one price per day; the first one's kept
+    assert!(!prices.add(point("2025-03-01", Finish::Nonfoil, 175)));
+
+    let history = prices
+        .history(&card, Finish::Nonfoil, Currency::Usd)
+        .iter()
+        .map(|x| x.cents)
+        .collect::<Vec<_>>();
+    assert_eq!(history, [100, 125, 150]);
+    assert_eq!(prices.latest(&card, Finish::Nonfoil, Currency::Usd).map(|x| x.cents), Some(150));
+    assert_eq!(prices.latest(&card, Finish::Foil, Currency::Usd).map(|x| x.cents), Some(900));
+    assert_eq!(prices.latest(&card, Finish::Nonfoil, Currency::Eur), None);
+    assert!(prices.history(&card, Finish::Etched, Currency::Usd).is_empty());
+
+    assert_eq!(
+        prices.value_of([(&card, Finish::Nonfoil, 2), (&card, Finish::Foil, 1), (&card, Finish::Etched, 4)], Currency::Usd),
+        1200
+    );
+}
@@ -0,0 +1,390 @@
+//Working out which of the user's cards are spoken for.
+//
+//Exclusive decks reserve their cards: nothing else can use those copies, so a deck can
+//only be made exclusive if no other deck uses any of its cards.
+//Non-exclusive decks share with each other, so between them they only
+//need as many copies as the one which uses the most.
+
+use std::collections::HashMap;
+
+use crate::{
+    data_model::{
+        card::CardRef,
+        deck::{Deck, DeckSection},
+        group::{CardGroup, CardGroupVariety, GroupId},
+    },
+    dbs::{
+        allcards::AllCardsDb,
+        collection::CollectionDb,
+        saving_decks::{GroupDb, GroupDbErr},
+    },
+};
+
+impl CardGroupVariety {
+    ///
+    /// Whether the group lays claim to cards from the collection. Binders and boxes are
+    /// where cards are kept rather than what they're used for, and wishlists aren't owned.
+    pub fn allocates_cards(&self) -> bool {
+        matches!(self, CardGroupVariety::Deck | CardGroupVariety::Cube)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllocationConflict {
+    /// The card (not printing) that there aren't enough copies of
+    pub card: CardRef,
+    pub owned: usize,
+    /// How many the exclusive group (or the one being made exclusive) needs
+    pub wanted: usize,
+    /// The other groups using the card, and how many copies each uses
+    pub claimed_by: Vec<(GroupId, String, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllocationErr {
+    Group(GroupDbErr),
+    Conflicts(Vec<AllocationConflict>),
+}
+
+impl From<GroupDbErr> for AllocationErr {
+    fn from(value: GroupDbErr) -> Self {
+        AllocationErr::Group(value)
+    }
+}
+
+//a group, with how many copies of each card it uses
+type Claim = (GroupId, CardGroup, HashMap<CardRef, usize>);
+
+///
+/// Changes to groups which keep exclusive groups' cards reserved for them.
+/// `GroupDb` itself doesn't check any of this, so edits that should respect
+/// exclusive decks go through here instead.
+pub struct Allocator<'a> {
+    cards: &'a AllCardsDb,
+    collection: &'a CollectionDb,
+    groups: &'a GroupDb,
+}
+
+impl<'a> Allocator<'a> {
+    pub fn new(cards: &'a AllCardsDb, collection: &'a CollectionDb, groups: &'a GroupDb) -> Self {
+        Self {
+            cards,
+            collection,
+            groups,
+        }
+    }
+
+    ///
+    /// Decks and the collection can refer to any printing; allocations are
+    /// counted per card, since any printing of a card will do in a deck.
+    fn card_of(&self, card_ref: &CardRef) -> CardRef {
+        self.cards
+            .printing(card_ref)
+            .map(|x| x.card)
+            .unwrap_or_else(|| card_ref.clone())
+    }
+
+    fn tally<'c>(&self, card_refs: impl IntoIterator<Item = (&'c CardRef, usize)>) -> HashMap<CardRef, usize> {
+        let mut tally = HashMap::new();
+        for (card_ref, count) in card_refs {
+            *tally.entry(self.card_of(card_ref)).or_default() += count;
+        }
+        tally
+    }
+
+    fn owned_tally(&self) -> HashMap<CardRef, usize> {
+        let entries = self.collection.entries();
+        self.tally(entries.iter().map(|x| (&x.card.card, x.card.duplicates)))
+    }
+
+    fn group_tally(&self, group: &CardGroup) -> HashMap<CardRef, usize> {
+        self.tally(
+            group
+                .cards
+                .entries
+                .iter()
+                .filter(|x| x.section.is_played())
+                .map(|x| (&x.card, x.quantity)),
+        )
+    }
+
+    fn allocating_groups(&self) -> Vec<(GroupId, CardGroup)> {
+        self.groups
+            .all()
+            .into_iter()
+            .filter(|(_, group)| group.group_type.allocates_cards())
+            .collect()
+    }
+
+    fn claims(&self) -> Vec<Claim> {
+        self.allocating_groups()
+            .into_iter()
+            .map(|(id, group)| {
+                let tally = self.group_tally(&group);
+                (id, group, tally)
+            })
+            .collect()
+    }
+
+    ///
+    /// The copies of each card which exclusive groups have reserved.
+    /// This is what `available` takes away from the collection.
+    pub fn reserved_tally(&self) -> HashMap<CardRef, usize> {
+        let mut reserved = HashMap::new();
+        for (_, _, tally) in self.claims().into_iter().filter(|x| x.1.exclusive) {
+            for (card, count) in tally {
+                *reserved.entry(card).or_default() += count;
+            }
+        }
+        reserved
+    }
+
+    /// Copies of the card in the collection, in any printing
+    pub fn owned(&self, card: &CardRef) -> usize {
+        let card = self.card_of(card);
+        self.owned_tally().get(&card).copied().unwrap_or_default()
+    }
+
+    ///
+    /// How many copies of the card are still available for a new deck,
+    /// i.e. owned copies minus those reserved by exclusive decks.
+    pub fn available(&self, card: &CardRef) -> usize {
+        let card = self.card_of(card);
+        let reserved = self.reserved_tally().get(&card).copied().unwrap_or_default();
+
+        self.owned(&card).saturating_sub(reserved)
+    }
+
+    ///
+    /// Whether group `id` can have `wanted` copies of the card, given what the other groups
+    /// use: its copies plus the other exclusive groups' copies plus the most that any
+    /// one non-exclusive group uses has to fit in the collection.
+    fn conflict(
+        card: &CardRef,
+        id: GroupId,
+        wanted: usize,
+        owned: &HashMap<CardRef, usize>,
+        claims: &[Claim],
+    ) -> Option<AllocationConflict> {
+        let owned = owned.get(card).copied().unwrap_or_default();
+
+        let mut exclusive_claims = 0;
+        let mut shared_claims = 0;
+        let mut claimed_by = Vec::new();
+
+        for (other_id, other, tally) in claims.iter().filter(|x| x.0 != id) {
+            let Some(count) = tally.get(card).copied() else {
+                continue;
+            };
+
+            if other.exclusive {
+                exclusive_claims += count;
+            } else {
+                shared_claims = shared_claims.max(count);
+            }
+            claimed_by.push((*other_id, other.name.clone(), count));
+        }
+
+        (wanted + exclusive_claims + shared_claims > owned).then(|| AllocationConflict {
+            card: card.clone(),
+            owned,
+            wanted,
+            claimed_by,
+        })
+    }
+
+    ///
+    /// Everything which would stop the group from being made exclusive: each of its cards which
+    /// another deck or cube also uses, even if there are enough copies for both, and each
+    /// that the collection doesn't have enough copies of.
+    pub fn exclusivity_conflicts(&self, id: GroupId) -> Result<Vec<AllocationConflict>, GroupDbErr> {
+        let group = self.groups.get(id).ok_or(GroupDbErr::NoSuchGroup)?;
+        let owned = self.owned_tally();
+        let claims = self.claims();
+
+        Ok(self
+            .group_tally(&group)
+            .into_iter()
+            .filter_map(|(card, wanted)| {
+                let claimed_by = claims
+                    .iter()
+                    .filter(|x| x.0 != id)
+                    .filter_map(|(other_id, other, tally)| {
+                        Some((*other_id, other.name.clone(), tally.get(&card).copied()?))
+                    })
+                    .collect::<Vec<_>>();
+                let owned = owned.get(&card).copied().unwrap_or_default();
+
+                (!claimed_by.is_empty() || wanted > owned).then(|| AllocationConflict {
+                    card,
+                    owned,
+                    wanted,
+                    claimed_by,
+                })
+            })
+            .collect())
+    }
+
+    ///
+    /// Making a group exclusive fails if any other group uses the same cards, or if the
+    /// collection can't cover it. Making it non-exclusive always works.
+    pub fn set_exclusive(&self, id: GroupId, exclusive: bool) -> Result<(), AllocationErr> {
+        if exclusive {
+            let conflicts = self.exclusivity_conflicts(id)?;
+            if !conflicts.is_empty() {
+                return Err(AllocationErr::Conflicts(conflicts));
+            }
+        }
+
+        self.groups.set_exclusive(id, exclusive)?;
+        Ok(())
+    }
+
+    ///
+    /// Replaces the group's cards, unless that would take copies which an exclusive
+    /// group has reserved. Only cards the group would use more of are checked,
+    /// so a collection that's already too small doesn't stop unrelated edits.
+    pub fn set_cards(&self, id: GroupId, cards: Deck) -> Result<(), AllocationErr> {
+        let mut group = self.groups.get(id).ok_or(GroupDbErr::NoSuchGroup)?;
+
+        if group.group_type.allocates_cards() {
+            let before = self.group_tally(&group);
+            group.cards = cards.clone();
+            let after = self.group_tally(&group);
+
+            let mut claims = self.claims();
+            if let Some(claim) = claims.iter_mut().find(|x| x.0 == id) {
+                claim.1 = group;
+                claim.2 = after.clone();
+            }
+            let owned = self.owned_tally();
+
+            let conflicts = after
+                .iter()
+                .filter(|(card, count)| before.get(*card).copied().unwrap_or_default() < **count)
+                .flat_map(|(card, _)| {
+                    claims
+                        .iter()
+                        .filter(|x| x.1.exclusive)
+                        .filter_map(|(other_id, _, tally)| {
+                            let wanted = tally.get(card).copied()?;
+                            Self::conflict(card, *other_id, wanted, &owned, &claims)
+                        })
+                })
+                .collect::<Vec<_>>();
+            if !conflicts.is_empty() {
+                return Err(AllocationErr::Conflicts(conflicts));
+            }
+        }
+
+        self.groups.set_cards(id, cards)?;
+        Ok(())
+    }
+
+    /// Adds cards to the group, with the same check as `set_cards`
+    pub fn add_card(
+        &self,
+        id: GroupId,
+        section: DeckSection,
+        card: CardRef,
+        quantity: usize,
+    ) -> Result<(), AllocationErr> {
+        let mut cards = self.groups.get(id).ok_or(GroupDbErr::NoSuchGroup)?.cards;
+        cards.add(section, card, quantity);
+        self.set_cards(id, cards)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_allocation() {
+    use crate::{data_model::card::PhysicalCard, dbs::folders::test_db_path};
+
+    //nothing's in the card DB, so every ref is its own card
+    let cards = AllCardsDb::open(test_db_path("allocation-cards")).unwrap();
+    let collection = CollectionDb::open(test_db_path("allocation-collection")).unwrap();
+    let groups = GroupDb::open(test_db_path("allocation-groups")).unwrap();
+    let allocator = Allocator::new(&cards, &collection, &groups);
+
+    let bolt = CardRef {
+        set: "TST".to_string(),
+        collector_number: 1.into(),
+        printing: None,
+    };
+    collection.add(PhysicalCard::new(bolt.clone(), 4));
+
+    let group = |variety, name: &str, count, exclusive| {
+        let mut group = CardGroup::new(variety, name);
+        group.cards.add(DeckSection::Main, bolt.clone(), count);
+        group.exclusive = exclusive;
+        groups.create(group).unwrap()
+    };
+    let burn = group(CardGroupVariety::Deck, "Burn", 3, true);
+    let prowess = group(CardGroupVariety::Deck, "Prowess", 1, false);
+    //binders don't use up cards, even exclusive ones
+    group(CardGroupVariety::Binder, "Binder", 4, true);
+
+    assert_eq!(allocator.owned(&bolt), 4);
+    assert_eq!(allocator.available(&bolt), 1);
+    assert_eq!(allocator.reserved_tally().get(&bolt), Some(&3));
+
+    //the shared deck fits alongside the exclusive one, but only just
+    allocator.add_card(prowess, DeckSection::Main, bolt.clone(), 1).unwrap_err();
+    assert_eq!(groups.get(prowess).unwrap().cards.count_of(&bolt), 1);
+
+    //a deck that's already over can still be edited in other ways
+    groups.add_card(prowess, DeckSection::Main, bolt.clone(), 1).unwrap();
+    let mut fewer = groups.get(prowess).unwrap().cards;
+    fewer.remove(DeckSection::Main, &bolt, 1);
+    fewer.add(DeckSection::Maybe, bolt.clone(), 3);
+    allocator.set_cards(prowess, fewer).unwrap();
+
+    //there are enough copies for both, but the cards are shared, so prowess can't reserve them
+    assert_eq!(
+        allocator.exclusivity_conflicts(prowess),
+        Ok(vec![AllocationConflict {
+            card: bolt.clone(),
+            owned: 4,
+            wanted: 1,
+            claimed_by: vec![(burn, "Burn".to_string(), 3)],
+        }])
+    );
+
+    groups.add_card(prowess, DeckSection::Main, bolt.clone(), 1).unwrap();
+    let conflicts = vec![AllocationConflict {
+        card: bolt.clone(),
+        owned: 4,
+        wanted: 2,
+        claimed_by: vec![(burn, "Burn".to_string(), 3)],
+    }];
+    assert_eq!(allocator.exclusivity_conflicts(prowess), Ok(conflicts.clone()));
+    assert_eq!(
+        allocator.set_exclusive(prowess, true),
+        Err(AllocationErr::Conflicts(conflicts))
+    );
+    assert!(!groups.get(prowess).unwrap().exclusive);
+
+    allocator.set_exclusive(burn, false).unwrap();
+    assert_eq!(allocator.available(&bolt), 4);
+
+    //every other deck using the card is reported, not just the exclusive ones
+    let storm = group(CardGroupVariety::Deck, "Storm", 1, false);
+    match allocator.set_exclusive(storm, true) {
+        Err(AllocationErr::Conflicts(conflicts)) => {
+            let mut claimed_by = conflicts[0].claimed_by.iter().map(|x| x.0).collect::<Vec<_>>();
+            claimed_by.sort();
+            assert_eq!(claimed_by, [burn, prowess]);
+        }
+        other => panic!("expected conflicts, got {other:?}"),
+    }
+    assert!(!groups.get(storm).unwrap().exclusive);
+
+    //one deck on its own reserves its cards
+    let solo_groups = GroupDb::open(test_db_path("allocation-groups-alone")).unwrap();
+    let alone = Allocator::new(&cards, &collection, &solo_groups);
+    let mut solo = CardGroup::new(CardGroupVariety::Deck, "Solo");
+    solo.cards.add(DeckSection::Main, bolt.clone(), 4);
+    let solo = solo_groups.create(solo).unwrap();
+    alone.set_exclusive(solo, true).unwrap();
+    assert_eq!(alone.available(&bolt), 0);
+}
@@ -0,0 +1,407 @@
+use std::{
+    collections::BTreeMap,
+    io::ErrorKind,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    data_model::{
+        card::CardRef,
+        deck::{Deck, DeckSection},
+        group::{CardGroup, CardGroupVariety, GroupId},
+    },
+    dbs::{
+        folders::save_directory,
+        journal::{Journal, JournalEdit},
+        paged_db::layout_paged_db,
+    },
+};
+
+pub mod allocation;
+pub mod versions;
+
+///
+/// The state of one group after a change; `None` means that it was deleted.
+/// Like the collection, groups are stored as a log which is replayed when opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GroupLogRecord {
+    pub seq: u128,
+    pub id: GroupId,
+    pub group: Option<CardGroup>,
+}
+
+layout_paged_db! {
+    GroupLog {
+        changes: u128 => GroupLogRecord: 1 dimensional,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupDbErr {
+    NoSuchGroup,
+    /// Only folders can have groups inside of them
+    ParentNotFolder,
+    /// Moving a folder into itself or one of its own subfolders
+    WouldNestInItself,
+    /// Adding cards to a folder
+    NotACardGroup,
+    /// Looking up a deck version that was never saved
+    NoSuchVersion,
+}
+
+#[derive(Default)]
+struct GroupState {
+    groups: BTreeMap<GroupId, CardGroup>,
+    next_id: usize,
+    next_seq: u128,
+}
+
+///
+/// Decks, binders, boxes, and everything else that cards can be sorted into.
+/// Changes here aren't checked against exclusive groups' reserved cards;
+/// `allocation::Allocator` does that, for callers that have the collection to check against.
+pub struct GroupDb {
+    log: GroupLog,
+    state: RwLock<GroupState>,
+    journal: Option<Arc<Journal>>,
+}
+
+impl GroupDb {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let log = GroupLog::open_trees(path)?;
+
+        let mut records = log
+            .changes
+            .find_items_in_box(&(u128::MIN..=u128::MAX))
+            .collect::<Vec<_>>();
+        records.sort_by_key(|x| x.seq);
+
+        let mut state = GroupState::default();
+        for record in records {
+            state.next_seq = record.seq + 1;
+            state.next_id = state.next_id.max(record.id.0 + 1);
+            match record.group {
+                Some(group) => state.groups.insert(record.id, group),
+                None => state.groups.remove(&record.id),
+            };
+        }
+
+        Ok(Self {
+            log,
+            state: RwLock::new(state),
+            journal: None,
+        })
+    }
+
+    /// Records every change from now on in the journal, so it can be undone
+    pub fn with_journal(mut self, journal: Arc<Journal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    pub fn open_in_save_directory() -> std::io::Result<Self> {
+        let dir = save_directory().ok_or(ErrorKind::NotFound)?;
+        Self::open(dir.join("groups.db"))
+    }
+
+    fn record(&self, state: &mut GroupState, id: GroupId, group: Option<CardGroup>) -> JournalEdit {
+        let seq = state.next_seq;
+        state.next_seq += 1;
+
+        let before = match &group {
+            Some(group) => state.groups.insert(id, group.clone()),
+            None => state.groups.remove(&id),
+        };
+        let after = group.clone();
+        self.log
+            .changes
+            .insert(seq, GroupLogRecord { seq, id, group });
+
+        JournalEdit::Group { id, before, after }
+    }
+
+    //only called once the state's lock is released, since undoing goes the other way around
+    fn journal(&self, edits: Vec<JournalEdit>) {
+        if let Some(journal) = &self.journal {
+            journal.step(edits);
+        }
+    }
+
+    ///
+    /// Puts a group into the given state without journaling it; this is what undo and redo use.
+    pub(crate) fn restore(&self, id: GroupId, group: Option<CardGroup>) {
+        let mut state = self.state.write().unwrap();
+        state.next_id = state.next_id.max(id.0 + 1);
+        self.record(&mut state, id, group);
+    }
+
+    fn check_parent(
+        state: &GroupState,
+        id: Option<GroupId>,
+        parent: Option<GroupId>,
+    ) -> Result<(), GroupDbErr> {
+        let Some(parent) = parent else {
+            return Ok(());
+        };
+
+        let parent_group = state.groups.get(&parent).ok_or(GroupDbErr::NoSuchGroup)?;
+        if parent_group.group_type != CardGroupVariety::Folder {
+            return Err(GroupDbErr::ParentNotFolder);
+        }
+
+        let mut ancestor = Some(parent);
+        while let Some(a) = ancestor {
+            if Some(a) == id {
+                return Err(GroupDbErr::WouldNestInItself);
+            }
+            ancestor = state.groups.get(&a).and_then(|x| x.parent);
+        }
+        Ok(())
+    }
+
+    pub fn create(&self, group: CardGroup) -> Result<GroupId, GroupDbErr> {
+        let mut state = self.state.write().unwrap();
+        Self::check_parent(&state, None, group.parent)?;
+
+        let id = GroupId(state.next_id);
+        state.next_id += 1;
+        let edit = self.record(&mut state, id, Some(group));
+        drop(state);
+
+        self.journal(vec![edit]);
+        Ok(id)
+    }
+
+    pub fn get(&self, id: GroupId) -> Option<CardGroup> {
+        self.state.read().unwrap().groups.get(&id).cloned()
+    }
+
+    pub fn all(&self) -> Vec<(GroupId, CardGroup)> {
+        self.state
+            .read()
+            .unwrap()
+            .groups
+            .iter()
+            .map(|(id, group)| (*id, group.clone()))
+            .collect()
+    }
+
+    /// The groups directly inside `parent`, or at the top level for `None`
+    pub fn children(&self, parent: Option<GroupId>) -> Vec<(GroupId, CardGroup)> {
+        self.state
+            .read()
+            .unwrap()
+            .groups
+            .iter()
+            .filter(|(_, group)| group.parent == parent)
+            .map(|(id, group)| (*id, group.clone()))
+            .collect()
+    }
+
+    ///
+    /// Names aren't unique, so this is the first (oldest) group with the name, ignoring case.
+    pub fn find_by_name(
+        &self,
+        group_type: Option<CardGroupVariety>,
+        name: &str,
+    ) -> Option<(GroupId, CardGroup)> {
+        self.state
+            .read()
+            .unwrap()
+            .groups
+            .iter()
+            .filter(|(_, group)| group_type.is_none_or(|t| group.group_type == t))
+            .find(|(_, group)| group.name.eq_ignore_ascii_case(name))
+            .map(|(id, group)| (*id, group.clone()))
+    }
+
+    fn update(
+        &self,
+        id: GroupId,
+        change: impl FnOnce(&GroupState, &mut CardGroup) -> Result<(), GroupDbErr>,
+    ) -> Result<(), GroupDbErr> {
+        let mut state = self.state.write().unwrap();
+
+        let mut group = state
+            .groups
+            .get(&id)
+            .cloned()
+            .ok_or(GroupDbErr::NoSuchGroup)?;
+        change(&state, &mut group)?;
+
+        let edit = self.record(&mut state, id, Some(group));
+        drop(state);
+
+        self.journal(vec![edit]);
+        Ok(())
+    }
+
+    pub fn rename(&self, id: GroupId, name: impl Into<String>) -> Result<(), GroupDbErr> {
+        let name = name.into();
+        self.update(id, |_, group| {
+            group.name = name;
+            Ok(())
+        })
+    }
+
+    /// Moves the group into a folder, or to the top level for `None`
+    pub fn move_to(&self, id: GroupId, parent: Option<GroupId>) -> Result<(), GroupDbErr> {
+        self.update(id, |state, group| {
+            Self::check_parent(state, Some(id), parent)?;
+            group.parent = parent;
+            Ok(())
+        })
+    }
+
+    /// Unchecked; see `allocation::Allocator::set_cards`
+    pub fn set_cards(&self, id: GroupId, cards: Deck) -> Result<(), GroupDbErr> {
+        self.update(id, |_, group| {
+            if !group.group_type.holds_cards() {
+                return Err(GroupDbErr::NotACardGroup);
+            }
+            group.cards = cards;
+            Ok(())
+        })
+    }
+
+    /// Unchecked; see `allocation::Allocator::add_card`
+    pub fn add_card(
+        &self,
+        id: GroupId,
+        section: DeckSection,
+        card: CardRef,
+        quantity: usize,
+    ) -> Result<(), GroupDbErr> {
+        self.update(id, |_, group| {
+            if !group.group_type.holds_cards() {
+                return Err(GroupDbErr::NotACardGroup);
+            }
+            group.cards.add(section, card, quantity);
+            Ok(())
+        })
+    }
+
+    /// Returns how many copies were actually removed
+    pub fn remove_card(
+        &self,
+        id: GroupId,
+        section: DeckSection,
+        card: &CardRef,
+        quantity: usize,
+    ) -> Result<usize, GroupDbErr> {
+        let mut removed = 0;
+        self.update(id, |_, group| {
+            removed = group.cards.remove(section, card, quantity);
+            Ok(())
+        })?;
+        Ok(removed)
+    }
+
+    ///
+    /// This doesn't check whether the collection can cover the group;
+    /// use `allocation::Allocator::set_exclusive` for that.
+    pub fn set_exclusive(&self, id: GroupId, exclusive: bool) -> Result<(), GroupDbErr> {
+        self.update(id, |_, group| {
+            group.exclusive = exclusive;
+            Ok(())
+        })
+    }
+
+    ///
+    /// Anything inside of a deleted folder is moved up into the folder's parent.
+    pub fn delete(&self, id: GroupId) -> Result<(), GroupDbErr> {
+        let mut state = self.state.write().unwrap();
+
+        let deleted = state.groups.get(&id).cloned().ok_or(GroupDbErr::NoSuchGroup)?;
+
+        let children = state
+            .groups
+            .iter()
+            .filter(|(_, group)| group.parent == Some(id))
+            .map(|(child_id, group)| (*child_id, group.clone()))
+            .collect::<Vec<_>>();
+
+        let mut edits = Vec::new();
+        for (child_id, mut child) in children {
+            child.parent = deleted.parent;
+            edits.push(self.record(&mut state, child_id, Some(child)));
+        }
+        edits.push(self.record(&mut state, id, None));
+        drop(state);
+
+        self.journal(edits);
+        Ok(())
+    }
+
+    pub fn condense(&mut self) {
+        self.log.condense();
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_groups() {
+    let path = crate::dbs::folders::test_db_path("groups");
+
+    let (top, middle, deck) = {
+        let groups = GroupDb::open(&path).unwrap();
+
+        let top = groups.create(CardGroup::new(CardGroupVariety::Folder, "Decks")).unwrap();
+        let middle = groups
+            .create(CardGroup {
+                parent: Some(top),
+                ..CardGroup::new(CardGroupVariety::Folder, "Modern")
+            })
+            .unwrap();
+        let deck = groups.create(CardGroup::new(CardGroupVariety::Deck, "Burn")).unwrap();
+
+        groups.rename(deck, "Boros Burn").unwrap();
+        assert_eq!(groups.find_by_name(Some(CardGroupVariety::Deck), "boros burn").map(|x| x.0), Some(deck));
+        assert_eq!(groups.find_by_name(None, "Burn"), None);
+
+        //only folders hold groups, and a folder can't go inside of itself
+        groups.move_to(deck, Some(middle)).unwrap();
+        assert_eq!(groups.move_to(top, Some(deck)), Err(GroupDbErr::ParentNotFolder));
+        assert_eq!(groups.move_to(top, Some(middle)), Err(GroupDbErr::WouldNestInItself));
+        assert_eq!(groups.move_to(top, Some(top)), Err(GroupDbErr::WouldNestInItself));
+        assert_eq!(
+            groups.create(CardGroup {
+                parent: Some(deck),
+                ..CardGroup::new(CardGroupVariety::Binder, "Binder")
+            }),
+            Err(GroupDbErr::ParentNotFolder)
+        );
+        assert_eq!(
+            groups.add_card(middle, DeckSection::Main, CardRef {
+                set: "TST".to_string(),
+                collector_number: 1.into(),
+                printing: None,
+            }, 1),
+            Err(GroupDbErr::NotACardGroup)
+        );
+
+        //deleting a folder moves what was in it up a level
+        groups.delete(middle).unwrap();
+        assert_eq!(groups.get(middle), None);
+        assert_eq!(groups.get(deck).unwrap().parent, Some(top));
+        assert_eq!(groups.delete(middle), Err(GroupDbErr::NoSuchGroup));
+
+        (top, middle, deck)
+    };
+
+    //everything's replayed from the log
+    let groups = GroupDb::open(&path).unwrap();
+    assert_eq!(groups.get(middle), None);
+    let reopened = groups.get(deck).unwrap();
+    assert_eq!(reopened.name, "Boros Burn");
+    assert_eq!(reopened.parent, Some(top));
+    assert_eq!(
+        groups.children(Some(top)).into_iter().map(|x| x.0).collect::<Vec<_>>(),
+        [deck]
+    );
+
+    //ids aren't reused after reopening
+    let binder = groups.create(CardGroup::new(CardGroupVariety::Binder, "Binder")).unwrap();
+    assert!(binder.0 > deck.0);
+}
@@ -0,0 +1,242 @@
+use std::{collections::BTreeMap, io::ErrorKind, path::Path, sync::Mutex, time::SystemTime};
+
+use crate::{
+    data_model::{
+        date::Date,
+        deck::{Deck, DeckDiff},
+        group::GroupId,
+    },
+    dbs::{folders::save_directory, paged_db::layout_paged_db},
+};
+
+use super::{GroupDb, GroupDbErr};
+
+///
+/// A saved revision of a deck. These are never changed once they're written;
+/// restoring an old version saves it again as the newest one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckVersion {
+    pub deck: GroupId,
+    /// Counts up from 1 for each deck
+    pub version: usize,
+    /// Seconds since the Unix epoch
+    pub saved_at: usize,
+    /// Empty if no note was given
+    pub note: String,
+    pub cards: Deck,
+}
+
+impl DeckVersion {
+    pub fn date(&self) -> Date {
+        Date::from_days_since_epoch(self.saved_at / 86400)
+    }
+}
+
+layout_paged_db! {
+    DeckVersionLog {
+        versions: u128 => DeckVersion: 1 dimensional,
+    }
+}
+
+//the deck goes in the high bits, so one deck's versions are a contiguous, ordered range
+fn version_key(deck: GroupId, version: usize) -> u128 {
+    ((deck.0 as u128) << 64) | version as u128
+}
+
+pub struct DeckVersionDb {
+    log: DeckVersionLog,
+    /// The newest version of each deck, filled in as decks are looked at
+    latest: Mutex<BTreeMap<GroupId, usize>>,
+}
+
+impl DeckVersionDb {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Ok(Self {
+            log: DeckVersionLog::open_trees(path)?,
+            latest: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn open_in_save_directory() -> std::io::Result<Self> {
+        let dir = save_directory().ok_or(ErrorKind::NotFound)?;
+        Self::open(dir.join("deck_versions.db"))
+    }
+
+    /// Oldest first
+    pub fn versions(&self, deck: GroupId) -> Vec<DeckVersion> {
+        let mut versions = self
+            .log
+            .versions
+            .find_items_in_box(&(version_key(deck, 0)..=version_key(deck, u64::MAX as usize)))
+            .collect::<Vec<_>>();
+        versions.sort_by_key(|x| x.version);
+        versions
+    }
+
+    pub fn version(&self, deck: GroupId, version: usize) -> Option<DeckVersion> {
+        self.log.versions.get_owned(&version_key(deck, version))
+    }
+
+    pub fn latest(&self, deck: GroupId) -> Option<DeckVersion> {
+        let latest = self.latest_number(&mut self.latest.lock().unwrap(), deck);
+        self.version(deck, latest)
+    }
+
+    fn latest_number(&self, latest: &mut BTreeMap<GroupId, usize>, deck: GroupId) -> usize {
+        *latest
+            .entry(deck)
+            .or_insert_with(|| self.versions(deck).last().map_or(0, |x| x.version))
+    }
+
+    ///
+    /// Saves the deck's current cards as a new version.
+    /// Nothing is saved if they're the same as the latest version's, and that version is returned instead.
+    pub fn snapshot(&self, deck: GroupId, cards: &Deck, note: &str) -> DeckVersion {
+        let mut latest = self.latest.lock().unwrap();
+        let number = self.latest_number(&mut latest, deck);
+
+        if let Some(previous) = self.version(deck, number) {
+            if previous.cards == *cards {
+                return previous;
+            }
+        }
+
+        let saved_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|x| x.as_secs() as usize)
+            .unwrap_or_default();
+
+        let version = DeckVersion {
+            deck,
+            version: number + 1,
+            saved_at,
+            note: note.to_string(),
+            cards: cards.clone(),
+        };
+        self.log
+            .versions
+            .insert(version_key(deck, version.version), version.clone());
+        latest.insert(deck, version.version);
+
+        version
+    }
+
+    ///
+    /// Saves the group's current cards as a new version.
+    pub fn snapshot_group(
+        &self,
+        groups: &GroupDb,
+        deck: GroupId,
+        note: &str,
+    ) -> Result<DeckVersion, GroupDbErr> {
+        let group = groups.get(deck).ok_or(GroupDbErr::NoSuchGroup)?;
+        Ok(self.snapshot(deck, &group.cards, note))
+    }
+
+    /// What changed going from one version to another
+    pub fn diff(&self, deck: GroupId, from: usize, to: usize) -> Result<DeckDiff, GroupDbErr> {
+        let from = self.version(deck, from).ok_or(GroupDbErr::NoSuchVersion)?;
+        let to = self.version(deck, to).ok_or(GroupDbErr::NoSuchVersion)?;
+        Ok(DeckDiff::between(&from.cards, &to.cards))
+    }
+
+    ///
+    /// Puts an old version's cards back into the deck, and records that as a new version.
+    pub fn restore(
+        &self,
+        groups: &GroupDb,
+        deck: GroupId,
+        version: usize,
+    ) -> Result<DeckVersion, GroupDbErr> {
+        let old = self.version(deck, version).ok_or(GroupDbErr::NoSuchVersion)?;
+        groups.set_cards(deck, old.cards.clone())?;
+        Ok(self.snapshot(deck, &old.cards, &format!("Restored version {version}")))
+    }
+
+    pub fn condense(&mut self) {
+        self.log.condense();
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_deck_versions() {
+    use crate::data_model::{
+        card::CardRef,
+        deck::{DeckSection, SectionChange},
+        group::{CardGroup, CardGroupVariety},
+    };
+
+    let card = |n: usize| CardRef {
+        set: "TST".to_string(),
+        collector_number: n.into(),
+        printing: None,
+    };
+    let groups = GroupDb::open(crate::dbs::folders::test_db_path("deck_versions_groups")).unwrap();
+    let path = crate::dbs::folders::test_db_path("deck_versions");
+
+    let deck = groups.create(CardGroup::new(CardGroupVariety::Deck, "Burn")).unwrap();
+    let other = groups.create(CardGroup::new(CardGroupVariety::Deck, "Prowess")).unwrap();
+
+    let mut first = Deck::new();
+    first.add(DeckSection::Main, card(1), 4);
+    first.add(DeckSection::Main, card(2), 2);
+    let mut second = first.clone();
+    second.remove(DeckSection::Main, &card(2), 2);
+    second.add(DeckSection::Main, card(3), 3);
+
+    {
+        let versions = DeckVersionDb::open(&path).unwrap();
+        assert_eq!(versions.latest(deck), None);
+
+        //numbers count up per deck
+        assert_eq!(versions.snapshot(deck, &first, "First try").version, 1);
+        assert_eq!(versions.snapshot(other, &second, "").version, 1);
+        assert_eq!(versions.snapshot(deck, &second, "").version, 2);
+
+        //the same cards again don't make a new version
+        let unchanged = versions.snapshot(deck, &second, "Again");
+        assert_eq!(unchanged.version, 2);
+        assert_eq!(unchanged.note, "");
+        assert_eq!(versions.versions(deck).len(), 2);
+
+        assert_eq!(
+            versions.diff(deck, 1, 2).unwrap().changes,
+            [
+                SectionChange {
+                    section: DeckSection::Main,
+                    card: card(2),
+                    before: 2,
+                    after: 0,
+                },
+                SectionChange {
+                    section: DeckSection::Main,
+                    card: card(3),
+                    before: 0,
+                    after: 3,
+                },
+            ]
+        );
+        assert!(versions.diff(deck, 2, 2).unwrap().is_empty());
+        assert_eq!(versions.diff(deck, 1, 3), Err(GroupDbErr::NoSuchVersion));
+
+        //restoring puts the cards back and saves them as the newest version
+        let restored = versions.restore(&groups, deck, 1).unwrap();
+        assert_eq!(restored.version, 3);
+        assert_eq!(restored.cards, first);
+        assert_eq!(restored.note, "Restored version 1");
+        assert_eq!(groups.get(deck).unwrap().cards, first);
+        assert_eq!(versions.restore(&groups, deck, 4), Err(GroupDbErr::NoSuchVersion));
+    }
+
+    //everything's still there after reopening, and numbering carries on
+    let versions = DeckVersionDb::open(&path).unwrap();
+    assert_eq!(
+        versions.versions(deck).into_iter().map(|x| x.version).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert_eq!(versions.version(deck, 1).unwrap().note, "First try");
+    assert_eq!(versions.latest(deck).unwrap().cards, first);
+    assert_eq!(versions.latest(other).unwrap().cards, second);
+    assert_eq!(versions.snapshot(deck, &second, "").version, 4);
+}
@@ -6,10 +6,75 @@ use minimal_storage::{
     serialize_min::{DeserializeFromMinimal, ReadExtReadOne, SerializeMinimal},
 };
 
-use crate::data_model::card::{
-    Card, CardDynamicNumber, Color, ColorCombination, ManaCost, ManaSymbol, ManaVariable, Rarity,
-    Supertype,
+use crate::data_model::{
+    card::{
+        Card, CardDynamicNumber, CardRef, Color, ColorCombination, Condition, Finish, ForeignName,
+        ForeignNames, Language, ManaCost, ManaSymbol, ManaVariable, PhysicalCard, Printing, Rarity,
+        Supertype,
+    },
+    collection::CollectionEntryId,
+    deck::{Deck, DeckEntry, DeckSection},
+    group::{CardGroup, CardGroupVariety, GroupId},
+    legality::{CardLegalities, Legality},
+    tag::TagTarget,
+    date::Date,
+    price::{Currency, PricePoint},
+    ruling::{CardRulings, OracleChange, Ruling},
+    set::SetInfo,
 };
+use crate::dbs::allcards::cardref_key::{card_ref_to_index, index_to_card_ref};
+use crate::dbs::collection::{CollectionChange, CollectionLogRecord, CollectionSnapshot};
+use crate::dbs::journal::{JournalAction, JournalEdit, JournalRecord};
+use crate::dbs::saving_decks::GroupLogRecord;
+use crate::dbs::saving_decks::versions::DeckVersion;
+use crate::dbs::tags::TagLogRecord;
+
+///
+/// Implements `MinimalSerdeFast` by deferring to the type's `SerializeMinimal`
+/// and `DeserializeFromMinimal` impls. The variable-length records we store as
+/// DB values don't have a faster representation than their minimal one.
+macro_rules! fast_serde_via_minimal {
+    ( $($t:ty),* $(,)? ) => {
+        $(
+            impl MinimalSerdeFast for $t {
+                fn fast_minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+                    &'a self,
+                    write_to: &mut W,
+                    external_data: <Self as SerializeMinimal>::ExternalData<'s>,
+                ) -> std::io::Result<()> {
+                    self.minimally_serialize(write_to, external_data)
+                }
+
+                fn fast_deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+                    from: &'a mut R,
+                    external_data: <Self as DeserializeFromMinimal>::ExternalData<'d>,
+                ) -> Result<Self, std::io::Error> {
+                    Self::deserialize_minimal(from, external_data)
+                }
+
+                fn fast_seek_after<R: std::io::Read>(from: &mut R) -> std::io::Result<()> {
+                    Self::deserialize_minimal(from, ())?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+fast_serde_via_minimal!(
+    SetInfo,
+    Printing,
+    PricePoint,
+    CardRulings,
+    ForeignNames,
+    CardLegalities,
+    CollectionLogRecord,
+    CollectionSnapshot,
+    GroupLogRecord,
+    DeckVersion,
+    TagLogRecord,
+    JournalRecord,
+);
 
 impl MinimalSerdeFast for Card {
     fn fast_minimally_serialize<'a, 's: 'a, W: std::io::Write>(
@@ -482,3 +547,1003 @@ fn byte_to_mana_symbol(ms: u8) -> std::io::Result<ManaSymbol> {
         split_color,
     })
 }
+
+impl SerializeMinimal for Date {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.year.minimally_serialize(write_to, ())?;
+        self.month.minimally_serialize(write_to, ())?;
+        self.day.minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for Date {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Ok(Date {
+            year: usize::deserialize_minimal(from, ())?,
+            month: u8::deserialize_minimal(from, ())?,
+            day: u8::deserialize_minimal(from, ())?,
+        })
+    }
+}
+
+//optional strings are stored as empty strings, since none of
+//the optional fields we store are ever legitimately empty.
+fn write_optional_str(s: &Option<String>, write_to: &mut impl std::io::Write) -> std::io::Result<()> {
+    s.as_deref()
+        .unwrap_or("")
+        .minimally_serialize(write_to, 0u8.into())
+}
+
+fn read_optional_str(from: &mut impl std::io::Read) -> std::io::Result<Option<String>> {
+    let s = String::deserialize_minimal(from, None)?;
+    Ok((!s.is_empty()).then_some(s))
+}
+
+impl SerializeMinimal for SetInfo {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.code.as_str().minimally_serialize(write_to, 0u8.into())?;
+        self.name.as_str().minimally_serialize(write_to, 0u8.into())?;
+        self.release_date.minimally_serialize(write_to, ())?;
+        self.set_type
+            .as_str()
+            .minimally_serialize(write_to, 0u8.into())?;
+        write_optional_str(&self.block, write_to)?;
+        write_optional_str(&self.parent_code, write_to)?;
+        self.card_count.minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for SetInfo {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Ok(SetInfo {
+            code: String::deserialize_minimal(from, None)?,
+            name: String::deserialize_minimal(from, None)?,
+            release_date: Date::deserialize_minimal(from, ())?,
+            set_type: String::deserialize_minimal(from, None)?,
+            block: read_optional_str(from)?,
+            parent_code: read_optional_str(from)?,
+            card_count: usize::deserialize_minimal(from, ())?,
+        })
+    }
+}
+
+//card refs are already squeezed into a u128 to key the DB, so reuse that.
+impl SerializeMinimal for CardRef {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        card_ref_to_index(self).minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for CardRef {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        u128::deserialize_minimal(from, ()).map(index_to_card_ref)
+    }
+}
+
+impl SerializeMinimal for Printing {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.card_ref.minimally_serialize(write_to, ())?;
+        self.card.minimally_serialize(write_to, ())?;
+        self.artist.as_str().minimally_serialize(write_to, 0u8.into())?;
+        self.flavor_text
+            .as_str()
+            .minimally_serialize(write_to, 0u8.into())?;
+        write_optional_str(&self.watermark, write_to)?;
+        self.frame.as_str().minimally_serialize(write_to, 0u8.into())?;
+        self.border.as_str().minimally_serialize(write_to, 0u8.into())
+    }
+}
+
+impl DeserializeFromMinimal for Printing {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Ok(Printing {
+            card_ref: CardRef::deserialize_minimal(from, ())?,
+            card: CardRef::deserialize_minimal(from, ())?,
+            artist: String::deserialize_minimal(from, None)?,
+            flavor_text: String::deserialize_minimal(from, None)?,
+            watermark: read_optional_str(from)?,
+            frame: String::deserialize_minimal(from, None)?,
+            border: String::deserialize_minimal(from, None)?,
+        })
+    }
+}
+
+impl SerializeMinimal for Finish {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        let b: u8 = match self {
+            Finish::Nonfoil => 0,
+            Finish::Foil => 1,
+            Finish::Etched => 2,
+        };
+        b.minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for Finish {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        match from.read_one()? {
+            0 => Ok(Finish::Nonfoil),
+            1 => Ok(Finish::Foil),
+            2 => Ok(Finish::Etched),
+            _ => Err(ErrorKind::InvalidData.into()),
+        }
+    }
+}
+
+impl SerializeMinimal for Currency {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        let b: u8 = match self {
+            Currency::Usd => 0,
+            Currency::Eur => 1,
+            Currency::Tix => 2,
+        };
+        b.minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for Currency {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        match from.read_one()? {
+            0 => Ok(Currency::Usd),
+            1 => Ok(Currency::Eur),
+            2 => Ok(Currency::Tix),
+            _ => Err(ErrorKind::InvalidData.into()),
+        }
+    }
+}
+
+impl SerializeMinimal for PricePoint {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.card_ref.minimally_serialize(write_to, ())?;
+        self.finish.minimally_serialize(write_to, ())?;
+        self.currency.minimally_serialize(write_to, ())?;
+        self.date.minimally_serialize(write_to, ())?;
+        self.cents.minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for PricePoint {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Ok(PricePoint {
+            card_ref: CardRef::deserialize_minimal(from, ())?,
+            finish: Finish::deserialize_minimal(from, ())?,
+            currency: Currency::deserialize_minimal(from, ())?,
+            date: Date::deserialize_minimal(from, ())?,
+            cents: usize::deserialize_minimal(from, ())?,
+        })
+    }
+}
+
+impl SerializeMinimal for CardRulings {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.rulings.len().minimally_serialize(write_to, ())?;
+        for ruling in self.rulings.iter() {
+            ruling.date.minimally_serialize(write_to, ())?;
+            ruling.text.as_str().minimally_serialize(write_to, 0u8.into())?;
+        }
+
+        self.oracle_changes.len().minimally_serialize(write_to, ())?;
+        for change in self.oracle_changes.iter() {
+            change.replaced.minimally_serialize(write_to, ())?;
+            change
+                .old_text
+                .as_str()
+                .minimally_serialize(write_to, 0u8.into())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DeserializeFromMinimal for CardRulings {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let len = usize::deserialize_minimal(from, ())?;
+        let mut rulings = Vec::with_capacity(len);
+        for _ in 0..len {
+            rulings.push(Ruling {
+                date: Date::deserialize_minimal(from, ())?,
+                text: String::deserialize_minimal(from, None)?,
+            });
+        }
+
+        let len = usize::deserialize_minimal(from, ())?;
+        let mut oracle_changes = Vec::with_capacity(len);
+        for _ in 0..len {
+            oracle_changes.push(OracleChange {
+                replaced: Date::deserialize_minimal(from, ())?,
+                old_text: String::deserialize_minimal(from, None)?,
+            });
+        }
+
+        Ok(CardRulings {
+            rulings,
+            oracle_changes,
+        })
+    }
+}
+
+impl SerializeMinimal for Language {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.as_u8().minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for Language {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Language::from_u8(from.read_one()?).ok_or(ErrorKind::InvalidData.into())
+    }
+}
+
+impl SerializeMinimal for ForeignNames {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.0.len().minimally_serialize(write_to, ())?;
+        for foreign in self.0.iter() {
+            foreign.language.minimally_serialize(write_to, ())?;
+            foreign.name.as_str().minimally_serialize(write_to, 0u8.into())?;
+        }
+        Ok(())
+    }
+}
+
+impl DeserializeFromMinimal for ForeignNames {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let len = usize::deserialize_minimal(from, ())?;
+        let mut names = Vec::with_capacity(len);
+        for _ in 0..len {
+            names.push(ForeignName {
+                language: Language::deserialize_minimal(from, ())?,
+                name: String::deserialize_minimal(from, None)?,
+            });
+        }
+        Ok(ForeignNames(names))
+    }
+}
+
+//two bits per format, in `GameFormat::ALL` order
+impl SerializeMinimal for CardLegalities {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        let packed = self.0.iter().enumerate().fold(0usize, |packed, (i, legality)| {
+            let bits = Legality::ALL.iter().position(|x| x == legality).unwrap();
+            packed | (bits << (i * 2))
+        });
+        packed.minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for CardLegalities {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let packed = usize::deserialize_minimal(from, ())?;
+        let mut legalities = CardLegalities::default();
+        for (i, legality) in legalities.0.iter_mut().enumerate() {
+            *legality = Legality::ALL[(packed >> (i * 2)) & 0b11];
+        }
+        Ok(legalities)
+    }
+}
+
+impl SerializeMinimal for Condition {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        (*self as u8).minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for Condition {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Condition::ALL
+            .get(from.read_one()? as usize)
+            .copied()
+            .ok_or(ErrorKind::InvalidData.into())
+    }
+}
+
+impl SerializeMinimal for PhysicalCard {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.card.minimally_serialize(write_to, ())?;
+        self.duplicates.minimally_serialize(write_to, ())?;
+        self.finish.minimally_serialize(write_to, ())?;
+        self.condition.minimally_serialize(write_to, ())?;
+        self.language.minimally_serialize(write_to, ())?;
+        match &self.acquired {
+            Some(date) => {
+                1u8.minimally_serialize(write_to, ())?;
+                date.minimally_serialize(write_to, ())
+            }
+            None => 0u8.minimally_serialize(write_to, ()),
+        }
+    }
+}
+
+impl DeserializeFromMinimal for PhysicalCard {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Ok(PhysicalCard {
+            card: CardRef::deserialize_minimal(from, ())?,
+            duplicates: usize::deserialize_minimal(from, ())?,
+            finish: Finish::deserialize_minimal(from, ())?,
+            condition: Condition::deserialize_minimal(from, ())?,
+            language: Language::deserialize_minimal(from, ())?,
+            acquired: match from.read_one()? {
+                0 => None,
+                1 => Some(Date::deserialize_minimal(from, ())?),
+                _ => return Err(ErrorKind::InvalidData.into()),
+            },
+        })
+    }
+}
+
+impl SerializeMinimal for CollectionLogRecord {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.seq.minimally_serialize(write_to, ())?;
+        match &self.change {
+            CollectionChange::Add { id, card } => {
+                0u8.minimally_serialize(write_to, ())?;
+                id.0.minimally_serialize(write_to, ())?;
+                card.minimally_serialize(write_to, ())
+            }
+            CollectionChange::Remove { id } => {
+                1u8.minimally_serialize(write_to, ())?;
+                id.0.minimally_serialize(write_to, ())
+            }
+            CollectionChange::SetQuantity { id, quantity } => {
+                2u8.minimally_serialize(write_to, ())?;
+                id.0.minimally_serialize(write_to, ())?;
+                quantity.minimally_serialize(write_to, ())
+            }
+        }
+    }
+}
+
+impl DeserializeFromMinimal for CollectionLogRecord {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let seq = u128::deserialize_minimal(from, ())?;
+        let tag = from.read_one()?;
+        let id = CollectionEntryId(usize::deserialize_minimal(from, ())?);
+
+        let change = match tag {
+            0 => CollectionChange::Add {
+                id,
+                card: PhysicalCard::deserialize_minimal(from, ())?,
+            },
+            1 => CollectionChange::Remove { id },
+            2 => CollectionChange::SetQuantity {
+                id,
+                quantity: usize::deserialize_minimal(from, ())?,
+            },
+            _ => return Err(ErrorKind::InvalidData.into()),
+        };
+
+        Ok(CollectionLogRecord { seq, change })
+    }
+}
+
+impl SerializeMinimal for CollectionSnapshot {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.seq.minimally_serialize(write_to, ())?;
+        self.next_id.minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for CollectionSnapshot {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Ok(CollectionSnapshot {
+            seq: u128::deserialize_minimal(from, ())?,
+            next_id: usize::deserialize_minimal(from, ())?,
+        })
+    }
+}
+
+impl SerializeMinimal for JournalEdit {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        match self {
+            JournalEdit::Collection { id, before, after } => {
+                0u8.minimally_serialize(write_to, ())?;
+                id.0.minimally_serialize(write_to, ())?;
+                for card in [before, after] {
+                    match card {
+                        Some(card) => {
+                            1u8.minimally_serialize(write_to, ())?;
+                            card.minimally_serialize(write_to, ())?;
+                        }
+                        None => 0u8.minimally_serialize(write_to, ())?,
+                    }
+                }
+            }
+            JournalEdit::Group { id, before, after } => {
+                1u8.minimally_serialize(write_to, ())?;
+                id.0.minimally_serialize(write_to, ())?;
+                for group in [before, after] {
+                    match group {
+                        Some(group) => {
+                            1u8.minimally_serialize(write_to, ())?;
+                            group.minimally_serialize(write_to, ())?;
+                        }
+                        None => 0u8.minimally_serialize(write_to, ())?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DeserializeFromMinimal for JournalEdit {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let tag = from.read_one()?;
+        let id = usize::deserialize_minimal(from, ())?;
+
+        match tag {
+            0 => {
+                let mut read_card = || -> std::io::Result<Option<PhysicalCard>> {
+                    match from.read_one()? {
+                        0 => Ok(None),
+                        1 => Ok(Some(PhysicalCard::deserialize_minimal(from, ())?)),
+                        _ => Err(ErrorKind::InvalidData.into()),
+                    }
+                };
+                Ok(JournalEdit::Collection {
+                    id: CollectionEntryId(id),
+                    before: read_card()?,
+                    after: read_card()?,
+                })
+            }
+            1 => {
+                let mut read_group = || -> std::io::Result<Option<CardGroup>> {
+                    match from.read_one()? {
+                        0 => Ok(None),
+                        1 => Ok(Some(CardGroup::deserialize_minimal(from, ())?)),
+                        _ => Err(ErrorKind::InvalidData.into()),
+                    }
+                };
+                Ok(JournalEdit::Group {
+                    id: GroupId(id),
+                    before: read_group()?,
+                    after: read_group()?,
+                })
+            }
+            _ => Err(ErrorKind::InvalidData.into()),
+        }
+    }
+}
+
+impl SerializeMinimal for JournalRecord {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.seq.minimally_serialize(write_to, ())?;
+        self.at.minimally_serialize(write_to, ())?;
+        match &self.action {
+            JournalAction::Step(edits) => {
+                0u8.minimally_serialize(write_to, ())?;
+                edits.len().minimally_serialize(write_to, ())?;
+                for edit in edits.iter() {
+                    edit.minimally_serialize(write_to, ())?;
+                }
+                Ok(())
+            }
+            JournalAction::Undo(seq) => {
+                1u8.minimally_serialize(write_to, ())?;
+                seq.minimally_serialize(write_to, ())
+            }
+            JournalAction::Redo(seq) => {
+                2u8.minimally_serialize(write_to, ())?;
+                seq.minimally_serialize(write_to, ())
+            }
+        }
+    }
+}
+
+impl DeserializeFromMinimal for JournalRecord {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let seq = u128::deserialize_minimal(from, ())?;
+        let at = usize::deserialize_minimal(from, ())?;
+
+        let action = match from.read_one()? {
+            0 => {
+                let len = usize::deserialize_minimal(from, ())?;
+                let mut edits = Vec::with_capacity(len);
+                for _ in 0..len {
+                    edits.push(JournalEdit::deserialize_minimal(from, ())?);
+                }
+                JournalAction::Step(edits)
+            }
+            1 => JournalAction::Undo(u128::deserialize_minimal(from, ())?),
+            2 => JournalAction::Redo(u128::deserialize_minimal(from, ())?),
+            _ => return Err(ErrorKind::InvalidData.into()),
+        };
+
+        Ok(JournalRecord { seq, at, action })
+    }
+}
+
+impl SerializeMinimal for Deck {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.entries.len().minimally_serialize(write_to, ())?;
+        for entry in self.entries.iter() {
+            let section = DeckSection::ALL
+                .iter()
+                .position(|x| *x == entry.section)
+                .unwrap() as u8;
+            section.minimally_serialize(write_to, ())?;
+            entry.card.minimally_serialize(write_to, ())?;
+            entry.quantity.minimally_serialize(write_to, ())?;
+        }
+        Ok(())
+    }
+}
+
+impl DeserializeFromMinimal for Deck {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let len = usize::deserialize_minimal(from, ())?;
+        let mut entries = Vec::with_capacity(len);
+        for _ in 0..len {
+            entries.push(DeckEntry {
+                section: *DeckSection::ALL
+                    .get(from.read_one()? as usize)
+                    .ok_or(ErrorKind::InvalidData)?,
+                card: CardRef::deserialize_minimal(from, ())?,
+                quantity: usize::deserialize_minimal(from, ())?,
+            });
+        }
+        Ok(Deck { entries })
+    }
+}
+
+impl SerializeMinimal for CardGroup {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        let group_type = CardGroupVariety::ALL
+            .iter()
+            .position(|x| *x == self.group_type)
+            .unwrap() as u8;
+        group_type.minimally_serialize(write_to, ())?;
+        self.name.as_str().minimally_serialize(write_to, 0u8.into())?;
+
+        self.cards.minimally_serialize(write_to, ())?;
+
+        (self.exclusive as u8).minimally_serialize(write_to, ())?;
+
+        //IDs are stored +1 so that 0 can mean "no parent"
+        self.parent
+            .map_or(0, |x| x.0 + 1)
+            .minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for CardGroup {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let group_type = *CardGroupVariety::ALL
+            .get(from.read_one()? as usize)
+            .ok_or(ErrorKind::InvalidData)?;
+        let name = String::deserialize_minimal(from, None)?;
+
+        let cards = Deck::deserialize_minimal(from, ())?;
+
+        let exclusive = from.read_one()? != 0;
+        let parent = usize::deserialize_minimal(from, ())?
+            .checked_sub(1)
+            .map(GroupId);
+
+        Ok(CardGroup {
+            group_type,
+            name,
+            cards,
+            exclusive,
+            parent,
+        })
+    }
+}
+
+impl SerializeMinimal for DeckVersion {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.deck.0.minimally_serialize(write_to, ())?;
+        self.version.minimally_serialize(write_to, ())?;
+        self.saved_at.minimally_serialize(write_to, ())?;
+        self.note.as_str().minimally_serialize(write_to, 0u8.into())?;
+        self.cards.minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for DeckVersion {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Ok(DeckVersion {
+            deck: GroupId(usize::deserialize_minimal(from, ())?),
+            version: usize::deserialize_minimal(from, ())?,
+            saved_at: usize::deserialize_minimal(from, ())?,
+            note: String::deserialize_minimal(from, None)?,
+            cards: Deck::deserialize_minimal(from, ())?,
+        })
+    }
+}
+
+impl SerializeMinimal for GroupLogRecord {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.seq.minimally_serialize(write_to, ())?;
+        self.id.0.minimally_serialize(write_to, ())?;
+        match &self.group {
+            Some(group) => {
+                1u8.minimally_serialize(write_to, ())?;
+                group.minimally_serialize(write_to, ())
+            }
+            None => 0u8.minimally_serialize(write_to, ()),
+        }
+    }
+}
+
+impl DeserializeFromMinimal for GroupLogRecord {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        Ok(GroupLogRecord {
+            seq: u128::deserialize_minimal(from, ())?,
+            id: GroupId(usize::deserialize_minimal(from, ())?),
+            group: match from.read_one()? {
+                0 => None,
+                1 => Some(CardGroup::deserialize_minimal(from, ())?),
+                _ => return Err(ErrorKind::InvalidData.into()),
+            },
+        })
+    }
+}
+
+impl SerializeMinimal for TagLogRecord {
+    type ExternalData<'s> = ();
+
+    fn minimally_serialize<'a, 's: 'a, W: std::io::Write>(
+        &'a self,
+        write_to: &mut W,
+        _: Self::ExternalData<'s>,
+    ) -> std::io::Result<()> {
+        self.seq.minimally_serialize(write_to, ())?;
+        match &self.target {
+            TagTarget::Card(card_ref) => {
+                0u8.minimally_serialize(write_to, ())?;
+                card_ref.minimally_serialize(write_to, ())?;
+            }
+            TagTarget::Copy(id) => {
+                1u8.minimally_serialize(write_to, ())?;
+                id.0.minimally_serialize(write_to, ())?;
+            }
+        }
+        self.tag.as_str().minimally_serialize(write_to, 0u8.into())?;
+        (self.tagged as u8).minimally_serialize(write_to, ())
+    }
+}
+
+impl DeserializeFromMinimal for TagLogRecord {
+    type ExternalData<'d> = ();
+
+    fn deserialize_minimal<'a, 'd: 'a, R: std::io::Read>(
+        from: &'a mut R,
+        _: Self::ExternalData<'d>,
+    ) -> Result<Self, std::io::Error> {
+        let seq = u128::deserialize_minimal(from, ())?;
+        let target = match from.read_one()? {
+            0 => TagTarget::Card(CardRef::deserialize_minimal(from, ())?),
+            1 => TagTarget::Copy(CollectionEntryId(usize::deserialize_minimal(from, ())?)),
+            _ => return Err(ErrorKind::InvalidData.into()),
+        };
+
+        Ok(TagLogRecord {
+            seq,
+            target,
+            tag: String::deserialize_minimal(from, None)?,
+            tagged: from.read_one()? != 0,
+        })
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_collection_log_serde() {
+    fn roundtrip(original: CollectionLogRecord) {
+        let mut buf = Vec::new();
+        original.minimally_serialize(&mut buf, ()).unwrap();
+        let roundtripped = CollectionLogRecord::deserialize_minimal(&mut &buf[..], ()).unwrap();
+
+        assert_eq!(original, roundtripped)
+    }
+
+    let mut card = PhysicalCard::new(
+        CardRef {
+            set: "LEA".to_string(),
+            collector_number: 161.into(),
+            printing: None,
+        },
+        3,
+    );
+    let id = CollectionEntryId(7);
+
+    roundtrip(CollectionLogRecord {
+        seq: 0,
+        change: CollectionChange::Add {
+            id,
+            card: card.clone(),
+        },
+    });
+
+    card.finish = Finish::Foil;
+    card.language = Language::Japanese;
+    card.acquired = Some(Date::first_of_year(2024));
+    roundtrip(CollectionLogRecord {
+        seq: 1,
+        change: CollectionChange::Add { id, card },
+    });
+    roundtrip(CollectionLogRecord {
+        seq: 2,
+        change: CollectionChange::SetQuantity { id, quantity: 12 },
+    });
+    roundtrip(CollectionLogRecord {
+        seq: 3,
+        change: CollectionChange::Remove { id },
+    });
+
+    let snapshot = CollectionSnapshot { seq: 4, next_id: 8 };
+    let mut buf = Vec::new();
+    snapshot.minimally_serialize(&mut buf, ()).unwrap();
+    assert_eq!(CollectionSnapshot::deserialize_minimal(&mut &buf[..], ()).unwrap(), snapshot);
+}
+
+#[cfg(test)]
+#[test]
+fn test_journal_serde() {
+    let card_ref = CardRef {
+        set: "MH3".to_string(),
+        collector_number: 108.into(),
+        printing: None,
+    };
+    let mut deck = CardGroup::new(CardGroupVariety::Deck, "Scam");
+    deck.cards.add(DeckSection::Main, card_ref.clone(), 4);
+
+    let original = JournalRecord {
+        seq: 5,
+        at: 1_760_000_000,
+        action: JournalAction::Step(vec![
+            JournalEdit::Collection {
+                id: CollectionEntryId(2),
+                before: None,
+                after: Some(PhysicalCard::new(card_ref, 4)),
+            },
+            JournalEdit::Group {
+                id: GroupId(0),
+                before: Some(CardGroup::new(CardGroupVariety::Deck, "Scam")),
+                after: Some(deck),
+            },
+        ]),
+    };
+
+    let mut buf = Vec::new();
+    original.minimally_serialize(&mut buf, ()).unwrap();
+    let roundtripped = JournalRecord::deserialize_minimal(&mut &buf[..], ()).unwrap();
+    assert_eq!(original, roundtripped);
+    for action in [JournalAction::Undo(5), JournalAction::Redo(5)] {
+        let original = JournalRecord { seq: 6, at: 1_760_000_100, action };
+        let mut buf = Vec::new();
+        original.minimally_serialize(&mut buf, ()).unwrap();
+        assert_eq!(JournalRecord::deserialize_minimal(&mut &buf[..], ()).unwrap(), original);
+    }
+}
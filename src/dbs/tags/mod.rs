@@ -0,0 +1,246 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    io::ErrorKind,
+    path::Path,
+    sync::RwLock,
+};
+
+use crate::{
+    data_model::tag::{TagTarget, normalize_tag},
+    dbs::{folders::save_directory, paged_db::layout_paged_db},
+};
+
+///
+/// A tag being put on or taken off of something. Like the collection, tags
+/// are stored as a log which is replayed when opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TagLogRecord {
+    pub seq: u128,
+    pub target: TagTarget,
+    pub tag: String,
+    pub tagged: bool,
+}
+
+layout_paged_db! {
+    TagLog {
+        changes: u128 => TagLogRecord: 1 dimensional,
+    }
+}
+
+#[derive(Default)]
+struct TagState {
+    targets_by_tag: BTreeMap<String, HashSet<TagTarget>>,
+    tags_by_target: HashMap<TagTarget, BTreeSet<String>>,
+    next_seq: u128,
+}
+
+impl TagState {
+    fn apply(&mut self, target: &TagTarget, tag: &str, tagged: bool) {
+        if tagged {
+            self.targets_by_tag
+                .entry(tag.to_string())
+                .or_default()
+                .insert(target.clone());
+            self.tags_by_target
+                .entry(target.clone())
+                .or_default()
+                .insert(tag.to_string());
+        } else {
+            if let Some(targets) = self.targets_by_tag.get_mut(tag) {
+                targets.remove(target);
+                if targets.is_empty() {
+                    self.targets_by_tag.remove(tag);
+                }
+            }
+            if let Some(tags) = self.tags_by_target.get_mut(target) {
+                tags.remove(tag);
+            }
+        }
+    }
+
+    fn has(&self, target: &TagTarget, tag: &str) -> bool {
+        self.tags_by_target
+            .get(target)
+            .is_some_and(|x| x.contains(tag))
+    }
+}
+
+///
+/// User-defined tags on cards and on copies in the collection.
+pub struct TagDb {
+    log: TagLog,
+    state: RwLock<TagState>,
+}
+
+impl TagDb {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let log = TagLog::open_trees(path)?;
+
+        let mut records = log
+            .changes
+            .find_items_in_box(&(u128::MIN..=u128::MAX))
+            .collect::<Vec<_>>();
+        records.sort_by_key(|x| x.seq);
+
+        let mut state = TagState::default();
+        for record in records.iter() {
+            state.apply(&record.target, &record.tag, record.tagged);
+            state.next_seq = record.seq + 1;
+        }
+
+        Ok(Self {
+            log,
+            state: RwLock::new(state),
+        })
+    }
+
+    pub fn open_in_save_directory() -> std::io::Result<Self> {
+        let dir = save_directory().ok_or(ErrorKind::NotFound)?;
+        Self::open(dir.join("tags.db"))
+    }
+
+    fn record(&self, state: &mut TagState, target: TagTarget, tag: String, tagged: bool) {
+        let seq = state.next_seq;
+        state.next_seq += 1;
+
+        state.apply(&target, &tag, tagged);
+        self.log.changes.insert(
+            seq,
+            TagLogRecord {
+                seq,
+                target,
+                tag,
+                tagged,
+            },
+        );
+    }
+
+    ///
+    /// Tags every target, returning how many didn't already have the tag.
+    /// This is how tagging a single card works too.
+    pub fn tag_all(&self, targets: impl IntoIterator<Item = TagTarget>, tag: &str) -> usize {
+        let Some(tag) = normalize_tag(tag) else {
+            return 0;
+        };
+        let mut state = self.state.write().unwrap();
+
+        let mut newly_tagged = 0;
+        for target in targets {
+            if !state.has(&target, &tag) {
+                self.record(&mut state, target, tag.clone(), true);
+                newly_tagged += 1;
+            }
+        }
+        newly_tagged
+    }
+
+    pub fn tag(&self, target: TagTarget, tag: &str) -> bool {
+        self.tag_all([target], tag) > 0
+    }
+
+    /// Returns how many targets had the tag
+    pub fn untag_all(&self, targets: impl IntoIterator<Item = TagTarget>, tag: &str) -> usize {
+        let Some(tag) = normalize_tag(tag) else {
+            return 0;
+        };
+        let mut state = self.state.write().unwrap();
+
+        let mut untagged = 0;
+        for target in targets {
+            if state.has(&target, &tag) {
+                self.record(&mut state, target, tag.clone(), false);
+                untagged += 1;
+            }
+        }
+        untagged
+    }
+
+    pub fn untag(&self, target: TagTarget, tag: &str) -> bool {
+        self.untag_all([target], tag) > 0
+    }
+
+    /// Alphabetical
+    pub fn tags_of(&self, target: &TagTarget) -> Vec<String> {
+        self.state
+            .read()
+            .unwrap()
+            .tags_by_target
+            .get(target)
+            .map(|x| x.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn has_tag(&self, target: &TagTarget, tag: &str) -> bool {
+        let Some(tag) = normalize_tag(tag) else {
+            return false;
+        };
+        self.state.read().unwrap().has(target, &tag)
+    }
+
+    pub fn tagged_with(&self, tag: &str) -> Vec<TagTarget> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Vec::new();
+        };
+        self.state
+            .read()
+            .unwrap()
+            .targets_by_tag
+            .get(&tag)
+            .map(|x| x.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Every tag in use, alphabetically, with how many things have it
+    pub fn all_tags(&self) -> Vec<(String, usize)> {
+        self.state
+            .read()
+            .unwrap()
+            .targets_by_tag
+            .iter()
+            .map(|(tag, targets)| (tag.clone(), targets.len()))
+            .collect()
+    }
+
+    pub fn condense(&mut self) {
+        self.log.condense();
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_tag_db() {
+    use crate::data_model::{card::CardRef, collection::CollectionEntryId};
+
+    let path = crate::dbs::folders::test_db_path("tags");
+    let bolt = TagTarget::Card(CardRef {
+        set: "TST".to_string(),
+        collector_number: 1.into(),
+        printing: None,
+    });
+    let copy = TagTarget::Copy(CollectionEntryId(3));
+
+    {
+        let tags = TagDb::open(&path).unwrap();
+        assert!(tags.tag(bolt.clone(), "Burn"));
+        //already tagged, whatever the case or spacing
+        assert!(!tags.tag(bolt.clone(), " burn "));
+        assert_eq!(tags.tag_all([bolt.clone(), copy.clone()], "Card Draw"), 1);
+        assert!(!tags.tag(copy.clone(), "   "));
+
+        assert!(tags.untag(bolt.clone(), "card draw"));
+        assert!(!tags.untag(bolt.clone(), "card-draw"));
+        assert!(tags.tag(copy.clone(), "trade"));
+        assert_eq!(tags.untag_all([bolt.clone(), copy.clone()], "TRADE"), 1);
+    }
+
+    //the log is replayed, untagging included
+    let tags = TagDb::open(&path).unwrap();
+    assert_eq!(tags.tags_of(&bolt), ["burn"]);
+    assert_eq!(tags.tags_of(&copy), ["card-draw"]);
+    assert!(tags.has_tag(&bolt, "BURN"));
+    assert!(tags.has_tag(&copy, "card draw"));
+    assert!(!tags.has_tag(&copy, "trade"));
+    assert_eq!(tags.tagged_with("Card  Draw"), [copy.clone()]);
+    assert!(tags.tagged_with("trade").is_empty());
+    assert_eq!(tags.all_tags(), [("burn".to_string(), 1), ("card-draw".to_string(), 1)]);
+}
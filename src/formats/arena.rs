@@ -0,0 +1,170 @@
+//MTG Arena's export format: section headers, then `4 Lightning Bolt (STA) 42` lines.
+//Exports can start with an `About` section holding the deck's name, which we skip.
+
+use std::io::Write;
+
+use crate::{
+    data_model::{
+        card::CardRef,
+        deck::{Deck, DeckSection},
+    },
+    dbs::allcards::AllCardsDb,
+};
+
+use super::{DeckLine, FormatErr, ImportedDeck, check_in_db, resolve_lines};
+
+///
+/// Takes the printing off the end of a card: `Lightning Bolt (STA) 42` or `Lightning Bolt (STA)`.
+/// Returns the name, and the set and collector number if they were there.
+pub(crate) fn split_printing(text: &str) -> (&str, Option<String>, Option<String>) {
+    let Some(open) = text.rfind(" (") else {
+        return (text, None, None);
+    };
+    let Some((set, number)) = text[open + 2..].split_once(')') else {
+        return (text, None, None);
+    };
+
+    let number = number.trim();
+    if set.is_empty()
+        || !set.chars().all(|c| c.is_ascii_alphanumeric())
+        || number.contains(char::is_whitespace)
+    {
+        return (text, None, None);
+    }
+
+    (
+        text[..open].trim(),
+        Some(set.to_string()),
+        (!number.is_empty()).then(|| number.to_string()),
+    )
+}
+
+///
+/// Splits `4 Lightning Bolt (STA) 42` into its parts.
+/// The set and collector number are optional, as is an `x` after the quantity.
+fn parse_card_line(line_number: usize, section: DeckSection, text: &str) -> Option<DeckLine> {
+    let (quantity, rest) = text.split_once(char::is_whitespace)?;
+    let quantity = quantity.strip_suffix(['x', 'X']).unwrap_or(quantity);
+    let quantity = quantity.parse().ok()?;
+
+    let (name, set, collector_number) = split_printing(rest.trim());
+    let mut line = DeckLine::new(line_number, section, quantity, name);
+    line.set = set;
+    line.collector_number = collector_number;
+
+    Some(line)
+}
+
+pub fn parse_arena(text: &str) -> Result<Vec<DeckLine>, FormatErr> {
+    let mut lines = Vec::new();
+    let mut section = DeckSection::Main;
+    let mut in_about = false;
+    //older exports have no headers, just a blank line before the sideboard
+    let mut saw_header = false;
+
+    for (i, text_line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let text_line = text_line.trim();
+
+        if text_line.is_empty() {
+            if !saw_header && !lines.is_empty() && section == DeckSection::Main {
+                section = DeckSection::Side;
+            }
+            continue;
+        }
+
+        if text_line.eq_ignore_ascii_case("about") {
+            in_about = true;
+            continue;
+        }
+        if let Some(header) = DeckSection::from_header(text_line) {
+            section = header;
+            saw_header = true;
+            in_about = false;
+            continue;
+        }
+        if in_about {
+            continue;
+        }
+
+        let line = parse_card_line(line_number, section, text_line).ok_or_else(|| {
+            FormatErr::new(line_number, format!("expected `<quantity> <name>`, found `{text_line}`"))
+        })?;
+        lines.push(line);
+    }
+
+    Ok(lines)
+}
+
+pub fn import_arena(db: &AllCardsDb, text: &str) -> Result<ImportedDeck, FormatErr> {
+    Ok(resolve_lines(db, parse_arena(text)?))
+}
+
+/// One line of an Arena decklist
+pub fn format_arena_line(quantity: usize, name: &str, card: &CardRef) -> String {
+    format!("{} {} ({}) {}", quantity, name, card.set, card.collector_number)
+}
+
+///
+/// Every section gets a header (even the maybeboard, which Arena itself doesn't have),
+/// so the deck reads back in exactly as it was.
+pub fn write_arena<W: Write>(db: &AllCardsDb, deck: &Deck, w: &mut W) -> std::io::Result<()> {
+    check_in_db(db, &deck.entries)?;
+    let mut first = true;
+
+    for section in DeckSection::ALL {
+        if deck.section(section).next().is_none() {
+            continue;
+        }
+        if !first {
+            writeln!(w)?;
+        }
+        first = false;
+
+        writeln!(w, "{}", section.name())?;
+        for entry in deck.section(section) {
+            let Some(card) = db.card_by_ref(&entry.card) else {
+                continue;
+            };
+            writeln!(w, "{}", format_arena_line(entry.quantity, &card.name, &entry.card))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_arena() {
+    let text = "About\nName Boros Burn\n\nCommander\n1 Zada, Hedron Grinder (BFZ) 162\n\nCompanion\n1 Lurrus of the Dream-Den (IKO) 226\n\nDeck\n4 Lightning Bolt (STA) 42\n4x Boros Charm\n\nSideboard\n2 Fire // Ice (MH2) 290\n";
+    let lines = parse_arena(text).unwrap();
+
+    let mut zada = DeckLine::new(5, DeckSection::Commander, 1, "Zada, Hedron Grinder");
+    zada.set = Some("BFZ".to_string());
+    zada.collector_number = Some("162".to_string());
+    assert_eq!(lines[0], zada);
+
+    assert_eq!(lines[1].section, DeckSection::Companion);
+    assert_eq!(lines[2].name, "Lightning Bolt");
+    assert_eq!(lines[2].collector_number.as_deref(), Some("42"));
+    assert_eq!(lines[3], DeckLine::new(12, DeckSection::Main, 4, "Boros Charm"));
+    assert_eq!(lines[4].section, DeckSection::Side);
+    assert_eq!(lines[4].name, "Fire // Ice");
+    assert_eq!(lines.len(), 5);
+
+    //no headers: the blank line starts the sideboard
+    let lines = parse_arena("4 Lightning Bolt\n\n3 Smash to Smithereens\n").unwrap();
+    assert_eq!(lines[1].section, DeckSection::Side);
+
+    let card = CardRef {
+        set: "STA".to_string(),
+        collector_number: 42.into(),
+        printing: None,
+    };
+    let line = format_arena_line(4, "Lightning Bolt", &card);
+    let parsed = parse_card_line(1, DeckSection::Main, &line).unwrap();
+    assert_eq!(parsed.set.as_deref(), Some("STA"));
+    assert_eq!(parsed.collector_number.as_deref(), Some("42"));
+
+    assert!(parse_arena("Deck\nLightning Bolt\n").is_err());
+}
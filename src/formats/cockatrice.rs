@@ -0,0 +1,151 @@
+//Cockatrice's `.cod` decks: XML with a `<zone>` per board, e.g.
+//`<zone name="main"><card number="4" name="Lightning Bolt" setShortName="M10"/></zone>`
+
+use std::io::Write;
+
+use crate::{
+    data_model::deck::{Deck, DeckSection},
+    dbs::allcards::AllCardsDb,
+};
+
+use super::{
+    DeckLine, FormatErr, ImportedDeck, check_in_db, resolve_lines,
+    xml::{self, XmlToken},
+};
+
+///
+/// Cockatrice only has a main and side zone (and a tokens zone, which isn't part of the deck and is skipped).
+pub fn parse_cod(document: &str) -> Result<Vec<DeckLine>, FormatErr> {
+    let mut lines = Vec::new();
+    let mut zone = None;
+
+    for (line, token) in xml::tokenize(document)? {
+        match &token {
+            XmlToken::Open { name, .. } if name == "zone" => {
+                zone = match token.attribute("name") {
+                    Some("main") => Some(DeckSection::Main),
+                    Some("side") => Some(DeckSection::Side),
+                    _ => None,
+                };
+            }
+            XmlToken::Close(name) if name == "zone" => zone = None,
+            XmlToken::Open { name, .. } if name == "card" => {
+                let Some(section) = zone else {
+                    continue;
+                };
+
+                let card_name = token
+                    .attribute("name")
+                    .ok_or_else(|| FormatErr::new(line, "card without a name"))?;
+                let quantity = match token.attribute("number") {
+                    Some(q) => q
+                        .trim()
+                        .parse()
+                        .map_err(|_| FormatErr::new(line, format!("bad number `{q}`")))?,
+                    None => 1,
+                };
+
+                let mut deck_line = DeckLine::new(line, section, quantity, card_name);
+                deck_line.set = token
+                    .attribute("setShortName")
+                    .filter(|x| !x.is_empty())
+                    .map(str::to_string);
+                deck_line.collector_number = token
+                    .attribute("collectorNumber")
+                    .filter(|x| !x.is_empty())
+                    .map(str::to_string);
+                lines.push(deck_line);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(lines)
+}
+
+pub fn import_cod(db: &AllCardsDb, document: &str) -> Result<ImportedDeck, FormatErr> {
+    Ok(resolve_lines(db, parse_cod(document)?))
+}
+
+///
+/// Commanders and companions go in the side zone, the same as Cockatrice players put them.
+/// The maybeboard is left out.
+pub fn write_cod<W: Write>(
+    db: &AllCardsDb,
+    name: &str,
+    deck: &Deck,
+    w: &mut W,
+) -> std::io::Result<()> {
+    check_in_db(db, deck.entries.iter().filter(|x| x.section != DeckSection::Maybe))?;
+    writeln!(w, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(w, "<cockatrice_deck version=\"1\">")?;
+    writeln!(w, "    <deckname>{}</deckname>", xml::escape(name))?;
+    writeln!(w, "    <comments></comments>")?;
+
+    for (zone, sections) in [
+        ("main", &[DeckSection::Main][..]),
+        (
+            "side",
+            &[
+                DeckSection::Commander,
+                DeckSection::Companion,
+                DeckSection::Side,
+            ][..],
+        ),
+    ] {
+        writeln!(w, "    <zone name=\"{zone}\">")?;
+        for entry in deck.entries.iter().filter(|x| sections.contains(&x.section)) {
+            let Some(card) = db.card_by_ref(&entry.card) else {
+                continue;
+            };
+            writeln!(
+                w,
+                "        <card number=\"{}\" name=\"{}\" setShortName=\"{}\" collectorNumber=\"{}\"/>",
+                entry.quantity,
+                xml::escape(&card.name),
+                xml::escape(&entry.card.set),
+                xml::escape(&entry.card.collector_number.to_string())
+            )?;
+        }
+        writeln!(w, "    </zone>")?;
+    }
+
+    writeln!(w, "</cockatrice_deck>")
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_cod() {
+    let document = r#"<?xml version="1.0" encoding="UTF-8"?>
+<cockatrice_deck version="1">
+    <deckname>Burn</deckname>
+    <comments>Goes &amp; goes</comments>
+    <zone name="main">
+        <card number="4" name="Lightning Bolt" setShortName="M10" collectorNumber="146"/>
+        <card number="20" name="Mountain"/>
+    </zone>
+    <zone name="side">
+        <card number="2" name="Smash to Smithereens" setShortName="ORI"/>
+    </zone>
+    <zone name="tokens">
+        <card number="1" name="Goblin"/>
+    </zone>
+</cockatrice_deck>"#;
+
+    let lines = parse_cod(document).unwrap();
+
+    let mut bolt = DeckLine::new(6, DeckSection::Main, 4, "Lightning Bolt");
+    bolt.set = Some("M10".to_string());
+    bolt.collector_number = Some("146".to_string());
+    let mut smash = DeckLine::new(10, DeckSection::Side, 2, "Smash to Smithereens");
+    smash.set = Some("ORI".to_string());
+
+    assert_eq!(
+        lines,
+        [
+            bolt,
+            DeckLine::new(7, DeckSection::Main, 20, "Mountain"),
+            smash
+        ]
+    );
+}
@@ -0,0 +1,119 @@
+//RFC 4180 CSV: commas between fields, and double quotes around any field
+//with a comma, quote, or newline in it (quotes inside are doubled).
+
+use std::io::Write;
+
+use super::FormatErr;
+
+///
+/// A record, and the (1-based) line it starts on.
+pub(crate) type CsvRecord = (usize, Vec<String>);
+
+pub(crate) fn parse_csv(text: &str) -> Result<Vec<CsvRecord>, FormatErr> {
+    let mut records = Vec::new();
+
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    //whether the field was quoted, so `""` is kept as an empty field
+    let mut quoted = false;
+    let mut line = 1;
+    let mut record_line = 1;
+
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                c => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quoted = true;
+            }
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' => {}
+            '\n' => {
+                if !field.is_empty() || quoted || !record.is_empty() {
+                    record.push(std::mem::take(&mut field));
+                    records.push((record_line, std::mem::take(&mut record)));
+                }
+                quoted = false;
+                line += 1;
+                record_line = line;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(FormatErr::new(record_line, "unclosed quote"));
+    }
+    if !field.is_empty() || quoted || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+
+    Ok(records)
+}
+
+pub(crate) fn write_csv_record<W: Write, S: AsRef<str>>(w: &mut W, fields: &[S]) -> std::io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(w, ",")?;
+        }
+
+        let field = field.as_ref();
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(w, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            write!(w, "{field}")?;
+        }
+    }
+    writeln!(w)
+}
+
+#[cfg(test)]
+#[test]
+fn test_csv() {
+    let text = "\u{feff}Count,Name,Edition\r\n4,\"Jace, the Mind Sculptor\",wwk\n1,\"Say \"\"Cheese\"\"\",\"\"\n\n2,\"Line\nBreak\",neo";
+    let records = parse_csv(text).unwrap();
+
+    assert_eq!(
+        records,
+        [
+            (1, vec!["Count".to_string(), "Name".to_string(), "Edition".to_string()]),
+            (2, vec!["4".to_string(), "Jace, the Mind Sculptor".to_string(), "wwk".to_string()]),
+            (3, vec!["1".to_string(), "Say \"Cheese\"".to_string(), "".to_string()]),
+            (5, vec!["2".to_string(), "Line\nBreak".to_string(), "neo".to_string()]),
+        ]
+    );
+
+    let mut written = Vec::new();
+    for (_, record) in records.iter() {
+        write_csv_record(&mut written, record).unwrap();
+    }
+    let reparsed = parse_csv(std::str::from_utf8(&written).unwrap()).unwrap();
+    assert_eq!(
+        reparsed.into_iter().map(|x| x.1).collect::<Vec<_>>(),
+        records.into_iter().map(|x| x.1).collect::<Vec<_>>()
+    );
+
+    assert!(parse_csv("1,\"open").is_err());
+}
@@ -0,0 +1,169 @@
+//MTGO's `.dek` format: an XML `<Deck>` with a `<Cards>` element per card, e.g.
+//`<Cards CatID="12345" Quantity="4" Sideboard="false" Name="Lightning Bolt" />`
+//`CatID` is MTGO's own catalog ID. The all-cards DB has it for printings that are on MTGO,
+//so it picks the printing on import, and is written back out on export. Cards whose `CatID`
+//isn't known are matched by name, which is also what MTGO falls back to.
+
+use std::io::Write;
+
+use crate::{
+    data_model::deck::{Deck, DeckSection},
+    dbs::allcards::AllCardsDb,
+};
+
+use super::{
+    DeckLine, FormatErr, ImportedDeck, check_in_db, resolve_lines,
+    xml::{self, XmlToken},
+};
+
+///
+/// MTGO has no commander or companion sections; those go in the sideboard, the same as MTGO puts them.
+pub fn parse_dek(document: &str) -> Result<Vec<DeckLine>, FormatErr> {
+    let mut lines = Vec::new();
+
+    for (line, token) in xml::tokenize(document)? {
+        let XmlToken::Open { name, .. } = &token else {
+            continue;
+        };
+        if name != "Cards" {
+            continue;
+        }
+
+        let card_name = token
+            .attribute("Name")
+            .ok_or_else(|| FormatErr::new(line, "card without a Name"))?;
+        let quantity = match token.attribute("Quantity") {
+            Some(q) => q
+                .trim()
+                .parse()
+                .map_err(|_| FormatErr::new(line, format!("bad Quantity `{q}`")))?,
+            None => 1,
+        };
+        let section = match token.attribute("Sideboard") {
+            Some(x) if x.eq_ignore_ascii_case("true") => DeckSection::Side,
+            _ => DeckSection::Main,
+        };
+
+        let mut deck_line = DeckLine::new(line, section, quantity, card_name);
+        deck_line.mtgo_id = token.attribute("CatID").and_then(|x| x.trim().parse().ok());
+        lines.push(deck_line);
+    }
+
+    Ok(lines)
+}
+
+pub fn import_dek(db: &AllCardsDb, document: &str) -> Result<ImportedDeck, FormatErr> {
+    Ok(resolve_lines(db, parse_dek(document)?))
+}
+
+///
+/// The maybeboard isn't written, since MTGO has nowhere to put it.
+/// A card whose printing isn't on MTGO gets the `CatID` of another printing that is;
+/// if none are, `CatID` is left out and MTGO goes by the name.
+pub fn write_dek<W: Write>(db: &AllCardsDb, deck: &Deck, w: &mut W) -> std::io::Result<()> {
+    check_in_db(db, deck.entries.iter().filter(|x| x.section != DeckSection::Maybe))?;
+    writeln!(w, "<?xml version=\"1.0\" encoding=\"utf-8\"?>")?;
+    writeln!(
+        w,
+        "<Deck xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">"
+    )?;
+    writeln!(w, "  <NetDeckID>0</NetDeckID>")?;
+    writeln!(w, "  <PreconstructedDeckID>0</PreconstructedDeckID>")?;
+
+    for entry in deck.entries.iter() {
+        let sideboard = match entry.section {
+            DeckSection::Main => false,
+            DeckSection::Commander | DeckSection::Companion | DeckSection::Side => true,
+            DeckSection::Maybe => continue,
+        };
+        let Some(card) = db.card_by_ref(&entry.card) else {
+            continue;
+        };
+        let mtgo_id = db.mtgo_id(&entry.card).or_else(|| {
+            db.printings(&card)
+                .iter()
+                .find_map(|x| db.mtgo_id(&x.card_ref))
+        });
+        let cat_id = mtgo_id.map_or_else(String::new, |x| format!("CatID=\"{x}\" "));
+
+        writeln!(
+            w,
+            "  <Cards {cat_id}Quantity=\"{}\" Sideboard=\"{}\" Name=\"{}\" Annotation=\"0\" />",
+            entry.quantity,
+            sideboard,
+            xml::escape(&card.name)
+        )?;
+    }
+
+    writeln!(w, "</Deck>")
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_dek() {
+    let document = r#"<?xml version="1.0" encoding="utf-8"?>
+<Deck xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <NetDeckID>0</NetDeckID>
+  <PreconstructedDeckID>0</PreconstructedDeckID>
+  <Cards CatID="68013" Quantity="4" Sideboard="false" Name="Lightning Bolt" Annotation="0" />
+  <Cards CatID="52181" Quantity="2" Sideboard="true" Name="Fire // Ice" Annotation="0" />
+</Deck>"#;
+
+    let mut bolt = DeckLine::new(5, DeckSection::Main, 4, "Lightning Bolt");
+    bolt.mtgo_id = Some(68013);
+    let mut fire_ice = DeckLine::new(6, DeckSection::Side, 2, "Fire // Ice");
+    fire_ice.mtgo_id = Some(52181);
+    assert_eq!(parse_dek(document).unwrap(), [bolt, fire_ice]);
+
+    assert!(parse_dek("<Deck><Cards Quantity=\"x\" Name=\"Bolt\" /></Deck>").is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn test_dek_cat_ids() {
+    use crate::{
+        data_model::card::{Card, CardRef, Printing},
+        dbs::allcards::test_all_cards_db,
+    };
+
+    let (db, refs) = test_all_cards_db("dek-cat-ids", vec![Card::named("Lightning Bolt"), Card::named("Opt")]);
+    let old_bolt = CardRef {
+        set: "OLD".to_string(),
+        collector_number: 1.into(),
+        printing: None,
+    };
+    db.add_printing(Printing {
+        card_ref: old_bolt.clone(),
+        card: refs[0].clone(),
+        artist: String::new(),
+        flavor_text: String::new(),
+        watermark: None,
+        frame: "1993".to_string(),
+        border: "white".to_string(),
+    });
+    db.add_mtgo_ids(&old_bolt, Some(68013), Some(68014));
+
+    //the foil ID picks the printing too; Opt's CatID isn't known, so it goes by name
+    let document = r#"<Deck>
+  <Cards CatID="68014" Quantity="4" Sideboard="false" Name="Lightning Bolt" />
+  <Cards CatID="99999" Quantity="1" Sideboard="true" Name="Opt" />
+</Deck>"#;
+    let imported = import_dek(&db, document).unwrap();
+    assert!(imported.unresolved.is_empty());
+    assert_eq!(imported.deck.count_of(&old_bolt), 4);
+    assert_eq!(imported.deck.count_of(&refs[1]), 1);
+
+    let write = |deck: &Deck| {
+        let mut written = Vec::new();
+        write_dek(&db, deck, &mut written).unwrap();
+        String::from_utf8(written).unwrap()
+    };
+    let written = write(&imported.deck);
+    assert!(written.contains(r#"<Cards CatID="68013" Quantity="4" Sideboard="false" Name="Lightning Bolt" Annotation="0" />"#));
+    assert!(written.contains(r#"<Cards Quantity="1" Sideboard="true" Name="Opt" Annotation="0" />"#));
+
+    //a printing that isn't on MTGO gets the ID of one that is
+    let mut deck = Deck::new();
+    deck.add(DeckSection::Main, refs[0].clone(), 2);
+    assert!(write(&deck).contains(r#"<Cards CatID="68013" Quantity="2""#));
+}
@@ -0,0 +1,136 @@
+//Forge's `.dck` decks: INI-style, with `[Main]`, `[Sideboard]` and `[Commander]`
+//sections of `4 Lightning Bolt|M10|1` lines. The last part is Forge's art index, not a collector number.
+
+use std::io::Write;
+
+use crate::{
+    data_model::deck::{Deck, DeckSection},
+    dbs::allcards::AllCardsDb,
+};
+
+use super::{DeckLine, FormatErr, ImportedDeck, check_in_db, resolve_lines};
+
+fn forge_section(header: &str) -> Option<DeckSection> {
+    match header.to_ascii_lowercase().as_str() {
+        "main" => Some(DeckSection::Main),
+        "sideboard" => Some(DeckSection::Side),
+        "commander" => Some(DeckSection::Commander),
+        _ => None,
+    }
+}
+
+///
+/// Sections other than the main deck, sideboard, and commander (e.g. `[Planes]`) are skipped,
+/// as is the `[metadata]` section.
+pub fn parse_forge(text: &str) -> Result<Vec<DeckLine>, FormatErr> {
+    let mut lines = Vec::new();
+    let mut section = None;
+
+    for (i, text_line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let text_line = text_line.trim();
+        if text_line.is_empty() || text_line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = text_line
+            .strip_prefix('[')
+            .and_then(|x| x.strip_suffix(']'))
+        {
+            section = forge_section(header);
+            continue;
+        }
+        let Some(section) = section else {
+            continue;
+        };
+
+        let bad_line = || FormatErr::new(line_number, format!("expected `<count> <name>|<set>`, found `{text_line}`"));
+
+        let (quantity, card) = text_line.split_once(' ').ok_or_else(bad_line)?;
+        let quantity = quantity.parse().map_err(|_| bad_line())?;
+
+        let mut parts = card.split('|');
+        let name = parts.next().unwrap_or_default().trim();
+        if name.is_empty() {
+            return Err(bad_line());
+        }
+
+        let mut line = DeckLine::new(line_number, section, quantity, name);
+        line.set = parts
+            .next()
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(str::to_string);
+        lines.push(line);
+    }
+
+    Ok(lines)
+}
+
+pub fn import_forge(db: &AllCardsDb, text: &str) -> Result<ImportedDeck, FormatErr> {
+    Ok(resolve_lines(db, parse_forge(text)?))
+}
+
+///
+/// Companions go in the sideboard, and the maybeboard is left out; Forge has neither.
+pub fn write_forge<W: Write>(
+    db: &AllCardsDb,
+    name: &str,
+    deck: &Deck,
+    w: &mut W,
+) -> std::io::Result<()> {
+    check_in_db(db, deck.entries.iter().filter(|x| x.section != DeckSection::Maybe))?;
+    writeln!(w, "[metadata]")?;
+    writeln!(w, "Name={name}")?;
+
+    for (header, sections) in [
+        ("Commander", &[DeckSection::Commander][..]),
+        ("Main", &[DeckSection::Main][..]),
+        ("Sideboard", &[DeckSection::Companion, DeckSection::Side][..]),
+    ] {
+        let entries = deck
+            .entries
+            .iter()
+            .filter(|x| sections.contains(&x.section))
+            .collect::<Vec<_>>();
+        if entries.is_empty() && header != "Main" {
+            continue;
+        }
+
+        writeln!(w, "[{header}]")?;
+        for entry in entries {
+            let Some(card) = db.card_by_ref(&entry.card) else {
+                continue;
+            };
+            writeln!(w, "{} {}|{}", entry.quantity, card.name, entry.card.set)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_forge() {
+    let text = "[metadata]\nName=Atraxa Superfriends\n[Commander]\n1 Atraxa, Praetors' Voice|CM2|1\n[Main]\n1 Sol Ring|C21\n30 Plains\n[Sideboard]\n1 Fire // Ice|MH2\n[Planes]\n1 Akoum|PC2\n";
+    let lines = parse_forge(text).unwrap();
+
+    let mut atraxa = DeckLine::new(4, DeckSection::Commander, 1, "Atraxa, Praetors' Voice");
+    atraxa.set = Some("CM2".to_string());
+    let mut sol_ring = DeckLine::new(6, DeckSection::Main, 1, "Sol Ring");
+    sol_ring.set = Some("C21".to_string());
+    let mut fire_ice = DeckLine::new(9, DeckSection::Side, 1, "Fire // Ice");
+    fire_ice.set = Some("MH2".to_string());
+
+    assert_eq!(
+        lines,
+        [
+            atraxa,
+            sol_ring,
+            DeckLine::new(7, DeckSection::Main, 30, "Plains"),
+            fire_ice
+        ]
+    );
+
+    assert!(parse_forge("[Main]\nfour Plains\n").is_err());
+}
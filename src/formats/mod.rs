@@ -0,0 +1,249 @@
+//Reading and writing decklists in the formats other programs use.
+//Each format parses into `DeckLine`s, which are then resolved against the
+//all-cards DB the same way, whichever format they came from.
+
+use std::io::Write;
+
+use crate::{
+    data_model::{
+        card::CardRef,
+        deck::{Deck, DeckEntry, DeckSection},
+    },
+    dbs::allcards::AllCardsDb,
+};
+
+pub mod arena;
+pub mod cockatrice;
+pub mod collection_csv;
+mod csv;
+pub mod dek;
+pub mod forge;
+pub mod mtgjson;
+pub mod reconcile;
+pub mod text;
+mod xml;
+
+///
+/// One card line of a decklist, before its name is looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckLine {
+    /// 1-based, for pointing the user at the line
+    pub line: usize,
+    pub section: DeckSection,
+    pub quantity: usize,
+    pub name: String,
+    /// Set code, if the format gives the printing
+    pub set: Option<String>,
+    pub collector_number: Option<String>,
+    /// MTGO's catalog ID, which `.dek` files give instead of a set
+    pub mtgo_id: Option<usize>,
+}
+
+impl DeckLine {
+    pub fn new(line: usize, section: DeckSection, quantity: usize, name: impl Into<String>) -> Self {
+        Self {
+            line,
+            section,
+            quantity,
+            name: name.into(),
+            set: None,
+            collector_number: None,
+            mtgo_id: None,
+        }
+    }
+}
+
+///
+/// The file couldn't be read as the format at all, as opposed to
+/// having some cards in it that we don't know.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatErr {
+    pub line: usize,
+    pub message: String,
+}
+
+impl FormatErr {
+    pub(crate) fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ImportedDeck {
+    pub deck: Deck,
+    /// Lines whose card couldn't be found; these aren't in `deck`
+    pub unresolved: Vec<DeckLine>,
+}
+
+///
+/// The exact printing, if the line names one which is in the DB and is the card it names.
+fn resolve_printing(db: &AllCardsDb, line: &DeckLine) -> Option<CardRef> {
+    let card_ref = match line.mtgo_id.and_then(|x| db.printing_by_mtgo_id(x)) {
+        Some(printing) => printing.card_ref,
+        None => CardRef {
+            set: line.set.as_ref()?.to_ascii_uppercase(),
+            collector_number: line.collector_number.as_ref()?.parse().ok()?,
+            printing: None,
+        },
+    };
+
+    let card = db.card_by_ref(&card_ref)?;
+    (card.name == line.name).then_some(card_ref)
+}
+
+pub(crate) fn resolve_line(db: &AllCardsDb, line: &DeckLine) -> Option<CardRef> {
+    if let Some(card_ref) = resolve_printing(db, line) {
+        return Some(card_ref);
+    }
+
+    let card = db.card_by_name(&line.name)?;
+
+    //some formats only give the set; any printing from it will do
+    let in_set = line.set.as_ref().and_then(|set| {
+        db.printings(&card)
+            .into_iter()
+            .map(|x| x.card_ref)
+            .find(|x| x.set.eq_ignore_ascii_case(set))
+    });
+
+    in_set.or_else(|| db.card_ref(&card))
+}
+
+pub fn resolve_lines(db: &AllCardsDb, lines: Vec<DeckLine>) -> ImportedDeck {
+    let mut imported = ImportedDeck::default();
+
+    for line in lines {
+        match resolve_line(db, &line) {
+            Some(card_ref) => imported.deck.add(line.section, card_ref, line.quantity),
+            None => imported.unresolved.push(line),
+        }
+    }
+
+    imported
+}
+
+///
+/// Writers call this before writing anything, since a card that isn't in the DB
+/// has no name to write it by, and leaving it out would quietly lose it.
+pub(crate) fn check_in_db<'a>(
+    db: &AllCardsDb,
+    entries: impl IntoIterator<Item = &'a DeckEntry>,
+) -> std::io::Result<()> {
+    let missing = entries
+        .into_iter()
+        .filter(|x| db.card_by_ref(&x.card).is_none())
+        .map(|x| format!("{} {} {}", x.quantity, x.card.set, x.card.collector_number))
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("not in the card DB, so they can't be written: {}", missing.join(", ")),
+        ))
+    }
+}
+
+///
+/// The deck file formats, for picking one by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeckFormat {
+    /// Plain text, which takes most of what people paste around; also what `.txt` files are read as
+    Text,
+    Arena,
+    Dek,
+    Cockatrice,
+    Forge,
+}
+
+impl DeckFormat {
+    pub const ALL: [DeckFormat; 5] = [
+        DeckFormat::Text,
+        DeckFormat::Arena,
+        DeckFormat::Dek,
+        DeckFormat::Cockatrice,
+        DeckFormat::Forge,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DeckFormat::Text | DeckFormat::Arena => "txt",
+            DeckFormat::Dek => "dek",
+            DeckFormat::Cockatrice => "cod",
+            DeckFormat::Forge => "dck",
+        }
+    }
+
+    /// Text files are read as plain text, which Arena's format is a kind of
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.extension().eq_ignore_ascii_case(extension))
+    }
+
+    pub fn parse(&self, text: &str) -> Result<Vec<DeckLine>, FormatErr> {
+        match self {
+            DeckFormat::Text => Ok(text::parse_text(text)),
+            DeckFormat::Arena => arena::parse_arena(text),
+            DeckFormat::Dek => dek::parse_dek(text),
+            DeckFormat::Cockatrice => cockatrice::parse_cod(text),
+            DeckFormat::Forge => forge::parse_forge(text),
+        }
+    }
+
+    pub fn import(&self, db: &AllCardsDb, text: &str) -> Result<ImportedDeck, FormatErr> {
+        Ok(resolve_lines(db, self.parse(text)?))
+    }
+
+    /// Fails without writing anything if any of the cards to be written aren't in the DB
+    pub fn write<W: Write>(
+        &self,
+        db: &AllCardsDb,
+        name: &str,
+        deck: &Deck,
+        w: &mut W,
+    ) -> std::io::Result<()> {
+        match self {
+            DeckFormat::Text => text::write_text(db, deck, w),
+            DeckFormat::Arena => arena::write_arena(db, deck, w),
+            DeckFormat::Dek => dek::write_dek(db, deck, w),
+            DeckFormat::Cockatrice => cockatrice::write_cod(db, name, deck, w),
+            DeckFormat::Forge => forge::write_forge(db, name, deck, w),
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_write_unknown_cards() {
+    use crate::{data_model::card::Card, dbs::allcards::test_all_cards_db};
+
+    let (db, refs) = test_all_cards_db("formats-unknown", vec![Card::named("Lightning Bolt")]);
+    let unknown = CardRef {
+        set: "TST".to_string(),
+        collector_number: 2.into(),
+        printing: None,
+    };
+
+    let mut deck = Deck::default();
+    deck.add(DeckSection::Main, refs[0].clone(), 4);
+    deck.add(DeckSection::Side, unknown.clone(), 2);
+
+    for format in DeckFormat::ALL {
+        let mut out = Vec::new();
+        let err = format.write(&db, "Burn", &deck, &mut out).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("2 TST 2"), "{format:?}");
+        assert!(out.is_empty(), "{format:?}");
+    }
+
+    //the maybeboard isn't written by most formats, so an unknown card there only matters to those that do
+    deck.remove(DeckSection::Side, &unknown, 2);
+    deck.add(DeckSection::Maybe, unknown, 1);
+    assert!(DeckFormat::Dek.write(&db, "Burn", &deck, &mut Vec::new()).is_ok());
+    assert!(DeckFormat::Arena.write(&db, "Burn", &deck, &mut Vec::new()).is_err());
+}
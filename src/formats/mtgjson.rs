@@ -0,0 +1,204 @@
+//MTGJSON's Deck file format (https://mtgjson.com/data-models/deck/), the same
+//shape as the precon decks MTGJSON ships, so tools that read those can read ours.
+
+use std::{collections::HashMap, io::Write};
+
+use serde_json::{Value, json};
+
+use crate::{
+    data_model::{
+        card::{CardRef, Finish},
+        date::Date,
+        deck::{Deck, DeckSection},
+        group::{CardGroup, CardGroupVariety},
+    },
+    dbs::{allcards::AllCardsDb, collection::CollectionDb},
+};
+
+/// The MTGJSON version whose Deck schema this follows
+const MTGJSON_VERSION: &str = "5.2.2";
+
+///
+/// MTGJSON only has `isFoil`, so etched cards count as foil.
+fn card_json(db: &AllCardsDb, card_ref: &CardRef, quantity: usize, finish: Finish) -> Option<Value> {
+    let card = db.card_by_ref(card_ref)?;
+    let printing = db.printing(card_ref);
+
+    Some(json!({
+        "count": quantity,
+        "isFoil": finish != Finish::Nonfoil,
+        "name": card.name,
+        "setCode": card_ref.set,
+        "number": card_ref.collector_number.to_string(),
+        "uuid": db.printing_uuid(card_ref),
+        "manaValue": card.mana_value_times_4 as f64 / 4.0,
+        "text": card.oracle_text,
+        "artist": printing.map(|x| x.artist),
+    }))
+}
+
+fn file_json(name: &str, deck_type: &str, commander: Vec<Value>, main: Vec<Value>, side: Vec<Value>) -> Value {
+    json!({
+        "meta": {
+            "date": Date::today().to_string(),
+            "version": MTGJSON_VERSION,
+        },
+        "data": {
+            "code": "",
+            "name": name,
+            "releaseDate": null,
+            "type": deck_type,
+            "commander": commander,
+            "mainBoard": main,
+            "sideBoard": side,
+        }
+    })
+}
+
+///
+/// MTGJSON has no companion or maybeboard; companions go in the sideboard
+/// (where they live in a real deck), and the maybeboard is left out.
+/// Decks don't record finish, so every card is written as nonfoil.
+pub fn deck_json(db: &AllCardsDb, name: &str, deck: &Deck, deck_type: &str) -> Value {
+    let section = |sections: &[DeckSection]| {
+        deck.entries
+            .iter()
+            .filter(|x| sections.contains(&x.section))
+            .filter_map(|x| card_json(db, &x.card, x.quantity, Finish::Nonfoil))
+            .collect::<Vec<_>>()
+    };
+
+    file_json(
+        name,
+        deck_type,
+        section(&[DeckSection::Commander]),
+        section(&[DeckSection::Main]),
+        section(&[DeckSection::Companion, DeckSection::Side]),
+    )
+}
+
+///
+/// The whole collection as one main board. Unlike groups, collection entries know
+/// their finish, so foil copies are listed apart from nonfoil ones. MTGJSON has no
+/// condition or language, so entries differing only in those are merged.
+pub fn collection_json(db: &AllCardsDb, collection: &CollectionDb) -> Value {
+    //kept in the collection's order, with the position of each printing and finish
+    let mut merged: Vec<(CardRef, Finish, usize)> = Vec::new();
+    let mut positions: HashMap<(CardRef, bool), usize> = HashMap::new();
+    for entry in collection.entries() {
+        let card = entry.card;
+        let key = (card.card.clone(), card.finish != Finish::Nonfoil);
+        match positions.get(&key) {
+            Some(i) => merged[*i].2 += card.duplicates,
+            None => {
+                positions.insert(key, merged.len());
+                merged.push((card.card, card.finish, card.duplicates));
+            }
+        }
+    }
+
+    let main = merged
+        .iter()
+        .filter_map(|(card_ref, finish, quantity)| card_json(db, card_ref, *quantity, *finish))
+        .collect();
+
+    file_json("Collection", "collection", Vec::new(), main, Vec::new())
+}
+
+pub fn write_mtgjson_deck<W: Write>(
+    db: &AllCardsDb,
+    name: &str,
+    deck: &Deck,
+    w: &mut W,
+) -> std::io::Result<()> {
+    let deck_type = if deck.count_in(DeckSection::Commander) > 0 {
+        "Commander Deck"
+    } else {
+        "Constructed Deck"
+    };
+
+    serde_json::to_writer_pretty(&mut *w, &deck_json(db, name, deck, deck_type))?;
+    writeln!(w)
+}
+
+/// Writes any group of cards as a deck, e.g. a binder as one big main board
+pub fn write_mtgjson_group<W: Write>(
+    db: &AllCardsDb,
+    group: &CardGroup,
+    w: &mut W,
+) -> std::io::Result<()> {
+    match group.group_type {
+        CardGroupVariety::Deck => write_mtgjson_deck(db, &group.name, &group.cards, w),
+        variety => {
+            let json = deck_json(db, &group.name, &group.cards, variety.keyword());
+            serde_json::to_writer_pretty(&mut *w, &json)?;
+            writeln!(w)
+        }
+    }
+}
+
+pub fn write_mtgjson_collection<W: Write>(
+    db: &AllCardsDb,
+    collection: &CollectionDb,
+    w: &mut W,
+) -> std::io::Result<()> {
+    serde_json::to_writer_pretty(&mut *w, &collection_json(db, collection))?;
+    writeln!(w)
+}
+
+#[cfg(test)]
+#[test]
+fn test_mtgjson_shape() {
+    use crate::{
+        data_model::card::{Card, PhysicalCard},
+        dbs::{allcards::test_all_cards_db, folders::test_db_path},
+    };
+
+    let (db, refs) = test_all_cards_db(
+        "mtgjson-cards",
+        vec![Card::named("Lightning Bolt"), Card::named("Lurrus of the Dream-Den")],
+    );
+    db.add_printing_uuid("f29ba16f-c8fb-42fe-aabf-87089cb214a7", &refs[0]);
+
+    let mut deck = Deck::default();
+    deck.add(DeckSection::Main, refs[0].clone(), 4);
+    deck.add(DeckSection::Companion, refs[1].clone(), 1);
+    deck.add(DeckSection::Maybe, refs[1].clone(), 1);
+
+    let json = deck_json(&db, "Burn", &deck, "Constructed Deck");
+    assert_eq!(json["meta"]["version"], MTGJSON_VERSION);
+    assert_eq!(json["data"]["name"], "Burn");
+    assert_eq!(json["data"]["type"], "Constructed Deck");
+    assert_eq!(json["data"]["commander"], json!([]));
+
+    let main = &json["data"]["mainBoard"];
+    assert_eq!(main.as_array().unwrap().len(), 1);
+    assert_eq!(main[0]["count"], 4);
+    assert_eq!(main[0]["name"], "Lightning Bolt");
+    assert_eq!(main[0]["setCode"], "TST");
+    assert_eq!(main[0]["number"], "1");
+    assert_eq!(main[0]["uuid"], "f29ba16f-c8fb-42fe-aabf-87089cb214a7");
+    assert_eq!(main[0]["isFoil"], false);
+
+    //the companion's in the sideboard, the maybeboard's left out, and no UUID is null
+    let side = &json["data"]["sideBoard"];
+    assert_eq!(side.as_array().unwrap().len(), 1);
+    assert_eq!(side[0]["name"], "Lurrus of the Dream-Den");
+    assert_eq!(side[0]["uuid"], Value::Null);
+
+    let collection = CollectionDb::open(test_db_path("mtgjson-collection")).unwrap();
+    collection.add(PhysicalCard::new(refs[0].clone(), 2));
+    collection.add(PhysicalCard {
+        finish: Finish::Etched,
+        ..PhysicalCard::new(refs[0].clone(), 1)
+    });
+    let json = collection_json(&db, &collection);
+    let mut main = json["data"]["mainBoard"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| (x["count"].as_u64().unwrap(), x["isFoil"].as_bool().unwrap()))
+        .collect::<Vec<_>>();
+    main.sort();
+    assert_eq!(main, [(1, true), (2, false)]);
+}
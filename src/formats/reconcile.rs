@@ -0,0 +1,344 @@
+//Matching imported card names against the DB when they aren't quite right:
+//first exactly, then ignoring case, accents and punctuation, then fuzzily.
+//The fuzzy matcher only finds names with the typed letters in order (so it catches
+//abbreviations like "bolt"), so misspellings fall back on edit distance.
+
+use std::collections::HashMap;
+
+use nucleo_matcher::{
+    Config, Matcher,
+    pattern::{CaseMatching, Normalization, Pattern},
+};
+
+use crate::{data_model::card::CardRef, dbs::allcards::AllCardsDb};
+
+use super::{DeckLine, ImportedDeck, resolve_line};
+
+/// Fuzzy matches past this many aren't worth showing
+const MAX_CANDIDATES: usize = 5;
+
+/// How many letters can be wrong, per letter of the name, for a misspelling to match
+const TYPOS_PER_LETTER: f64 = 0.2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub card: CardRef,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    Exact(CardRef),
+    /// The name only matched once case, accents and punctuation were ignored,
+    /// or it fuzzily matched just one card
+    Corrected(Candidate),
+    /// Best match first
+    Ambiguous(Vec<Candidate>),
+    Unknown,
+    /// The user picked the card
+    Confirmed(Candidate),
+    /// The user chose to leave the line out
+    Skipped,
+}
+
+impl Resolution {
+    pub fn card(&self) -> Option<&CardRef> {
+        match self {
+            Resolution::Exact(card) => Some(card),
+            Resolution::Corrected(x) | Resolution::Confirmed(x) => Some(&x.card),
+            Resolution::Ambiguous(_) | Resolution::Unknown | Resolution::Skipped => None,
+        }
+    }
+
+    /// The cards the user should pick between; empty if nothing matched
+    pub fn candidates(&self) -> &[Candidate] {
+        match self {
+            Resolution::Corrected(x) => std::slice::from_ref(x),
+            Resolution::Ambiguous(x) => x,
+            _ => &[],
+        }
+    }
+
+    /// Whether the user ought to look at this before it's imported
+    pub fn needs_review(&self) -> bool {
+        matches!(
+            self,
+            Resolution::Corrected(_) | Resolution::Ambiguous(_) | Resolution::Unknown
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconciledLine {
+    pub line: DeckLine,
+    pub resolution: Resolution,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Reconciliation {
+    pub lines: Vec<ReconciledLine>,
+}
+
+impl Reconciliation {
+    /// Indexes into `lines`, in order
+    pub fn needs_review(&self) -> Vec<usize> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.resolution.needs_review())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn confirm(&mut self, index: usize, candidate: Candidate) {
+        if let Some(line) = self.lines.get_mut(index) {
+            line.resolution = Resolution::Confirmed(candidate);
+        }
+    }
+
+    pub fn skip(&mut self, index: usize) {
+        if let Some(line) = self.lines.get_mut(index) {
+            line.resolution = Resolution::Skipped;
+        }
+    }
+
+    ///
+    /// Corrected lines go in as they are, unless they've been skipped.
+    /// Ambiguous and unknown lines which weren't confirmed are left out, and listed as unresolved.
+    pub fn to_imported(&self) -> ImportedDeck {
+        let mut imported = ImportedDeck::default();
+
+        for line in self.lines.iter() {
+            match line.resolution.card() {
+                Some(card) => imported
+                    .deck
+                    .add(line.line.section, card.clone(), line.line.quantity),
+                None if line.resolution == Resolution::Skipped => {}
+                None => imported.unresolved.push(line.line.clone()),
+            }
+        }
+
+        imported
+    }
+}
+
+///
+/// Lowercases, takes the accents off letters, and drops punctuation, so that
+/// `Lim-Dûl’s Vault` and `lim duls vault` come out the same. ` // ` is kept for split cards.
+pub fn normalize_name(name: &str) -> String {
+    let mut r = String::with_capacity(name.len());
+
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => r.push('a'),
+            'ç' => r.push('c'),
+            'è' | 'é' | 'ê' | 'ë' => r.push('e'),
+            'ì' | 'í' | 'î' | 'ï' => r.push('i'),
+            'ñ' => r.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => r.push('o'),
+            'ù' | 'ú' | 'û' | 'ü' => r.push('u'),
+            'ý' | 'ÿ' => r.push('y'),
+            'æ' => r.push_str("ae"),
+            'œ' => r.push_str("oe"),
+            'ß' => r.push_str("ss"),
+            '/' => r.push('/'),
+            c if c.is_alphanumeric() => r.push(c),
+            '-' | '_' => r.push(' '),
+            c if c.is_whitespace() => r.push(' '),
+            //apostrophes, commas, etc.
+            _ => {}
+        }
+    }
+
+    r.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+///
+/// Holds every card name, so build one per import rather than one per line.
+pub struct Reconciler<'a> {
+    db: &'a AllCardsDb,
+    names: Vec<String>,
+    /// Indexes into `names`
+    by_normalized: HashMap<String, Vec<usize>>,
+    matcher: Matcher,
+}
+
+impl<'a> Reconciler<'a> {
+    pub fn new(db: &'a AllCardsDb) -> Self {
+        let names = db.all_cards().map(|x| x.name).collect::<Vec<_>>();
+
+        let mut by_normalized = HashMap::<String, Vec<usize>>::new();
+        for (i, name) in names.iter().enumerate() {
+            let normalized = normalize_name(name);
+            //double-faced cards are often written as just their front face
+            if let Some((front, _)) = normalized.split_once(" // ") {
+                by_normalized.entry(front.to_string()).or_default().push(i);
+            }
+            by_normalized.entry(normalized).or_default().push(i);
+        }
+
+        Self {
+            db,
+            names,
+            by_normalized,
+            matcher: Matcher::new(Config::DEFAULT),
+        }
+    }
+
+    fn candidate(&self, line: &DeckLine, name: &str) -> Option<Candidate> {
+        let mut corrected = line.clone();
+        corrected.name = name.to_string();
+
+        Some(Candidate {
+            card: resolve_line(self.db, &corrected)?,
+            name: name.to_string(),
+        })
+    }
+
+    fn from_candidates(candidates: Vec<Candidate>) -> Resolution {
+        match candidates.len() {
+            0 => Resolution::Unknown,
+            1 => Resolution::Corrected(candidates.into_iter().next().unwrap()),
+            _ => Resolution::Ambiguous(candidates),
+        }
+    }
+
+    pub fn resolve(&mut self, line: &DeckLine) -> Resolution {
+        if let Some(card) = resolve_line(self.db, line) {
+            return Resolution::Exact(card);
+        }
+
+        if let Some(matches) = self.by_normalized.get(&normalize_name(&line.name)) {
+            let candidates = matches
+                .iter()
+                .filter_map(|i| self.candidate(line, &self.names[*i]))
+                .collect::<Vec<_>>();
+            if !candidates.is_empty() {
+                return Self::from_candidates(candidates);
+            }
+        }
+
+        let mut fuzzy = Pattern::parse(&line.name, CaseMatching::Ignore, Normalization::Smart)
+            .match_list(self.names.iter(), &mut self.matcher)
+            .into_iter()
+            .take(MAX_CANDIDATES)
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        if fuzzy.is_empty() {
+            fuzzy = self.misspelled(&line.name);
+        }
+
+        Self::from_candidates(
+            fuzzy
+                .iter()
+                .filter_map(|name| self.candidate(line, name))
+                .collect(),
+        )
+    }
+
+    ///
+    /// The names closest to `name` by edit distance, closest first, ignoring those
+    /// with too many letters wrong for `name` to be a misspelling of them.
+    fn misspelled(&self, name: &str) -> Vec<String> {
+        let name = normalize_name(name);
+        let len = name.chars().count();
+        let max_typos = ((len as f64 * TYPOS_PER_LETTER) as usize).max(1);
+
+        let mut close = self
+            .by_normalized
+            .iter()
+            .filter(|(other, _)| other.chars().count().abs_diff(len) <= max_typos)
+            .map(|(other, names)| (edit_distance(&name, other), names))
+            .filter(|(distance, _)| *distance <= max_typos)
+            .collect::<Vec<_>>();
+        close.sort_by_key(|(distance, names)| (*distance, names[0]));
+
+        let mut found = Vec::new();
+        for i in close.into_iter().flat_map(|(_, names)| names) {
+            if !found.contains(&self.names[*i]) {
+                found.push(self.names[*i].clone());
+            }
+        }
+        found.truncate(MAX_CANDIDATES);
+        found
+    }
+
+    pub fn reconcile(&mut self, lines: Vec<DeckLine>) -> Reconciliation {
+        Reconciliation {
+            lines: lines
+                .into_iter()
+                .map(|line| ReconciledLine {
+                    resolution: self.resolve(&line),
+                    line,
+                })
+                .collect(),
+        }
+    }
+}
+
+///
+/// How many letters have to be added, removed or changed to turn one string into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a_char == *b_char {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("lightening bolt", "lightning bolt"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "opt"), 3);
+    assert_eq!(edit_distance("shock", "shock"), 0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_resolve_misspelling() {
+    use crate::{
+        data_model::{card::Card, deck::DeckSection},
+        dbs::allcards::test_all_cards_db,
+    };
+
+    let (db, refs) = test_all_cards_db(
+        "reconcile-cards",
+        vec![Card::named("Lightning Bolt"), Card::named("Lightning Helix")],
+    );
+    let mut reconciler = Reconciler::new(&db);
+    let line = |name: &str| DeckLine::new(1, DeckSection::Main, 4, name);
+
+    assert_eq!(
+        reconciler.resolve(&line("Lightening Bolt")),
+        Resolution::Corrected(Candidate {
+            card: refs[0].clone(),
+            name: "Lightning Bolt".to_string(),
+        })
+    );
+    assert_eq!(reconciler.resolve(&line("Counterspell")), Resolution::Unknown);
+}
+
+#[cfg(test)]
+#[test]
+fn test_normalize_name() {
+    assert_eq!(normalize_name("Lim-Dûl’s Vault"), "lim duls vault");
+    assert_eq!(normalize_name("  LIM-DUL'S   vault "), "lim duls vault");
+    assert_eq!(normalize_name("Æther Vial"), "aether vial");
+    assert_eq!(normalize_name("Fire // Ice"), "fire // ice");
+    assert_eq!(normalize_name("Jace, the Mind Sculptor"), "jace the mind sculptor");
+}
@@ -0,0 +1,255 @@
+//Plain-text decklists, as pasted from websites and forums. There's no one format,
+//so this takes everything we've seen in the wild:
+// - `4 Name`, `4x Name`, and `Name x4`
+// - section headers (`Sideboard`, `SB:`, `// Commander`, `Sideboard (15)`), or just a
+//   blank line before the sideboard when there are no headers
+// - `SB: 2 Name` lines in amongst the main deck
+// - `*CMDR*` after commanders, and other `*X*` markers (e.g. `*F*` for foil), which are ignored
+// - Arena's `(SET) 123` printings
+// - `//` and `#` comments, and category headers like `Creatures (20)` or `Lands:`
+// - split cards, written as `Fire // Ice`, `Fire//Ice` or `Fire/Ice`
+
+use std::io::Write;
+
+use crate::{
+    data_model::deck::{Deck, DeckSection},
+    dbs::allcards::AllCardsDb,
+};
+
+use super::{DeckLine, ImportedDeck, arena::split_printing, check_in_db, resolve_lines};
+
+/// `Sideboard (15)` -> `Sideboard`
+fn strip_count(header: &str) -> &str {
+    let Some(open) = header.rfind('(') else {
+        return header;
+    };
+    let inside = header[open + 1..].trim_end().strip_suffix(')');
+    match inside {
+        Some(count) if count.trim().chars().all(|c| c.is_ascii_digit()) => header[..open].trim(),
+        _ => header,
+    }
+}
+
+fn parse_quantity(token: &str) -> Option<usize> {
+    let token = token
+        .strip_suffix(['x', 'X'])
+        .or_else(|| token.strip_prefix(['x', 'X']))
+        .unwrap_or(token);
+    token.parse().ok()
+}
+
+/// `Fire/Ice` and `Fire//Ice` -> `Fire // Ice`
+fn normalize_split_name(name: &str) -> String {
+    if !name.contains('/') {
+        return name.to_string();
+    }
+    name.split('/')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join(" // ")
+}
+
+///
+/// Never fails: anything that isn't recognized as something else is taken as a card name,
+/// and left for the name lookup to sort out.
+pub fn parse_text(text: &str) -> Vec<DeckLine> {
+    let mut lines = Vec::new();
+    let mut section = DeckSection::Main;
+    //blank lines only mean the sideboard when the list has no headers of any kind
+    let mut saw_header = false;
+    let mut in_about = false;
+    let mut blank_since_last_card = false;
+
+    for (i, text_line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let mut text_line = text_line.trim();
+
+        if text_line.is_empty() {
+            blank_since_last_card = true;
+            continue;
+        }
+
+        if let Some(header) = DeckSection::from_header(strip_count(text_line)) {
+            section = header;
+            saw_header = true;
+            in_about = false;
+            continue;
+        }
+        if text_line.eq_ignore_ascii_case("about") {
+            in_about = true;
+            continue;
+        }
+        if in_about || text_line.starts_with("//") || text_line.starts_with('#') {
+            continue;
+        }
+
+        let starts_with_number = text_line.starts_with(|c: char| c.is_ascii_digit());
+        if !starts_with_number && (text_line.ends_with(':') || strip_count(text_line) != text_line) {
+            //a category like `Creatures (20)`, within whatever section we're in
+            saw_header = true;
+            continue;
+        }
+
+        let mut line_section = section;
+        if text_line.get(..3).is_some_and(|x| x.eq_ignore_ascii_case("sb:")) {
+            line_section = DeckSection::Side;
+            text_line = text_line[3..].trim();
+        }
+
+        //markers come last, e.g. `1 Atraxa, Praetors' Voice *CMDR*`
+        while let Some(without_star) = text_line.strip_suffix('*') {
+            let Some(open) = without_star.rfind('*') else {
+                break;
+            };
+            if without_star[open + 1..].eq_ignore_ascii_case("cmdr") {
+                line_section = DeckSection::Commander;
+            }
+            text_line = without_star[..open].trim();
+        }
+
+        let mut quantity = None;
+        if let Some((first, rest)) = text_line.split_once(char::is_whitespace) {
+            if let Some(q) = parse_quantity(first) {
+                quantity = Some(q);
+                text_line = rest.trim();
+            }
+        }
+        if quantity.is_none() {
+            if let Some((rest, last)) = text_line.rsplit_once(char::is_whitespace) {
+                if last.starts_with(['x', 'X']) {
+                    if let Some(q) = parse_quantity(last) {
+                        quantity = Some(q);
+                        text_line = rest.trim();
+                    }
+                }
+            }
+        }
+
+        let (name, set, collector_number) = split_printing(text_line);
+        if name.is_empty() {
+            continue;
+        }
+
+        //no headers means a blank line is all that separates the sideboard
+        if blank_since_last_card
+            && !saw_header
+            && section == DeckSection::Main
+            && lines.iter().any(|x: &DeckLine| x.section == DeckSection::Main)
+        {
+            section = DeckSection::Side;
+            if line_section == DeckSection::Main {
+                line_section = DeckSection::Side;
+            }
+        }
+        blank_since_last_card = false;
+
+        let mut line = DeckLine::new(
+            line_number,
+            line_section,
+            quantity.unwrap_or(1),
+            normalize_split_name(name),
+        );
+        line.set = set;
+        line.collector_number = collector_number;
+        lines.push(line);
+    }
+
+    lines
+}
+
+pub fn import_text(db: &AllCardsDb, text: &str) -> ImportedDeck {
+    resolve_lines(db, parse_text(text))
+}
+
+///
+/// A header for each section, then `4 Name` lines. Reads back in with `parse_text`.
+pub fn write_text<W: Write>(db: &AllCardsDb, deck: &Deck, w: &mut W) -> std::io::Result<()> {
+    check_in_db(db, &deck.entries)?;
+    let mut first = true;
+
+    for section in DeckSection::ALL {
+        if deck.section(section).next().is_none() {
+            continue;
+        }
+        if !first {
+            writeln!(w)?;
+        }
+        first = false;
+
+        writeln!(w, "{}", section.name())?;
+        for entry in deck.section(section) {
+            if let Some(card) = db.card_by_ref(&entry.card) {
+                writeln!(w, "{} {}", entry.quantity, card.name)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_text() {
+    let parsed = |text: &str| {
+        parse_text(text)
+            .into_iter()
+            .map(|x| (x.section, x.quantity, x.name))
+            .collect::<Vec<_>>()
+    };
+    let line = |section: DeckSection, quantity: usize, name: &str| (section, quantity, name.to_string());
+    use DeckSection::*;
+
+    assert_eq!(
+        parsed("4x Lightning Bolt\nGoblin Guide x4\n2 Fire/Ice\n1 Wear//Tear\n\n3 Smash to Smithereens\nSB: 1 Pyroblast"),
+        [
+            line(Main, 4, "Lightning Bolt"),
+            line(Main, 4, "Goblin Guide"),
+            line(Main, 2, "Fire // Ice"),
+            line(Main, 1, "Wear // Tear"),
+            line(Side, 3, "Smash to Smithereens"),
+            line(Side, 1, "Pyroblast"),
+        ]
+    );
+
+    assert_eq!(
+        parsed("// Burn, by someone\n# tuned for the league\nCreatures (8)\n4 Goblin Guide\n4 Monastery Swiftspear *F*\nSpells:\n4 Lightning Bolt (STA) 42\n\n4 Lava Spike\nSB: 2 Path to Exile\n\nSideboard (2)\n2 Smash to Smithereens"),
+        [
+            line(Main, 4, "Goblin Guide"),
+            line(Main, 4, "Monastery Swiftspear"),
+            line(Main, 4, "Lightning Bolt"),
+            line(Main, 4, "Lava Spike"),
+            line(Side, 2, "Path to Exile"),
+            line(Side, 2, "Smash to Smithereens"),
+        ]
+    );
+
+    assert_eq!(
+        parsed("1x Atraxa, Praetors' Voice *CMDR*\n1x Sol Ring\n1 Command Tower"),
+        [
+            line(Commander, 1, "Atraxa, Praetors' Voice"),
+            line(Main, 1, "Sol Ring"),
+            line(Main, 1, "Command Tower"),
+        ]
+    );
+
+    //headers mean blank lines don't start the sideboard
+    assert_eq!(
+        parsed("Commander\n1 Zada, Hedron Grinder\n\nDeck\n1 Expedite\n\n1 Crimson Wisps\n\n// Maybeboard\n1 Fling"),
+        [
+            line(Commander, 1, "Zada, Hedron Grinder"),
+            line(Main, 1, "Expedite"),
+            line(Main, 1, "Crimson Wisps"),
+            line(Maybe, 1, "Fling"),
+        ]
+    );
+
+    assert_eq!(
+        parsed("1 Æther Vial\nSB: 1 Æther Spellbomb"),
+        [line(Main, 1, "Æther Vial"), line(Side, 1, "Æther Spellbomb")]
+    );
+
+    let bolt = &parse_text("4 Lightning Bolt (STA) 42")[0];
+    assert_eq!(bolt.set.as_deref(), Some("STA"));
+    assert_eq!(bolt.collector_number.as_deref(), Some("42"));
+}
@@ -0,0 +1,228 @@
+//Just enough XML for the deck formats which use it: elements, attributes,
+//text, and the five predefined entities. No DTDs, namespaces, or CDATA.
+
+use super::FormatErr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum XmlToken {
+    Open {
+        name: String,
+        attributes: Vec<(String, String)>,
+        /// `<Tag/>`, which has no separate close tag
+        self_closing: bool,
+    },
+    Close(String),
+    /// Only text that isn't all whitespace
+    Text(String),
+}
+
+impl XmlToken {
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        match self {
+            XmlToken::Open { attributes, .. } => attributes
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str()),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn unescape(text: &str) -> String {
+    let mut r = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        r.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let Some(semi) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..semi];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity
+                    .strip_prefix('#')
+                    .and_then(|x| x.parse().ok())
+                    .and_then(char::from_u32),
+            },
+        };
+
+        match c {
+            Some(c) => {
+                r.push(c);
+                rest = &rest[semi + 1..];
+            }
+            //not an entity we know; keep it as it was
+            None => {
+                r.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    r.push_str(rest);
+    r
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut r = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => r.push_str("&amp;"),
+            '<' => r.push_str("&lt;"),
+            '>' => r.push_str("&gt;"),
+            '"' => r.push_str("&quot;"),
+            '\'' => r.push_str("&apos;"),
+            c => r.push(c),
+        }
+    }
+    r
+}
+
+fn parse_tag(tag: &str, line: usize) -> Result<XmlToken, FormatErr> {
+    if let Some(name) = tag.strip_prefix('/') {
+        return Ok(XmlToken::Close(name.trim().to_string()));
+    }
+
+    let (tag, self_closing) = match tag.strip_suffix('/') {
+        Some(tag) => (tag, true),
+        None => (tag, false),
+    };
+
+    let name_end = tag
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(tag.len());
+    let name = tag[..name_end].to_string();
+    if name.is_empty() {
+        return Err(FormatErr::new(line, "tag without a name"));
+    }
+
+    let mut attributes = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let eq = rest
+            .find('=')
+            .ok_or_else(|| FormatErr::new(line, format!("attribute without a value in <{name}>")))?;
+        let key = rest[..eq].trim().to_string();
+
+        let value_part = rest[eq + 1..].trim_start();
+        let quote = value_part
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| FormatErr::new(line, format!("unquoted attribute in <{name}>")))?;
+        let value_end = value_part[1..]
+            .find(quote)
+            .ok_or_else(|| FormatErr::new(line, format!("unclosed attribute in <{name}>")))?;
+
+        attributes.push((key, unescape(&value_part[1..1 + value_end])));
+        rest = value_part[value_end + 2..].trim_start();
+    }
+
+    Ok(XmlToken::Open {
+        name,
+        attributes,
+        self_closing,
+    })
+}
+
+///
+/// Splits a document into tokens, each with the (1-based) line it starts on.
+/// Declarations, processing instructions and comments are skipped.
+pub(crate) fn tokenize(document: &str) -> Result<Vec<(usize, XmlToken)>, FormatErr> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = document;
+
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            if !rest.trim().is_empty() {
+                tokens.push((line, XmlToken::Text(unescape(rest.trim()))));
+            }
+            break;
+        };
+
+        let text = &rest[..open];
+        if !text.trim().is_empty() {
+            let leading = text.len() - text.trim_start().len();
+            let text_line = line + text[..leading].matches('\n').count();
+            tokens.push((text_line, XmlToken::Text(unescape(text.trim()))));
+        }
+        line += text.matches('\n').count();
+        rest = &rest[open..];
+
+        let end_marker = if rest.starts_with("<!--") {
+            "-->"
+        } else if rest.starts_with("<?") {
+            "?>"
+        } else {
+            ">"
+        };
+        let close = rest
+            .find(end_marker)
+            .ok_or_else(|| FormatErr::new(line, "unclosed tag"))?;
+        let tag = &rest[1..close];
+
+        if !(tag.starts_with("!") || tag.starts_with('?')) {
+            tokens.push((line, parse_tag(tag.trim(), line)?));
+        }
+
+        line += tag.matches('\n').count();
+        rest = &rest[close + end_marker.len()..];
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+#[test]
+fn test_tokenize() {
+    let doc = "<?xml version=\"1.0\"?>\n<!-- a comment -->\n<Deck a='1'>\n  <Name>Burn &amp; Co</Name>\n  <Cards Name=\"Jace, the Mind Sculptor\" Quantity=\"1\" />\n</Deck>";
+    let tokens = tokenize(doc).unwrap();
+
+    assert_eq!(
+        tokens,
+        [
+            (
+                3,
+                XmlToken::Open {
+                    name: "Deck".to_string(),
+                    attributes: vec![("a".to_string(), "1".to_string())],
+                    self_closing: false
+                }
+            ),
+            (
+                4,
+                XmlToken::Open {
+                    name: "Name".to_string(),
+                    attributes: vec![],
+                    self_closing: false
+                }
+            ),
+            (4, XmlToken::Text("Burn & Co".to_string())),
+            (4, XmlToken::Close("Name".to_string())),
+            (
+                5,
+                XmlToken::Open {
+                    name: "Cards".to_string(),
+                    attributes: vec![
+                        ("Name".to_string(), "Jace, the Mind Sculptor".to_string()),
+                        ("Quantity".to_string(), "1".to_string())
+                    ],
+                    self_closing: true
+                }
+            ),
+            (6, XmlToken::Close("Deck".to_string())),
+        ]
+    );
+
+    assert_eq!(escape("Fire // Ice & \"More\""), "Fire // Ice &amp; &quot;More&quot;");
+}
@@ -1,5 +1,7 @@
 pub const APPNAME_DIRECTORY: &'static str = "mtg-organizer";
 
+pub mod analysis;
 pub mod data_model;
 pub mod dbs;
+pub mod formats;
 pub mod query;
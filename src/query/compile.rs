@@ -1,16 +1,20 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     mem::{Discriminant, discriminant},
-    ops::Not,
+    ops::{Bound, Not, RangeBounds, RangeInclusive},
 };
 
 use tree::tree_traits::{MultidimensionalKey, MultidimensionalParent};
 
 use crate::{
     color_combo,
-    data_model::card::{Card, ColorCombination},
+    data_model::{
+        card::{Card, ColorCombination, Condition, Finish, Language, Printing},
+        date::Date,
+        group::{CardGroup, CardGroupVariety},
+        price::Currency,
+    },
     dbs::{
-        allcards::AllCardsDb,
         indexes::{
             color_combination::ColorCombinationMaybe,
             mana_cost::{self, ManaCostCount},
@@ -20,6 +24,7 @@ use crate::{
         },
     },
     query::{
+        context::{QueryContext, QueryMemo, copies_in_group},
         err_warn_support::{Message, MessageSink},
         parse::{SearchQuery, SearchQueryTree, SearchTerm, parse_str},
     },
@@ -76,6 +81,70 @@ pub enum DbQueryFieldParam<'s> {
     NotNameExact(&'s str),
     OracleTextIncludes(&'s str),
     OracleTextNotIncludes(&'s str),
+    SetType(&'s str),
+    SetTypeNot(&'s str),
+    /// Any printing was released within the bounds
+    ReleaseDate((Bound<Date>, Bound<Date>)),
+    /// No printing was released within the bounds
+    NotReleaseDate((Bound<Date>, Bound<Date>)),
+    /// Any printing matches
+    Printing(PrintingField, &'s str),
+    /// No printings match
+    NotPrinting(PrintingField, &'s str),
+    /// Any copy in the collection is in this language
+    Language(Language),
+    NotLanguage(Language),
+    /// Printed in this language
+    PrintedLanguage(Language),
+    NotPrintedLanguage(Language),
+    /// Copies in the collection, in any printing
+    Owned(RangeInclusive<usize>),
+    NotOwned(RangeInclusive<usize>),
+    /// Owned copies which aren't reserved by exclusive decks
+    Available(RangeInclusive<usize>),
+    NotAvailable(RangeInclusive<usize>),
+    /// In the group with this name. `None` is any variety of group.
+    InGroup(Option<CardGroupVariety>, &'s str),
+    NotInGroup(Option<CardGroupVariety>, &'s str),
+    /// Any owned copy has the finish
+    OwnedFinish(Finish),
+    NotOwnedFinish(Finish),
+    /// Any owned copy is in the condition
+    OwnedCondition(Condition),
+    NotOwnedCondition(Condition),
+    /// The card, or an owned copy of it, has the tag
+    Tag(&'s str),
+    NotTag(&'s str),
+    RulingIncludes(&'s str),
+    RulingNotIncludes(&'s str),
+    /// Cheapest current price of any printing, in cents, is within the range
+    Price(Currency, RangeInclusive<usize>),
+    /// Cheapest current price is outside of the range. Cards without a price don't match.
+    NotPrice(Currency, RangeInclusive<usize>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintingField {
+    Artist,
+    FlavorText,
+    Watermark,
+    Frame,
+    Border,
+}
+
+impl PrintingField {
+    fn matches(&self, printing: &Printing, v: &str) -> bool {
+        match self {
+            PrintingField::Artist => scryfall_ish_string_includes(&printing.artist, v),
+            PrintingField::FlavorText => scryfall_ish_string_includes(&printing.flavor_text, v),
+            PrintingField::Watermark => printing
+                .watermark
+                .as_ref()
+                .is_some_and(|w| w.eq_ignore_ascii_case(v)),
+            PrintingField::Frame => printing.frame.eq_ignore_ascii_case(v),
+            PrintingField::Border => printing.border.eq_ignore_ascii_case(v),
+        }
+    }
 }
 
 impl<'s> DbQueryFieldParam<'s> {
@@ -85,8 +154,9 @@ impl<'s> DbQueryFieldParam<'s> {
             DbQueryFieldParam::ColorId(c) => Some(DbQueryIndex::ColorId(c)),
             DbQueryFieldParam::CardStats(s) => Some(DbQueryIndex::CardStats(s)),
             DbQueryFieldParam::ManaCost(m) => Some(DbQueryIndex::ManaCost(m)),
+            //foreign names are indexed in lower case, and most aren't ASCII
             DbQueryFieldParam::NameExact(n) => Some(DbQueryIndex::NameExact(
-                LongestPrefixMatch::new_prefix(n.to_ascii_lowercase()),
+                LongestPrefixMatch::new_prefix(n.to_lowercase()),
             )),
             DbQueryFieldParam::Type(n) => Some(DbQueryIndex::Type(LongestPrefixMatch::new_prefix(
                 n.to_ascii_lowercase(),
@@ -95,7 +165,8 @@ impl<'s> DbQueryFieldParam<'s> {
         }
     }
 
-    fn matches_card(&self, card: &Card) -> bool {
+    fn matches_card(&self, card: &Card, ctx: QueryContext, memo: &QueryMemo) -> bool {
+        let db = ctx.cards;
         match self {
             DbQueryFieldParam::Color(color) => card.color.is_contained_in(color),
             DbQueryFieldParam::ColorId(color_id) => card.color_id.is_contained_in(color_id),
@@ -121,18 +192,131 @@ impl<'s> DbQueryFieldParam<'s> {
             DbQueryFieldParam::ManaCost(query) => {
                 ManaCostCount::Key::new(&card.mana_cost).is_contained_in(&query)
             }
-            DbQueryFieldParam::NameIncludes(t) => scryfall_ish_string_includes(&card.name, *t),
-            DbQueryFieldParam::NameExact(n) => card.name == *n,
+            DbQueryFieldParam::NameIncludes(t) => {
+                scryfall_ish_string_includes(&card.name, *t)
+                    || db
+                        .foreign_names(card)
+                        .iter()
+                        .any(|x| scryfall_ish_string_includes(&x.name, *t))
+            }
+            DbQueryFieldParam::NameExact(n) => {
+                card.name == *n || db.foreign_names(card).iter().any(|x| x.name == *n)
+            }
             DbQueryFieldParam::NameNotIncludes(t) => !card.name.contains(*t),
             DbQueryFieldParam::NotNameExact(n) => card.name != *n,
             DbQueryFieldParam::OracleTextIncludes(t) => {
                 scryfall_ish_string_includes(&card.oracle_text, *t)
             }
             DbQueryFieldParam::OracleTextNotIncludes(t) => !card.oracle_text.contains(*t),
+            DbQueryFieldParam::SetType(t) => card
+                .sets_released
+                .iter()
+                .filter_map(|s| db.set_info(s))
+                .any(|s| s.set_type.eq_ignore_ascii_case(t)),
+            DbQueryFieldParam::SetTypeNot(t) => card
+                .sets_released
+                .iter()
+                .filter_map(|s| db.set_info(s))
+                .all(|s| !s.set_type.eq_ignore_ascii_case(t)),
+            DbQueryFieldParam::ReleaseDate(bounds) => card
+                .sets_released
+                .iter()
+                .filter_map(|s| db.set_info(s))
+                .any(|s| bounds.contains(&s.release_date)),
+            DbQueryFieldParam::NotReleaseDate(bounds) => !card
+                .sets_released
+                .iter()
+                .filter_map(|s| db.set_info(s))
+                .any(|s| bounds.contains(&s.release_date)),
+            DbQueryFieldParam::Printing(field, v) => {
+                db.printings(card).iter().any(|p| field.matches(p, v))
+            }
+            DbQueryFieldParam::NotPrinting(field, v) => {
+                !db.printings(card).iter().any(|p| field.matches(p, v))
+            }
+            DbQueryFieldParam::Language(l) => {
+                ctx.owned_copies(card).iter().any(|x| x.language == *l)
+            }
+            DbQueryFieldParam::NotLanguage(l) => {
+                !ctx.owned_copies(card).iter().any(|x| x.language == *l)
+            }
+            DbQueryFieldParam::PrintedLanguage(l) => printed_in_language(card, *l, ctx),
+            DbQueryFieldParam::NotPrintedLanguage(l) => !printed_in_language(card, *l, ctx),
+            DbQueryFieldParam::Owned(range) => range.contains(&ctx.owned_count(card)),
+            DbQueryFieldParam::NotOwned(range) => !range.contains(&ctx.owned_count(card)),
+            DbQueryFieldParam::Available(range) => range.contains(&ctx.available_count(card, memo)),
+            DbQueryFieldParam::NotAvailable(range) => {
+                !range.contains(&ctx.available_count(card, memo))
+            }
+            DbQueryFieldParam::InGroup(variety, name) => in_group(card, *variety, name, ctx, memo),
+            DbQueryFieldParam::NotInGroup(variety, name) => {
+                !in_group(card, *variety, name, ctx, memo)
+            }
+            DbQueryFieldParam::OwnedFinish(f) => {
+                ctx.owned_copies(card).iter().any(|x| x.finish == *f)
+            }
+            DbQueryFieldParam::NotOwnedFinish(f) => {
+                !ctx.owned_copies(card).iter().any(|x| x.finish == *f)
+            }
+            DbQueryFieldParam::OwnedCondition(c) => {
+                ctx.owned_copies(card).iter().any(|x| x.condition == *c)
+            }
+            DbQueryFieldParam::NotOwnedCondition(c) => {
+                !ctx.owned_copies(card).iter().any(|x| x.condition == *c)
+            }
+            DbQueryFieldParam::Tag(t) => ctx.has_tag(card, t),
+            DbQueryFieldParam::NotTag(t) => !ctx.has_tag(card, t),
+            DbQueryFieldParam::RulingIncludes(t) => db
+                .rulings(card)
+                .iter()
+                .any(|r| scryfall_ish_string_includes(&r.text, t)),
+            DbQueryFieldParam::RulingNotIncludes(t) => !db
+                .rulings(card)
+                .iter()
+                .any(|r| scryfall_ish_string_includes(&r.text, t)),
+            DbQueryFieldParam::Price(currency, range) => {
+                cheapest_price(card, *currency, ctx).is_some_and(|x| range.contains(&x))
+            }
+            DbQueryFieldParam::NotPrice(currency, range) => {
+                cheapest_price(card, *currency, ctx).is_some_and(|x| !range.contains(&x))
+            }
         }
     }
 }
 
+///
+/// Whether the card was printed in the language at all; `lang:` is for the user's copies.
+fn printed_in_language(card: &Card, language: Language, ctx: QueryContext) -> bool {
+    //every card is printed in English, at least for now
+    language == Language::English
+        || ctx
+            .cards
+            .foreign_names(card)
+            .iter()
+            .any(|x| x.language == language)
+}
+
+fn in_group(
+    card: &Card,
+    variety: Option<CardGroupVariety>,
+    name: &str,
+    ctx: QueryContext,
+    memo: &QueryMemo,
+) -> bool {
+    let Some(group) = memo.group(ctx, variety, name) else {
+        return false;
+    };
+
+    copies_in_group(group, &ctx.printing_refs(card)) > 0
+}
+
+fn cheapest_price(card: &Card, currency: Currency, ctx: QueryContext) -> Option<usize> {
+    let prices = ctx.prices?;
+    prices
+        .cheapest(ctx.cards.printings(card).iter(), currency)
+        .map(|x| x.cents)
+}
+
 fn scryfall_ish_string_includes(haystack: &str, needle: &str) -> bool {
     for i in 0..(haystack.len().saturating_sub(needle.len()) + 1) {
         let needed_chars = needle
@@ -159,31 +343,65 @@ pub struct DbQuery<'s> {
 }
 
 impl DbQuery<'_> {
-    pub fn query_db<'a>(&'a self, db: &'a AllCardsDb) -> Box<dyn Iterator<Item = Card> + 'a> {
+    pub fn query_db<'a>(&'a self, ctx: QueryContext<'a>) -> Box<dyn Iterator<Item = Card> + 'a> {
+        let db = ctx.cards;
+        let memo = QueryMemo::default();
+        let filter = move |x: &Card| self.tree.matches_card(x, ctx, &memo);
         match &self.index {
-            Some(DbQueryIndex::CardStats(c)) => {
-                return Box::new(db.query_stats(c).filter(|x| self.tree.matches_card(x)));
-            }
-            Some(DbQueryIndex::Color(c)) => {
-                return Box::new(db.query_color(c).filter(|x| self.tree.matches_card(x)));
-            }
-            Some(DbQueryIndex::ColorId(c)) => {
-                return Box::new(db.query_color_id(c).filter(|x| self.tree.matches_card(x)));
-            }
-            Some(DbQueryIndex::ManaCost(c)) => {
-                return Box::new(db.query_mana(c).filter(|x| self.tree.matches_card(x)));
-            }
-            Some(DbQueryIndex::NameExact(c)) => {
-                return Box::new(db.query_name(c).filter(|x| self.tree.matches_card(x)));
-            }
-            Some(DbQueryIndex::Type(t)) => {
-                return Box::new(db.query_type(t).filter(|x| self.tree.matches_card(x)));
-            }
-            Some(DbQueryIndex::Empty) => return Box::new(std::iter::empty()),
-            None => {
-                return Box::new(db.all_cards().filter(|x| self.tree.matches_card(x)));
-            }
+            Some(DbQueryIndex::CardStats(c)) => Box::new(db.query_stats(c).filter(filter)),
+            Some(DbQueryIndex::Color(c)) => Box::new(db.query_color(c).filter(filter)),
+            Some(DbQueryIndex::ColorId(c)) => Box::new(db.query_color_id(c).filter(filter)),
+            Some(DbQueryIndex::ManaCost(c)) => Box::new(db.query_mana(c).filter(filter)),
+            Some(DbQueryIndex::NameExact(c)) => Box::new(db.query_name(c).filter(filter)),
+            Some(DbQueryIndex::Type(t)) => Box::new(db.query_type(t).filter(filter)),
+            Some(DbQueryIndex::Empty) => Box::new(std::iter::empty()),
+            None => Box::new(db.all_cards().filter(filter)),
+        }
+    }
+
+    ///
+    /// Runs the query over only these cards, instead of every card in the DB.
+    pub fn query_cards<'a>(
+        &'a self,
+        ctx: QueryContext<'a>,
+        cards: impl Iterator<Item = Card> + 'a,
+    ) -> Box<dyn Iterator<Item = Card> + 'a> {
+        let memo = QueryMemo::default();
+        Box::new(cards.filter(move |x| self.tree.matches_card(x, ctx, &memo)))
+    }
+
+    /// Each card in the group is only returned once, no matter how many copies it has.
+    pub fn query_group<'a>(
+        &'a self,
+        ctx: QueryContext<'a>,
+        group: &CardGroup,
+    ) -> Box<dyn Iterator<Item = Card> + 'a> {
+        let mut seen = HashSet::new();
+        let cards = group
+            .cards
+            .entries
+            .iter()
+            .filter_map(|x| ctx.cards.card_by_ref(&x.card))
+            .filter(|x| seen.insert(x.name.clone()))
+            .collect::<Vec<_>>();
+
+        self.query_cards(ctx, cards.into_iter())
+    }
+
+    pub fn query_collection<'a>(&'a self, ctx: QueryContext<'a>) -> Box<dyn Iterator<Item = Card> + 'a> {
+        let Some(collection) = ctx.collection else {
+            return Box::new(std::iter::empty());
         };
+
+        let mut seen = HashSet::new();
+        let cards = collection
+            .entries()
+            .iter()
+            .filter_map(|x| ctx.cards.card_by_ref(&x.card.card))
+            .filter(|x| seen.insert(x.name.clone()))
+            .collect::<Vec<_>>();
+
+        self.query_cards(ctx, cards.into_iter())
     }
 }
 
@@ -195,11 +413,14 @@ pub enum DbQueryTree<'s> {
 }
 
 impl DbQueryTree<'_> {
-    pub fn matches_card(&self, card: &Card) -> bool {
+    ///
+    /// `memo` should be shared by every card the query is run over, and no longer;
+    /// see `QueryMemo`.
+    pub fn matches_card(&self, card: &Card, ctx: QueryContext, memo: &QueryMemo) -> bool {
         match self {
-            DbQueryTree::And(ands) => ands.iter().all(|x| x.matches_card(card)),
-            DbQueryTree::Or(ors) => ors.iter().any(|x| x.matches_card(card)),
-            DbQueryTree::Term(field) => field.matches_card(card),
+            DbQueryTree::And(ands) => ands.iter().all(|x| x.matches_card(card, ctx, memo)),
+            DbQueryTree::Or(ors) => ors.iter().any(|x| x.matches_card(card, ctx, memo)),
+            DbQueryTree::Term(field) => field.matches_card(card, ctx, memo),
         }
     }
 }
@@ -492,6 +713,244 @@ fn key_op_to_field<'q>(
             }))
         }
 
+        "a" | "artist" | "ft" | "flavor" | "wm" | "watermark" | "frame" | "border" => {
+            let field = match k {
+                "a" | "artist" => PrintingField::Artist,
+                "ft" | "flavor" => PrintingField::FlavorText,
+                "wm" | "watermark" => PrintingField::Watermark,
+                "frame" => PrintingField::Frame,
+                _ => PrintingField::Border,
+            };
+            match op {
+                BinCmp::Neq => Some(DbQueryFieldParam::NotPrinting(field, v)),
+                _ => warn_interp_cmp_as_eq(&compile_errs, k, op, v, byte_index)
+                    .map(|v| DbQueryFieldParam::Printing(field, v)),
+            }
+        }
+        "lang" | "language" | "printlang" | "printedlanguage" => {
+            let Ok(language) = v.parse::<Language>() else {
+                compile_errs.send(Message {
+                    msg_type: super::err_warn_support::MessageSeverity::Error,
+                    msg_content: format!("{v:?} isn't a language we know of. Try a code like 'de' or 'ja'."),
+                    byte_pos: byte_index,
+                    source_phase_index: 2,
+                });
+                return None;
+            };
+
+            //lang: is about the copies in the collection, so it matches nothing without one
+            let printed = k.starts_with("print");
+            match op {
+                BinCmp::Neq if printed => Some(DbQueryFieldParam::NotPrintedLanguage(language)),
+                BinCmp::Neq => Some(DbQueryFieldParam::NotLanguage(language)),
+                _ => warn_interp_cmp_as_eq(&compile_errs, k, op, v, byte_index).map(|_| {
+                    if printed {
+                        DbQueryFieldParam::PrintedLanguage(language)
+                    } else {
+                        DbQueryFieldParam::Language(language)
+                    }
+                }),
+            }
+        }
+        "in" => {
+            let field = match v.split_once(':') {
+                None if v.eq_ignore_ascii_case("collection") => {
+                    DbQueryFieldParam::Owned(1..=usize::MAX)
+                }
+                Some((variety, name)) => {
+                    let variety = match variety.to_ascii_lowercase().as_str() {
+                        "group" => None,
+                        variety => match CardGroupVariety::ALL.iter().find(|x| x.keyword() == variety) {
+                            Some(v) => Some(*v),
+                            None => {
+                                compile_errs.send(Message {
+                                    msg_type: super::err_warn_support::MessageSeverity::Error,
+                                    msg_content: format!("{variety:?} isn't a kind of group. Try deck, binder, box, wishlist, trade, cube, or group."),
+                                    byte_pos: byte_index,
+                                    source_phase_index: 2,
+                                });
+                                return None;
+                            }
+                        },
+                    };
+                    let name = name.trim_matches('"');
+
+                    if op == BinCmp::Neq {
+                        return Some(DbQueryFieldParam::NotInGroup(variety, name));
+                    }
+                    DbQueryFieldParam::InGroup(variety, name)
+                }
+                None => {
+                    compile_errs.send(Message {
+                        msg_type: super::err_warn_support::MessageSeverity::Error,
+                        msg_content: String::from("Try in:collection, or in:deck:\"Name\" for a group"),
+                        byte_pos: byte_index,
+                        source_phase_index: 2,
+                    });
+                    return None;
+                }
+            };
+
+            match (op, field) {
+                (BinCmp::Neq, DbQueryFieldParam::Owned(range)) => {
+                    Some(DbQueryFieldParam::NotOwned(range))
+                }
+                (op, field) => {
+                    warn_interp_cmp_as_eq(&compile_errs, k, op, v, byte_index).map(|_| field)
+                }
+            }
+        }
+        "own" | "owned" | "avail" | "available" => {
+            let Ok(count) = v.parse::<usize>() else {
+                compile_errs.send(Message {
+                    msg_type: super::err_warn_support::MessageSeverity::Error,
+                    msg_content: format!("{v:?} isn't a valid integer"),
+                    byte_pos: byte_index,
+                    source_phase_index: 2,
+                });
+                return None;
+            };
+
+            let negated = op == BinCmp::Neq;
+            let range = count_range(op, count, byte_index, compile_errs)?;
+
+            Some(match (k.starts_with('o'), negated) {
+                (true, false) => DbQueryFieldParam::Owned(range),
+                (true, true) => DbQueryFieldParam::NotOwned(range),
+                (false, false) => DbQueryFieldParam::Available(range),
+                (false, true) => DbQueryFieldParam::NotAvailable(range),
+            })
+        }
+        "finish" => {
+            let finish = match v.to_ascii_lowercase().as_str() {
+                "nonfoil" => Finish::Nonfoil,
+                "foil" => Finish::Foil,
+                "etched" => Finish::Etched,
+                _ => {
+                    compile_errs.send(Message {
+                        msg_type: super::err_warn_support::MessageSeverity::Error,
+                        msg_content: format!("{v:?} isn't a finish. Try nonfoil, foil, or etched."),
+                        byte_pos: byte_index,
+                        source_phase_index: 2,
+                    });
+                    return None;
+                }
+            };
+
+            match op {
+                BinCmp::Neq => Some(DbQueryFieldParam::NotOwnedFinish(finish)),
+                _ => warn_interp_cmp_as_eq(&compile_errs, k, op, v, byte_index)
+                    .map(|_| DbQueryFieldParam::OwnedFinish(finish)),
+            }
+        }
+        "cond" | "condition" => {
+            let Some(condition) = Condition::ALL
+                .into_iter()
+                .find(|x| x.abbreviation().eq_ignore_ascii_case(v))
+            else {
+                compile_errs.send(Message {
+                    msg_type: super::err_warn_support::MessageSeverity::Error,
+                    msg_content: format!("{v:?} isn't a condition. Try M, NM, LP, MP, HP, or DMG."),
+                    byte_pos: byte_index,
+                    source_phase_index: 2,
+                });
+                return None;
+            };
+
+            match op {
+                BinCmp::Neq => Some(DbQueryFieldParam::NotOwnedCondition(condition)),
+                _ => warn_interp_cmp_as_eq(&compile_errs, k, op, v, byte_index)
+                    .map(|_| DbQueryFieldParam::OwnedCondition(condition)),
+            }
+        }
+        "tag" => match op {
+            BinCmp::Neq => Some(DbQueryFieldParam::NotTag(v)),
+            _ => warn_interp_cmp_as_eq(&compile_errs, k, op, v, byte_index)
+                .map(DbQueryFieldParam::Tag),
+        },
+        "ruling" => match op {
+            BinCmp::Neq => Some(DbQueryFieldParam::RulingNotIncludes(v)),
+            _ => warn_interp_cmp_as_eq(&compile_errs, k, op, v, byte_index)
+                .map(DbQueryFieldParam::RulingIncludes),
+        },
+        "st" | "settype" => match op {
+            BinCmp::Neq => Some(DbQueryFieldParam::SetTypeNot(v)),
+            _ => warn_interp_cmp_as_eq(&compile_errs, k, op, v, byte_index)
+                .map(DbQueryFieldParam::SetType),
+        },
+        "year" => {
+            let Ok(year) = v.parse::<usize>() else {
+                compile_errs.send(Message {
+                    msg_type: super::err_warn_support::MessageSeverity::Error,
+                    msg_content: format!("{v:?} isn't a valid year"),
+                    byte_pos: byte_index,
+                    source_phase_index: 2,
+                });
+                return None;
+            };
+
+            Some(release_period_field(
+                op,
+                Date::first_of_year(year),
+                Date::last_of_year(year),
+            ))
+        }
+        "date" => {
+            let Ok(date) = v.parse::<Date>() else {
+                compile_errs.send(Message {
+                    msg_type: super::err_warn_support::MessageSeverity::Error,
+                    msg_content: format!("{v:?} isn't a valid date. Dates are written like 2019-01-31."),
+                    byte_pos: byte_index,
+                    source_phase_index: 2,
+                });
+                return None;
+            };
+
+            Some(release_period_field(op, date, date))
+        }
+
+        "usd" | "eur" | "tix" => {
+            let currency = match k {
+                "usd" => Currency::Usd,
+                "eur" => Currency::Eur,
+                _ => Currency::Tix,
+            };
+            let Some(cents) = parse_cents(v) else {
+                compile_errs.send(Message {
+                    msg_type: super::err_warn_support::MessageSeverity::Error,
+                    msg_content: format!("{v:?} isn't a valid price. Prices are written like 1.50"),
+                    byte_pos: byte_index,
+                    source_phase_index: 2,
+                });
+                return None;
+            };
+
+            let range = match op {
+                BinCmp::Eq | BinCmp::Neq => cents..=cents,
+                BinCmp::Gt => (cents + 1)..=usize::MAX,
+                BinCmp::Gte => cents..=usize::MAX,
+                BinCmp::Lt => match cents.checked_sub(1) {
+                    Some(c) => 0..=c,
+                    None => {
+                        compile_errs.send(Message {
+                            msg_type: super::err_warn_support::MessageSeverity::Error,
+                            msg_content: String::from("There are no cards with a negative price"),
+                            byte_pos: byte_index,
+                            source_phase_index: 2,
+                        });
+                        return None;
+                    }
+                },
+                BinCmp::Lte => 0..=cents,
+            };
+
+            if op == BinCmp::Neq {
+                Some(DbQueryFieldParam::NotPrice(currency, range))
+            } else {
+                Some(DbQueryFieldParam::Price(currency, range))
+            }
+        }
+
         "c" | "color" | "id" | "identity" => {
             let value = color_name(v, byte_index, compile_errs)?;
 
@@ -534,6 +993,75 @@ fn key_op_to_field<'q>(
     }
 }
 
+///
+/// Compares a printing's release date against the period `first..=last`.
+/// A single day is a period where `first == last`.
+fn release_period_field<'q>(op: BinCmp, first: Date, last: Date) -> DbQueryFieldParam<'q> {
+    match op {
+        BinCmp::Eq => {
+            DbQueryFieldParam::ReleaseDate((Bound::Included(first), Bound::Included(last)))
+        }
+        BinCmp::Neq => {
+            DbQueryFieldParam::NotReleaseDate((Bound::Included(first), Bound::Included(last)))
+        }
+        BinCmp::Gt => DbQueryFieldParam::ReleaseDate((Bound::Excluded(last), Bound::Unbounded)),
+        BinCmp::Gte => DbQueryFieldParam::ReleaseDate((Bound::Included(first), Bound::Unbounded)),
+        BinCmp::Lt => DbQueryFieldParam::ReleaseDate((Bound::Unbounded, Bound::Excluded(first))),
+        BinCmp::Lte => DbQueryFieldParam::ReleaseDate((Bound::Unbounded, Bound::Included(last))),
+    }
+}
+
+///
+/// The counts which satisfy `op count`. `!=` gives the same range as `=`;
+/// it's up to the caller to negate it.
+fn count_range(
+    op: BinCmp,
+    count: usize,
+    byte_index: usize,
+    compile_errs: &impl MessageSink,
+) -> Option<RangeInclusive<usize>> {
+    Some(match op {
+        BinCmp::Eq | BinCmp::Neq => count..=count,
+        BinCmp::Gt => (count + 1)..=usize::MAX,
+        BinCmp::Gte => count..=usize::MAX,
+        BinCmp::Lt => match count.checked_sub(1) {
+            Some(c) => 0..=c,
+            None => {
+                compile_errs.send(Message {
+                    msg_type: super::err_warn_support::MessageSeverity::Error,
+                    msg_content: String::from("There can't be fewer than 0 copies of a card"),
+                    byte_pos: byte_index,
+                    source_phase_index: 2,
+                });
+                return None;
+            }
+        },
+        BinCmp::Lte => 0..=count,
+    })
+}
+
+///
+/// Parses a decimal amount like `1`, `1.5`, or `.25` into hundredths.
+fn parse_cents(v: &str) -> Option<usize> {
+    let (whole, fraction) = v.split_once('.').unwrap_or((v, ""));
+
+    if fraction.len() > 2 || !fraction.bytes().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    let whole = if whole.is_empty() && !fraction.is_empty() {
+        0
+    } else {
+        whole.parse::<usize>().ok()?
+    };
+    let fraction = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<usize>().ok()? * 10,
+        _ => fraction.parse::<usize>().ok()?,
+    };
+
+    whole.checked_mul(100)?.checked_add(fraction)
+}
+
 fn warn_interp_cmp_as_eq<'q>(
     compile_errs: &impl MessageSink,
     k: &str,
@@ -703,7 +1231,26 @@ pub fn flatten(q: &mut SearchQueryTree) {
 
 #[cfg(test)]
 mod test {
-    use crate::query::compile::scryfall_ish_string_includes;
+    use crate::{
+        data_model::{
+            card::{Card, CardRef, Finish, ForeignName, ForeignNames, Language, PhysicalCard, Printing},
+            date::Date,
+            deck::DeckSection,
+            group::{CardGroup, CardGroupVariety},
+            price::{Currency, PricePoint},
+            set::SetInfo,
+            tag::TagTarget,
+        },
+        dbs::{
+            allcards::test_all_cards_db, collection::CollectionDb, folders::test_db_path,
+            prices::PriceDb, saving_decks::GroupDb, tags::TagDb,
+        },
+        query::{
+            compile::{build_search_query, parse_cents, scryfall_ish_string_includes},
+            context::QueryContext,
+            err_warn_support::IgnoreMessages,
+        },
+    };
 
     #[test]
     pub fn test() {
@@ -711,4 +1258,240 @@ mod test {
         assert!(scryfall_ish_string_includes("haystack", "haystack"));
         assert!(!scryfall_ish_string_includes("haystack", "x"));
     }
+
+    fn names(query: &str, ctx: QueryContext) -> Vec<String> {
+        let query = build_search_query(query, &IgnoreMessages).unwrap();
+        let mut names = query.query_db(ctx).map(|x| x.name).collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    pub fn test_release_date_keys() {
+        let reprinted = Card {
+            sets_released: vec!["AAA".to_string(), "BBB".to_string()],
+            ..Card::named("Reprinted")
+        };
+        let (cards, _) = test_all_cards_db("query-release-cards", vec![reprinted]);
+        for (code, date) in [("AAA", "2019-01-31"), ("BBB", "2021-06-01")] {
+            cards.add_set(SetInfo {
+                code: code.to_string(),
+                name: code.to_string(),
+                release_date: date.parse().unwrap(),
+                set_type: "expansion".to_string(),
+                block: None,
+                parent_code: None,
+                card_count: 1,
+            });
+        }
+        let ctx = QueryContext::new(&cards);
+
+        assert_eq!(names("year:2019", ctx), ["Reprinted"]);
+        assert_eq!(names("year>2019", ctx), ["Reprinted"]);
+        assert_eq!(names("year<2019", ctx), Vec::<String>::new());
+        //printed in 2019, even though it was printed again later
+        assert_eq!(names("year!=2019", ctx), Vec::<String>::new());
+        assert_eq!(names("year!=2020", ctx), ["Reprinted"]);
+        assert_eq!(names("date!=2021-06-01", ctx), Vec::<String>::new());
+    }
+
+    #[test]
+    pub fn test_collection_keys() {
+        let (cards, refs) = test_all_cards_db(
+            "query-collection-cards",
+            vec![Card::named("Lightning Bolt"), Card::named("Shock"), Card::named("Opt")],
+        );
+        let collection = CollectionDb::open(test_db_path("query-collection")).unwrap();
+        let groups = GroupDb::open(test_db_path("query-collection-groups")).unwrap();
+
+        collection.add(PhysicalCard::new(refs[0].clone(), 4));
+        collection.add(PhysicalCard::new(refs[1].clone(), 1));
+
+        let mut burn = CardGroup::new(CardGroupVariety::Deck, "Burn");
+        burn.cards.add(DeckSection::Main, refs[0].clone(), 3);
+        burn.exclusive = true;
+        groups.create(burn).unwrap();
+        let mut wants = CardGroup::new(CardGroupVariety::Wishlist, "Wants");
+        wants.cards.add(DeckSection::Main, refs[2].clone(), 1);
+        groups.create(wants).unwrap();
+
+        let ctx = QueryContext {
+            collection: Some(&collection),
+            groups: Some(&groups),
+            ..QueryContext::new(&cards)
+        };
+
+        assert_eq!(names("in:collection", ctx), ["Lightning Bolt", "Shock"]);
+        assert_eq!(names("own>=2", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("own:0", ctx), ["Opt"]);
+        //3 of the 4 bolts are reserved by the exclusive deck
+        assert_eq!(names("avail:1", ctx), ["Lightning Bolt", "Shock"]);
+        assert_eq!(names("avail>=2", ctx), Vec::<String>::new());
+
+        assert_eq!(names("in:deck:burn", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("in:group:wants", ctx), ["Opt"]);
+        assert_eq!(names("in:wishlist:Burn", ctx), Vec::<String>::new());
+
+        //without a collection or groups, these match nothing
+        let ctx = QueryContext::new(&cards);
+        assert_eq!(names("in:collection", ctx), Vec::<String>::new());
+        assert_eq!(names("in:deck:burn", ctx), Vec::<String>::new());
+    }
+
+    #[test]
+    pub fn test_tag_keys() {
+        let (cards, refs) = test_all_cards_db(
+            "query-tag-cards",
+            vec![Card::named("Lightning Bolt"), Card::named("Shock"), Card::named("Opt")],
+        );
+        let collection = CollectionDb::open(test_db_path("query-tag-collection")).unwrap();
+        let tags = TagDb::open(test_db_path("query-tags")).unwrap();
+
+        //one card tagged itself, and one through a copy of it
+        tags.tag(TagTarget::Card(refs[0].clone()), "Burn");
+        let shock = collection.add(PhysicalCard::new(refs[1].clone(), 2));
+        tags.tag(TagTarget::Copy(shock), "burn");
+        tags.tag(TagTarget::Card(refs[2].clone()), "cantrip");
+
+        let ctx = QueryContext {
+            collection: Some(&collection),
+            tags: Some(&tags),
+            ..QueryContext::new(&cards)
+        };
+
+        assert_eq!(names("tag:burn", ctx), ["Lightning Bolt", "Shock"]);
+        //tags are looked up the same way they're stored
+        assert_eq!(names("tag:BURN", ctx), ["Lightning Bolt", "Shock"]);
+        assert_eq!(names("-tag:burn", ctx), ["Opt"]);
+        assert_eq!(names("tag!=burn", ctx), ["Opt"]);
+        assert_eq!(names("tag:cantrip or tag:burn", ctx), ["Lightning Bolt", "Opt", "Shock"]);
+        assert_eq!(names("tag:unused", ctx), Vec::<String>::new());
+
+        //without the collection, copies' tags aren't seen
+        let ctx = QueryContext {
+            tags: Some(&tags),
+            ..QueryContext::new(&cards)
+        };
+        assert_eq!(names("tag:burn", ctx), ["Lightning Bolt"]);
+
+        assert_eq!(names("tag:burn", QueryContext::new(&cards)), Vec::<String>::new());
+    }
+
+    #[test]
+    pub fn test_price_keys() {
+        let (cards, refs) = test_all_cards_db(
+            "query-price-cards",
+            vec![Card::named("Lightning Bolt"), Card::named("Shock"), Card::named("Opt")],
+        );
+        let prices = PriceDb::open(test_db_path("query-prices")).unwrap();
+        let point = |card_ref: &CardRef, finish, currency, cents| PricePoint {
+            card_ref: card_ref.clone(),
+            finish,
+            currency,
+            date: Date::first_of_year(2025),
+            cents,
+        };
+        prices.add(point(&refs[0], Finish::Nonfoil, Currency::Usd, 500));
+        //the cheapest finish is what counts
+        prices.add(point(&refs[1], Finish::Foil, Currency::Usd, 800));
+        prices.add(point(&refs[1], Finish::Nonfoil, Currency::Usd, 25));
+        prices.add(point(&refs[1], Finish::Nonfoil, Currency::Eur, 600));
+
+        let ctx = QueryContext {
+            prices: Some(&prices),
+            ..QueryContext::new(&cards)
+        };
+
+        //Opt has no price, so it never matches
+        assert_eq!(names("usd<5", ctx), ["Shock"]);
+        assert_eq!(names("usd<=5", ctx), ["Lightning Bolt", "Shock"]);
+        assert_eq!(names("usd>0.25", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("eur>=6", ctx), ["Shock"]);
+        assert_eq!(names("tix>0", ctx), Vec::<String>::new());
+
+        assert_eq!(names("usd<5", QueryContext::new(&cards)), Vec::<String>::new());
+    }
+
+    #[test]
+    pub fn test_language_keys() {
+        let (cards, refs) = test_all_cards_db(
+            "query-language-cards",
+            vec![Card::named("Lightning Bolt"), Card::named("Shock")],
+        );
+        cards.add_foreign_names(
+            &refs[0],
+            ForeignNames(vec![ForeignName {
+                language: Language::German,
+                name: "Blitzschlag".to_string(),
+            }]),
+        );
+        let collection = CollectionDb::open(test_db_path("query-language-collection")).unwrap();
+        collection.add(PhysicalCard::new(refs[0].clone(), 1));
+        collection.add(PhysicalCard {
+            language: Language::German,
+            ..PhysicalCard::new(refs[1].clone(), 1)
+        });
+
+        //lang: is the language of the copies in the collection
+        let ctx = QueryContext {
+            collection: Some(&collection),
+            ..QueryContext::new(&cards)
+        };
+        assert_eq!(names("lang:de", ctx), ["Shock"]);
+        assert_eq!(names("lang:en", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("lang!=de", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("printlang:de", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("printlang:en", ctx), ["Lightning Bolt", "Shock"]);
+        assert_eq!(names("printlang!=de", ctx), ["Shock"]);
+
+        //without a collection, nothing's owned in any language
+        let ctx = QueryContext::new(&cards);
+        assert_eq!(names("lang:de", ctx), Vec::<String>::new());
+        assert_eq!(names("printlang:de", ctx), ["Lightning Bolt"]);
+    }
+
+    #[test]
+    pub fn test_printing_keys() {
+        let (cards, refs) = test_all_cards_db(
+            "query-printing-cards",
+            vec![Card::named("Lightning Bolt"), Card::named("Shock")],
+        );
+        //every test card has a 2015-frame, black-bordered printing; this is a second one
+        cards.add_printing(Printing {
+            card_ref: CardRef {
+                set: "OLD".to_string(),
+                collector_number: 1.into(),
+                printing: None,
+            },
+            card: refs[0].clone(),
+            artist: "Christopher Rush".to_string(),
+            flavor_text: "The sparkmage shrieked, calling on the rage of the storms of his youth.".to_string(),
+            watermark: Some("Mirran".to_string()),
+            frame: "1993".to_string(),
+            border: "white".to_string(),
+        });
+        let ctx = QueryContext::new(&cards);
+
+        assert_eq!(names("a:rush", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("artist!=rush", ctx), ["Shock"]);
+        assert_eq!(names("ft:sparkmage", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("wm:mirran", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("wm!=mirran", ctx), ["Shock"]);
+        assert_eq!(names("frame:1993", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("frame:2015", ctx), ["Lightning Bolt", "Shock"]);
+        //any printing counts, so != only matches cards never printed that way
+        assert_eq!(names("frame!=2015", ctx), Vec::<String>::new());
+        assert_eq!(names("border:white", ctx), ["Lightning Bolt"]);
+        assert_eq!(names("border!=white", ctx), ["Shock"]);
+    }
+
+    #[test]
+    pub fn test_parse_cents() {
+        assert_eq!(parse_cents("1"), Some(100));
+        assert_eq!(parse_cents("1.5"), Some(150));
+        assert_eq!(parse_cents("0.05"), Some(5));
+        assert_eq!(parse_cents(".25"), Some(25));
+        assert_eq!(parse_cents("1.234"), None);
+        assert_eq!(parse_cents("$1"), None);
+    }
 }
@@ -0,0 +1,175 @@
+use std::{
+    cell::OnceCell,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{
+    data_model::{
+        card::{Card, CardRef, PhysicalCard},
+        collection::CollectionEntry,
+        group::{CardGroup, CardGroupVariety, GroupId},
+        tag::TagTarget,
+    },
+    dbs::{
+        allcards::AllCardsDb,
+        collection::CollectionDb,
+        prices::PriceDb,
+        saving_decks::{GroupDb, allocation::Allocator},
+        tags::TagDb,
+    },
+};
+
+///
+/// Everything a query can look at while it's being run.
+/// Only the card DB is required; keys which need the others match nothing without them.
+#[derive(Clone, Copy)]
+pub struct QueryContext<'a> {
+    pub cards: &'a AllCardsDb,
+    pub prices: Option<&'a PriceDb>,
+    pub collection: Option<&'a CollectionDb>,
+    pub groups: Option<&'a GroupDb>,
+    pub tags: Option<&'a TagDb>,
+}
+
+impl<'a> QueryContext<'a> {
+    pub fn new(cards: &'a AllCardsDb) -> Self {
+        Self {
+            cards,
+            prices: None,
+            collection: None,
+            groups: None,
+            tags: None,
+        }
+    }
+
+    ///
+    /// Every printing of the card. The collection and groups can refer to any of them.
+    pub fn printing_refs(&self, card: &Card) -> HashSet<CardRef> {
+        self.cards
+            .printings(card)
+            .into_iter()
+            .map(|x| x.card_ref)
+            .collect()
+    }
+
+    /// The user's copies of the card, in any printing
+    pub fn owned_entries(&self, card: &Card) -> Vec<CollectionEntry> {
+        let Some(collection) = self.collection else {
+            return Vec::new();
+        };
+
+        self.printing_refs(card)
+            .iter()
+            .flat_map(|x| collection.entries_of(x))
+            .collect()
+    }
+
+    pub fn owned_copies(&self, card: &Card) -> Vec<PhysicalCard> {
+        self.owned_entries(card)
+            .into_iter()
+            .map(|x| x.card)
+            .collect()
+    }
+
+    ///
+    /// Tags on the card itself, and on any of the user's copies of it.
+    pub fn has_tag(&self, card: &Card, tag: &str) -> bool {
+        let Some(tags) = self.tags else {
+            return false;
+        };
+
+        self.cards
+            .card_ref(card)
+            .is_some_and(|x| tags.has_tag(&TagTarget::Card(x), tag))
+            || self
+                .owned_entries(card)
+                .iter()
+                .any(|x| tags.has_tag(&TagTarget::Copy(x.id), tag))
+    }
+
+    pub fn owned_count(&self, card: &Card) -> usize {
+        self.owned_copies(card).iter().map(|x| x.duplicates).sum()
+    }
+
+    ///
+    /// Owned copies which aren't reserved by an exclusive deck.
+    /// The reservations are `Allocator::reserved_tally`, so this agrees with `Allocator::available`.
+    pub fn available_count(&self, card: &Card, memo: &QueryMemo) -> usize {
+        let reserved = self
+            .cards
+            .card_ref(card)
+            .and_then(|x| memo.reserved(*self).get(&x).copied())
+            .unwrap_or_default();
+
+        self.owned_count(card).saturating_sub(reserved)
+    }
+}
+
+///
+/// What a query works out about the collection and groups, which is the same for every card.
+/// Each run of a query starts with an empty one, so it never goes stale.
+#[derive(Default)]
+pub struct QueryMemo {
+    reserved: OnceCell<HashMap<CardRef, usize>>,
+    groups: OnceCell<Vec<(GroupId, CardGroup)>>,
+}
+
+impl QueryMemo {
+    pub fn reserved(&self, ctx: QueryContext) -> &HashMap<CardRef, usize> {
+        self.reserved.get_or_init(|| match (ctx.collection, ctx.groups) {
+            (Some(collection), Some(groups)) => {
+                Allocator::new(ctx.cards, collection, groups).reserved_tally()
+            }
+            _ => HashMap::new(),
+        })
+    }
+
+    /// The same group as `GroupDb::find_by_name` would find
+    pub fn group(
+        &self,
+        ctx: QueryContext,
+        group_type: Option<CardGroupVariety>,
+        name: &str,
+    ) -> Option<&CardGroup> {
+        self.groups
+            .get_or_init(|| ctx.groups.map(|x| x.all()).unwrap_or_default())
+            .iter()
+            .filter(|(_, group)| group_type.is_none_or(|t| group.group_type == t))
+            .find(|(_, group)| group.name.eq_ignore_ascii_case(name))
+            .map(|(_, group)| group)
+    }
+}
+
+pub fn copies_in_group(group: &CardGroup, printings: &HashSet<CardRef>) -> usize {
+    group
+        .cards
+        .entries
+        .iter()
+        .filter(|x| x.section.is_played() && printings.contains(&x.card))
+        .map(|x| x.quantity)
+        .sum()
+}
+
+///
+/// Owned handles to the DBs, for sending over to the query thread.
+#[derive(Clone)]
+pub struct QuerySources {
+    pub cards: Arc<AllCardsDb>,
+    pub prices: Option<Arc<PriceDb>>,
+    pub collection: Option<Arc<CollectionDb>>,
+    pub groups: Option<Arc<GroupDb>>,
+    pub tags: Option<Arc<TagDb>>,
+}
+
+impl QuerySources {
+    pub fn context(&self) -> QueryContext<'_> {
+        QueryContext {
+            cards: &self.cards,
+            prices: self.prices.as_deref(),
+            collection: self.collection.as_deref(),
+            groups: self.groups.as_deref(),
+            tags: self.tags.as_deref(),
+        }
+    }
+}
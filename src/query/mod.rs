@@ -1,4 +1,5 @@
 pub mod compile;
+pub mod context;
 pub mod err_warn_support;
 mod lex;
 mod parse;
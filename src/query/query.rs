@@ -1,7 +1,8 @@
 use std::{
+    collections::HashSet,
     result,
     sync::{
-        Arc, Mutex,
+        Mutex,
         mpsc::{Receiver, Sender},
     },
 };
@@ -11,16 +12,14 @@ use nucleo_matcher::{
     pattern::{CaseMatching, Normalization, Pattern},
 };
 
-use crate::{
-    dbs::allcards::AllCardsDb,
-    query::{
-        compile::build_search_query,
-        err_warn_support::{self, Message, MessageSink},
-    },
+use crate::query::{
+    compile::build_search_query,
+    context::{QueryContext, QuerySources},
+    err_warn_support::{self, Message, MessageSink},
 };
 
 pub fn start_query_running_background_threads(
-    db: Arc<AllCardsDb>,
+    sources: QuerySources,
 ) -> (Sender<String>, Receiver<(Option<Message>, Vec<String>)>) {
     let (tx_query, rx_query) = std::sync::mpsc::channel::<String>();
     let (tx_results, rx_results) = std::sync::mpsc::channel();
@@ -33,7 +32,7 @@ pub fn start_query_running_background_threads(
                 break;
             };
 
-            let results = get_results(search.as_str(), &mut matcher, &db);
+            let results = get_results(search.as_str(), &mut matcher, sources.context());
 
             tx_results.send(results).unwrap();
         }
@@ -45,7 +44,7 @@ pub fn start_query_running_background_threads(
 fn get_results(
     search: &str,
     matcher: &mut Matcher,
-    db: &AllCardsDb,
+    ctx: QueryContext,
 ) -> (Option<Message>, Vec<String>) {
     struct ErrLineMessage<'s>(Mutex<&'s mut Option<Message>>);
     impl MessageSink for ErrLineMessage<'_> {
@@ -59,16 +58,26 @@ fn get_results(
 
     let query = build_search_query(&search, &errors);
 
+    let mut seen = HashSet::new();
+
     let results = match query {
-        Ok(query) => query.query_db(&db).map(|x| x.name).collect(),
+        Ok(query) => query.query_db(ctx).map(|x| x.name).collect(),
         Err(simple_search) => Pattern::parse(
             simple_search.as_str(),
             CaseMatching::Ignore,
             Normalization::Smart,
         )
-        .match_list(db.all_cards().map(|x| x.name), matcher)
+        .match_list(ctx.cards.all_cards().map(|x| x.name), matcher)
         .into_iter()
         .map(|x| x.0.to_owned())
+        .chain(
+            //fuzzy matching every name in every language would drown out the English
+            // results, so other languages only get substring matches, after the fuzzy ones.
+            ctx.cards
+                .query_foreign_name(simple_search.as_str())
+                .map(|x| x.name),
+        )
+        .filter(|name| seen.insert(name.clone()))
         .collect(),
     };
 